@@ -1,5 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use std::path::PathBuf;
+use wordnet_ls::wordnet::Loading;
 use wordnet_ls::wordnet::SemanticRelation;
 use wordnet_ls::wordnet::WordNet;
 
@@ -31,6 +32,14 @@ fn criterion_benchmark(c: &mut Criterion) {
             black_box(words)
         })
     });
+    c.bench_function("all_words_read", |b| {
+        b.iter(|| {
+            let wndir = std::env::var("WNSEARCHDIR").unwrap();
+            let wn = WordNet::with_loading(&PathBuf::from(wndir), Loading::Read).unwrap();
+            let len = wn.all_words().len();
+            black_box(len)
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
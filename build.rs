@@ -0,0 +1,62 @@
+//! With the `bundled` feature, compress the WordNet database into the build output so it can be
+//! embedded in the binary.
+
+fn main() {
+    #[cfg(feature = "bundled")]
+    bundled::compress();
+}
+
+#[cfg(feature = "bundled")]
+mod bundled {
+    use std::{env, fmt::Write as _, fs, io::Write as _, path::PathBuf};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    /// Same as `WordNet::file_names`.
+    const FILES: [&str; 12] = [
+        "data.noun",
+        "data.verb",
+        "data.adj",
+        "data.adv",
+        "index.noun",
+        "index.verb",
+        "index.adj",
+        "index.adv",
+        "noun.exc",
+        "verb.exc",
+        "adj.exc",
+        "adv.exc",
+    ];
+
+    pub fn compress() {
+        println!("cargo:rerun-if-env-changed=WORDNET_BUNDLE_DIR");
+        let dir = env::var_os("WORDNET_BUNDLE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("dict"));
+        let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+        let mut files = String::from("&[\n");
+        for name in FILES {
+            let path = dir.join(name);
+            println!("cargo:rerun-if-changed={}", path.display());
+            // a binary without the database would defeat the point of the feature
+            let content = fs::read(&path).unwrap_or_else(|e| {
+                panic!(
+                    "The bundled feature needs the WordNet 3.1 database in {dir:?} (or set \
+                     WORDNET_BUNDLE_DIR), failed to read {path:?}: {e}"
+                )
+            });
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(&content).unwrap();
+            let compressed = out.join(format!("{name}.gz"));
+            fs::write(&compressed, encoder.finish().unwrap()).unwrap();
+            writeln!(
+                files,
+                "    ({name:?}, include_bytes!({compressed:?}).as_slice()),"
+            )
+            .unwrap();
+        }
+        files.push(']');
+        fs::write(out.join("bundled.rs"), files).unwrap();
+    }
+}
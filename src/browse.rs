@@ -0,0 +1,925 @@
+use ratatui::backend::Backend;
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::KeyModifiers;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use ratatui::Terminal;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use wordnet_ls::wordnet::LexicalRelation;
+use wordnet_ls::wordnet::LexicalRelationship;
+use wordnet_ls::wordnet::SemanticRelation;
+use wordnet_ls::wordnet::SynSet;
+
+use crate::query::DatabaseArgs;
+use crate::Dict;
+use crate::InitializationOptions;
+
+/// Options for browsing the database.
+#[derive(Debug, Clone, clap::Args)]
+pub struct BrowseArgs {
+    /// Word to start at, searching for one when not given.
+    word: Option<String>,
+    #[clap(flatten)]
+    database: DatabaseArgs,
+}
+
+/// A relationship between synsets or between the lemmas in them.
+#[derive(Debug, Clone, Copy)]
+enum Relation {
+    Semantic(SemanticRelation),
+    Lexical(LexicalRelation),
+}
+
+/// The keys following relationships from the selected sense, with what the related senses are
+/// called.
+const FOLLOW: [(char, &str, &[Relation]); 12] = [
+    (
+        'h',
+        "hypernyms",
+        &[
+            Relation::Semantic(SemanticRelation::Hypernym),
+            Relation::Semantic(SemanticRelation::InstanceHypernym),
+        ],
+    ),
+    (
+        'y',
+        "hyponyms",
+        &[
+            Relation::Semantic(SemanticRelation::Hyponym),
+            Relation::Semantic(SemanticRelation::InstanceHyponym),
+        ],
+    ),
+    (
+        'm',
+        "meronyms",
+        &[
+            Relation::Semantic(SemanticRelation::MemberMeronym),
+            Relation::Semantic(SemanticRelation::SubstanceMeronym),
+            Relation::Semantic(SemanticRelation::PartMeronym),
+        ],
+    ),
+    (
+        'o',
+        "holonyms",
+        &[
+            Relation::Semantic(SemanticRelation::MemberHolonym),
+            Relation::Semantic(SemanticRelation::SubstanceHolonym),
+            Relation::Semantic(SemanticRelation::PartHolonym),
+        ],
+    ),
+    (
+        'a',
+        "antonyms",
+        &[Relation::Lexical(LexicalRelation::Antonym)],
+    ),
+    (
+        'd',
+        "derivations",
+        &[Relation::Lexical(
+            LexicalRelation::DerivationallyRelatedForm,
+        )],
+    ),
+    (
+        'p',
+        "pertainyms",
+        &[Relation::Lexical(LexicalRelation::Pertainym)],
+    ),
+    (
+        's',
+        "similar words",
+        &[Relation::Semantic(SemanticRelation::SimilarTo)],
+    ),
+    (
+        't',
+        "attributes",
+        &[Relation::Semantic(SemanticRelation::Attribute)],
+    ),
+    (
+        'e',
+        "entailments",
+        &[Relation::Semantic(SemanticRelation::Entailment)],
+    ),
+    (
+        'c',
+        "causes",
+        &[Relation::Semantic(SemanticRelation::Cause)],
+    ),
+    (
+        'r',
+        "see also",
+        &[
+            Relation::Semantic(SemanticRelation::AlsoSee),
+            Relation::Lexical(LexicalRelation::AlsoSee),
+        ],
+    ),
+];
+
+/// Number of words offered while searching.
+const SEARCH_LIMIT: usize = 50;
+
+/// A synset shown on a page, with the words it is related to.
+#[derive(Debug, Clone)]
+struct Sense {
+    /// The lemma the synset was found for, whose lexical relationships are shown, or all of them
+    /// if there is none.
+    lemma: Option<String>,
+    synset: SynSet,
+    /// The related words by what they are called, as shown in the `all_info` document.
+    relations: Vec<(String, Vec<String>)>,
+}
+
+impl Sense {
+    fn new(dict: &Dict, lemma: Option<String>, synset: SynSet) -> Self {
+        let mut relations = Vec::new();
+        // without a lemma all the synonyms are shown as the words of the sense
+        if let Some(lemma) = &lemma {
+            let synonyms = synset
+                .synonyms()
+                .into_iter()
+                .filter(|w| w != lemma)
+                .collect::<Vec<_>>();
+            if !synonyms.is_empty() {
+                relations.push(("synonyms".to_owned(), synonyms));
+            }
+        }
+        relations.extend(
+            dict.semantic_relations(&synset)
+                .into_iter()
+                .map(|(r, words)| (r.to_string(), words.into_iter().collect())),
+        );
+        let mut lexical = BTreeMap::<LexicalRelation, BTreeSet<String>>::new();
+        for r in lexical_relationships(&lemma, &synset) {
+            if let Some(word) = dict.resolve_lemma(r) {
+                lexical.entry(r.relation).or_default().insert(word);
+            }
+        }
+        relations.extend(
+            lexical
+                .into_iter()
+                .map(|(r, words)| (r.to_string(), words.into_iter().collect())),
+        );
+        Self {
+            lemma,
+            synset,
+            relations,
+        }
+    }
+
+    /// The words that can be followed, in the order they are shown.
+    fn links(&self) -> impl Iterator<Item = &String> {
+        self.relations.iter().flat_map(|(_, words)| words)
+    }
+
+    /// The senses related to this one, without repeats.
+    fn related(&self, dict: &Dict, relations: &[Relation]) -> Vec<Sense> {
+        let mut seen = BTreeSet::new();
+        let mut related = Vec::new();
+        for relation in relations {
+            match relation {
+                Relation::Semantic(relation) => {
+                    for r in self.synset.with_relationship(*relation) {
+                        if !seen.insert((r.part_of_speech, r.synset_offset, None)) {
+                            continue;
+                        }
+                        if let Some(synset) =
+                            dict.wordnet.resolve(r.part_of_speech, r.synset_offset)
+                        {
+                            related.push(Sense::new(dict, None, synset));
+                        }
+                    }
+                }
+                Relation::Lexical(relation) => {
+                    for r in lexical_relationships(&self.lemma, &self.synset)
+                        .filter(|r| r.relation == *relation)
+                    {
+                        if !seen.insert((r.part_of_speech, r.synset_offset, Some(r.target))) {
+                            continue;
+                        }
+                        let Some(synset) = dict.wordnet.resolve(r.part_of_speech, r.synset_offset)
+                        else {
+                            continue;
+                        };
+                        let lemma = synset.lemmas.get(r.target).map(|l| l.word.clone());
+                        related.push(Sense::new(dict, lemma, synset));
+                    }
+                }
+            }
+        }
+        related
+    }
+}
+
+/// The lexical relationships of the lemma in the synset, or of all its lemmas.
+fn lexical_relationships<'a>(
+    lemma: &'a Option<String>,
+    synset: &'a SynSet,
+) -> impl Iterator<Item = &'a LexicalRelationship> {
+    synset
+        .lemmas
+        .iter()
+        .filter(move |l| lemma.as_ref().is_none_or(|lemma| l.word == *lemma))
+        .flat_map(|l| &l.relationships)
+}
+
+/// What is shown at a point in the history.
+#[derive(Debug, Clone)]
+struct Page {
+    title: String,
+    senses: Vec<Sense>,
+    selected: usize,
+    /// The word selected within the selected sense.
+    link: Option<usize>,
+    scroll: usize,
+}
+
+impl Page {
+    fn new(title: String, senses: Vec<Sense>) -> Self {
+        Self {
+            title,
+            senses,
+            selected: 0,
+            link: None,
+            scroll: 0,
+        }
+    }
+
+    /// The senses of the lemmas of the word, `None` if it isn't in the database.
+    fn word(dict: &Dict, word: &str) -> Option<Self> {
+        let senses = dict
+            .all_synsets(word)
+            .into_iter()
+            .map(|(lemma, synset)| Sense::new(dict, Some(lemma), synset))
+            .collect::<Vec<_>>();
+        (!senses.is_empty()).then(|| Self::new(word.replace('_', " "), senses))
+    }
+
+    fn selected_link(&self) -> Option<&String> {
+        self.senses.get(self.selected)?.links().nth(self.link?)
+    }
+}
+
+/// Whether keys move around the pages or edit the search.
+#[derive(Debug, Clone)]
+enum Mode {
+    Browse,
+    Search {
+        query: String,
+        matches: Vec<String>,
+        selected: usize,
+    },
+}
+
+/// The state of the browser, changed by each key pressed.
+struct Browser<'a> {
+    dict: &'a Dict,
+    history: Vec<Page>,
+    /// The index of the page shown in the history, earlier pages are gone back to.
+    current: usize,
+    mode: Mode,
+    help: bool,
+    status: String,
+}
+
+impl<'a> Browser<'a> {
+    fn new(dict: &'a Dict, word: Option<&str>) -> Self {
+        let mut browser = Self {
+            dict,
+            history: Vec::new(),
+            current: 0,
+            mode: Mode::Browse,
+            help: false,
+            status: String::new(),
+        };
+        if let Some(word) = word {
+            browser.open_word(word);
+        }
+        // search for words like it instead of showing nothing
+        if browser.history.is_empty() {
+            browser.search(word.unwrap_or_default().to_owned());
+        }
+        browser
+    }
+
+    fn page(&self) -> Option<&Page> {
+        self.history.get(self.current)
+    }
+
+    fn page_mut(&mut self) -> Option<&mut Page> {
+        self.history.get_mut(self.current)
+    }
+
+    /// Show the page, dropping the pages that were gone back from.
+    fn open(&mut self, page: Page) {
+        self.history.truncate(self.current + 1);
+        self.history.push(page);
+        self.current = self.history.len() - 1;
+    }
+
+    fn open_word(&mut self, word: &str) {
+        let word = word.trim().replace(' ', "_");
+        match Page::word(self.dict, &word) {
+            Some(page) => self.open(page),
+            None => self.status = format!("{:?} isn't in the database", word.replace('_', " ")),
+        }
+    }
+
+    fn search(&mut self, query: String) {
+        let matches = self
+            .dict
+            .complete(&query.replace(' ', "_"), false, SEARCH_LIMIT)
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        self.mode = Mode::Search {
+            query,
+            matches,
+            selected: 0,
+        };
+    }
+
+    /// Follow the relationships from the selected sense to a page of the related senses.
+    fn follow(&mut self, name: &str, relations: &[Relation]) {
+        let Some(page) = self.page() else {
+            return;
+        };
+        let Some(sense) = page.senses.get(page.selected) else {
+            return;
+        };
+        let word = sense
+            .lemma
+            .clone()
+            .or_else(|| sense.synset.synonyms().into_iter().next())
+            .unwrap_or_default()
+            .replace('_', " ");
+        let related = sense.related(self.dict, relations);
+        if related.is_empty() {
+            self.status = format!("No {name} of {word:?} in this sense");
+            return;
+        }
+        let title = format!("{name} of {word} ({})", sense.synset.part_of_speech);
+        self.open(Page::new(title, related));
+    }
+
+    /// Handle the key, returning whether to keep browsing.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        self.status.clear();
+        if let Mode::Search {
+            query,
+            matches,
+            selected,
+        } = &mut self.mode
+        {
+            match key.code {
+                KeyCode::Esc if self.history.is_empty() => return false,
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Enter => {
+                    let word = matches.get(*selected).unwrap_or(query).clone();
+                    self.mode = Mode::Browse;
+                    self.open_word(&word);
+                    if self.history.is_empty() {
+                        self.search(word.replace('_', " "));
+                    }
+                }
+                KeyCode::Down => *selected = (*selected + 1).min(matches.len().saturating_sub(1)),
+                KeyCode::Up => *selected = selected.saturating_sub(1),
+                KeyCode::Backspace => {
+                    let mut query = query.clone();
+                    query.pop();
+                    self.search(query);
+                }
+                KeyCode::Char(c) => {
+                    let query = format!("{query}{c}");
+                    self.search(query);
+                }
+                _ => {}
+            }
+            return true;
+        }
+        if self.help {
+            self.help = false;
+            return !matches!(key.code, KeyCode::Char('q'));
+        }
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('?') => self.help = true,
+            KeyCode::Char('/') => self.search(String::new()),
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('b') => {
+                if self.current == 0 {
+                    self.status = "No earlier page".to_owned();
+                } else {
+                    self.current -= 1;
+                }
+            }
+            KeyCode::Right | KeyCode::Char('f') => {
+                if self.current + 1 >= self.history.len() {
+                    self.status = "No later page".to_owned();
+                } else {
+                    self.current += 1;
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(page) = self.page_mut() {
+                    page.selected = (page.selected + 1).min(page.senses.len() - 1);
+                    page.link = None;
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                if let Some(page) = self.page_mut() {
+                    page.selected = page.selected.saturating_sub(1);
+                    page.link = None;
+                }
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                if let Some(page) = self.page_mut() {
+                    let links = page.senses[page.selected].links().count();
+                    page.link = match (page.link, key.code) {
+                        _ if links == 0 => None,
+                        (None, KeyCode::Tab) => Some(0),
+                        (None, _) => Some(links - 1),
+                        (Some(link), KeyCode::Tab) => Some((link + 1) % links),
+                        (Some(link), _) => Some((link + links - 1) % links),
+                    };
+                    if links == 0 {
+                        self.status = "No words to follow in this sense".to_owned();
+                    }
+                }
+            }
+            KeyCode::Enter => match self.page().and_then(|page| page.selected_link()).cloned() {
+                Some(word) => self.open_word(&word),
+                None => self.status = "Press tab to select a word to follow".to_owned(),
+            },
+            KeyCode::Char(c) => {
+                if let Some((_, name, relations)) = FOLLOW.iter().find(|(key, _, _)| *key == c) {
+                    self.follow(name, relations);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        let title = match &self.mode {
+            Mode::Search { query, .. } => format!("/{query}"),
+            Mode::Browse => self.page().map_or_else(String::new, |page| {
+                format!(
+                    "{} [{}/{}]",
+                    page.title,
+                    self.current + 1,
+                    self.history.len()
+                )
+            }),
+        };
+        frame.render_widget(Paragraph::new(Span::styled(title, bold)), header);
+        let status = if self.status.is_empty() {
+            match self.mode {
+                Mode::Search { .. } => "enter open  ↑↓ select  esc cancel",
+                Mode::Browse => "? help  / search  tab word  enter open  b back  f forward  q quit",
+            }
+        } else {
+            &self.status
+        };
+        let dim = Style::new().fg(Color::DarkGray);
+        frame.render_widget(Paragraph::new(Span::styled(status.to_owned(), dim)), footer);
+
+        let lines = match &self.mode {
+            Mode::Search {
+                matches, selected, ..
+            } => {
+                let skip = (*selected + 1).saturating_sub(body.height as usize);
+                matches
+                    .iter()
+                    .enumerate()
+                    .skip(skip)
+                    .map(|(i, word)| {
+                        let style = if i == *selected {
+                            Style::new().add_modifier(Modifier::REVERSED)
+                        } else {
+                            Style::new()
+                        };
+                        Line::from(Span::styled(word.replace('_', " "), style))
+                    })
+                    .collect()
+            }
+            Mode::Browse if self.help => help(),
+            Mode::Browse => {
+                let (width, height) = (body.width as usize, body.height as usize);
+                match self.page_mut() {
+                    Some(page) => render(page, width, height),
+                    None => Vec::new(),
+                }
+            }
+        };
+        frame.render_widget(Paragraph::new(lines), body);
+    }
+}
+
+/// The keys and what they do.
+fn help() -> Vec<Line<'static>> {
+    let navigation = [
+        ("↑ ↓ j k", "select a sense"),
+        ("tab", "select a word in the sense"),
+        ("enter", "open the selected word"),
+        ("/", "search for a word"),
+        ("← b", "go back"),
+        ("→ f", "go forward"),
+        ("q", "quit"),
+    ];
+    let follow = FOLLOW
+        .iter()
+        .map(|(key, name, _)| (key.to_string(), format!("follow to the {name}")));
+    navigation
+        .into_iter()
+        .map(|(key, action)| (key.to_owned(), action.to_owned()))
+        .chain(follow)
+        .map(|(key, action)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{key:>8}  "),
+                    Style::new().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(action),
+            ])
+        })
+        .collect()
+}
+
+/// The lines of the page that fit in the height, scrolling to keep the selection in view.
+fn render(page: &mut Page, width: usize, height: usize) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut selection = 0..0;
+    let mut link_line = None;
+    let link_style = Style::new().fg(Color::Cyan);
+    for (i, sense) in page.senses.iter().enumerate() {
+        let selected = i == page.selected;
+        let start = lines.len();
+        let synset = &sense.synset;
+        let words = match &sense.lemma {
+            Some(lemma) => lemma.replace('_', " "),
+            None => synset
+                .synonyms()
+                .iter()
+                .map(|w| w.replace('_', " "))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let number = if selected {
+            Style::new().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::new()
+        };
+        let mut units = vec![
+            vec![Span::styled(format!("{}.", i + 1), number)],
+            vec![Span::styled(
+                words,
+                Style::new().add_modifier(Modifier::BOLD),
+            )],
+            vec![Span::styled(
+                format!("({})", synset.part_of_speech),
+                Style::new().add_modifier(Modifier::ITALIC),
+            )],
+        ];
+        let mut text = format!("{}.", synset.definition);
+        if !synset.examples.is_empty() {
+            text.push_str(&format!(" e.g. {}.", synset.examples.join("; ")));
+        }
+        units.extend(
+            text.split_whitespace()
+                .map(|w| vec![Span::raw(w.to_owned())]),
+        );
+        lines.extend(wrap(units, width, 0, 3));
+
+        let mut link = 0;
+        for (name, words) in &sense.relations {
+            let mut units = vec![vec![Span::styled(
+                format!("{name}:"),
+                Style::new().add_modifier(Modifier::BOLD),
+            )]];
+            let mut selected_unit = None;
+            for (j, word) in words.iter().enumerate() {
+                let style = if selected && page.link == Some(link) {
+                    selected_unit = Some(units.len());
+                    link_style.add_modifier(Modifier::REVERSED)
+                } else {
+                    link_style
+                };
+                let mut unit = vec![Span::styled(word.replace('_', " "), style)];
+                if j + 1 < words.len() {
+                    unit.push(Span::raw(","));
+                }
+                units.push(unit);
+                link += 1;
+            }
+            let wrapped = wrap_units(units, width, 3, 5);
+            if let Some(unit) = selected_unit {
+                let offset = wrapped.iter().position(|(_, last)| *last >= unit);
+                link_line = offset.map(|offset| lines.len() + offset);
+            }
+            lines.extend(wrapped.into_iter().map(|(line, _)| line));
+        }
+        if selected {
+            selection = start..lines.len();
+        }
+        lines.push(Line::default());
+    }
+
+    // show as much of the selected sense as fits, and the selected word
+    if selection.end > page.scroll + height {
+        page.scroll = selection.start.min(selection.end.saturating_sub(height));
+    }
+    if selection.start < page.scroll {
+        page.scroll = selection.start;
+    }
+    if let Some(line) = link_line {
+        if line >= page.scroll + height {
+            page.scroll = line + 1 - height;
+        }
+    }
+    lines.into_iter().skip(page.scroll).take(height).collect()
+}
+
+/// Lay out the units in lines of the width with spaces between them, breaking lines only between
+/// units and indenting them by the first or the rest of the indent.
+fn wrap(
+    units: Vec<Vec<Span<'static>>>,
+    width: usize,
+    first: usize,
+    rest: usize,
+) -> Vec<Line<'static>> {
+    wrap_units(units, width, first, rest)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Like [`wrap`], along with the index of the last unit on each line.
+fn wrap_units(
+    units: Vec<Vec<Span<'static>>>,
+    width: usize,
+    first: usize,
+    rest: usize,
+) -> Vec<(Line<'static>, usize)> {
+    let mut lines = Vec::new();
+    let mut line = vec![Span::raw(" ".repeat(first))];
+    let mut len = first;
+    let mut empty = true;
+    for (i, unit) in units.into_iter().enumerate() {
+        let unit_len = unit.iter().map(Span::width).sum::<usize>();
+        if !empty && len + 1 + unit_len > width {
+            lines.push((Line::from(std::mem::take(&mut line)), i - 1));
+            line.push(Span::raw(" ".repeat(rest)));
+            len = rest;
+            empty = true;
+        }
+        if !empty {
+            line.push(Span::raw(" "));
+            len += 1;
+        }
+        len += unit_len;
+        line.extend(unit);
+        empty = false;
+    }
+    if !empty {
+        lines.push((Line::from(line), usize::MAX));
+    }
+    lines
+}
+
+/// Browse the database in the terminal until quitting.
+///
+/// Failing to load the database or to use the terminal is an error.
+pub fn run(args: BrowseArgs) -> Result<(), String> {
+    let dict = args.database.load(&InitializationOptions::default())?;
+    let mut browser = Browser::new(&dict, args.word.as_deref());
+    let mut terminal = ratatui::try_init().map_err(|err| err.to_string())?;
+    let browsed = browse(&mut terminal, &mut browser);
+    ratatui::restore();
+    browsed.map_err(|err| err.to_string())
+}
+
+fn browse(terminal: &mut Terminal<impl Backend>, browser: &mut Browser) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|frame| browser.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !browser.key(key) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+    use ratatui::backend::TestBackend;
+
+    /// Press the keys, then draw the browser as lines of text.
+    fn screen(browser: &mut Browser, keys: &[KeyCode]) -> String {
+        for key in keys {
+            assert!(browser.key(KeyEvent::from(*key)));
+        }
+        let mut terminal = Terminal::new(TestBackend::new(48, 14)).unwrap();
+        terminal.draw(|frame| browser.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn follow_relationships() {
+        let (_dir, dict) = crate::fixture::dogs();
+        let mut browser = Browser::new(&dict, Some("dogs"));
+        let expected = expect![[r#"
+            dogs [1/1]
+            1. dog (noun) a member of the genus Canis that
+               has been domesticated by man since
+               prehistoric times. e.g. the dog barked all
+               night.
+               synonyms: domestic dog
+               hypernym: canid, canine
+               part meronym: paw
+               antonym: cat
+               derivationally related form: dog
+
+            2. dog (verb) follow closely.
+               derivationally related form: dog
+            ? help  / search  tab word  enter open  b back"#]];
+        expected.assert_eq(&screen(&mut browser, &[]));
+
+        let expected = expect![[r#"
+            hypernyms of dog (noun) [2/2]
+            1. canine, canid (noun) a carnivore.
+               hyponym: dog, domestic dog
+
+
+
+
+
+
+
+
+
+
+            ? help  / search  tab word  enter open  b back"#]];
+        expected.assert_eq(&screen(&mut browser, &[KeyCode::Char('h')]));
+
+        let expected = expect![[r#"
+            dog [3/3]
+            1. dog (noun) a member of the genus Canis that
+               has been domesticated by man since
+               prehistoric times. e.g. the dog barked all
+               night.
+               synonyms: domestic dog
+               hypernym: canid, canine
+               part meronym: paw
+               antonym: cat
+               derivationally related form: dog
+
+            2. dog (verb) follow closely.
+               derivationally related form: dog
+            ? help  / search  tab word  enter open  b back"#]];
+        expected.assert_eq(&screen(&mut browser, &[KeyCode::Tab, KeyCode::Enter]));
+
+        let expected = expect![[r#"
+            antonyms of dog (noun) [2/2]
+            1. cat (noun) a mewing animal.
+
+
+
+
+
+
+
+
+
+
+
+            ? help  / search  tab word  enter open  b back"#]];
+        expected.assert_eq(&screen(
+            &mut browser,
+            &[KeyCode::Left, KeyCode::Left, KeyCode::Char('a')],
+        ));
+
+        let expected = expect![[r#"
+            antonyms of dog (noun) [2/2]
+            1. cat (noun) a mewing animal.
+
+
+
+
+
+
+
+
+
+
+
+            No later page"#]];
+        expected.assert_eq(&screen(
+            &mut browser,
+            &[
+                KeyCode::Left,
+                KeyCode::Right,
+                KeyCode::Left,
+                KeyCode::Down,
+                KeyCode::Right,
+                KeyCode::Right,
+            ],
+        ));
+    }
+
+    #[test]
+    fn search() {
+        let (_dir, dict) = crate::fixture::dogs();
+        let mut browser = Browser::new(&dict, None);
+        let expected = expect![[r#"
+            /ca
+            canid
+            canine
+            cat
+
+
+
+
+
+
+
+
+
+            enter open  ↑↓ select  esc cancel"#]];
+        expected.assert_eq(&screen(
+            &mut browser,
+            &[KeyCode::Char('c'), KeyCode::Char('a')],
+        ));
+
+        let expected = expect![[r#"
+            cat [1/1]
+            1. cat (noun) a mewing animal.
+
+
+
+
+
+
+
+
+
+
+
+            ? help  / search  tab word  enter open  b back"#]];
+        expected.assert_eq(&screen(
+            &mut browser,
+            &[KeyCode::Down, KeyCode::Down, KeyCode::Down, KeyCode::Enter],
+        ));
+
+        let expected = expect![[r#"
+            cat [1/1]
+            1. cat (noun) a mewing animal.
+
+
+
+
+
+
+
+
+
+
+
+            No hypernyms of "cat" in this sense"#]];
+        expected.assert_eq(&screen(&mut browser, &[KeyCode::Char('h')]));
+    }
+}
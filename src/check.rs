@@ -0,0 +1,452 @@
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::NumberOrString;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::diagnostics;
+use crate::diagnostics::DiagnosticData;
+use crate::diagnostics::Region;
+use crate::prose;
+use crate::prose::Syntax;
+use crate::query::DatabaseArgs;
+use crate::Dict;
+use crate::InitializationOptions;
+
+/// How the findings are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// A line for each finding, as compilers print them.
+    Human,
+    /// A line of JSON for each finding.
+    Json,
+    /// A SARIF log, for code scanning services.
+    Sarif,
+}
+
+/// Options for checking documents.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CheckArgs {
+    /// Files to check, whose language is taken from their extension.
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+    /// How to print the findings.
+    #[clap(long, value_enum, default_value_t = Format::Human)]
+    format: Format,
+    /// JSON file of the options, as given to the server in `initializationOptions`.
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Locale that words should belong to, e.g. en-GB, overriding the options.
+    #[clap(long)]
+    locale: Option<String>,
+    /// Usage domain to flag, can be given more than once, overriding the options.
+    #[clap(long = "usage-domain")]
+    usage_domains: Vec<String>,
+    #[clap(flatten)]
+    database: DatabaseArgs,
+}
+
+/// What the documents are checked against, resolved from the options.
+struct Rules {
+    usage_domains: Vec<String>,
+    locale: Option<Region>,
+    languages: BTreeMap<String, Syntax>,
+}
+
+impl Rules {
+    fn new(options: &InitializationOptions) -> Result<Self, String> {
+        let usage_domains = options.usage_domains.clone().unwrap_or_else(|| {
+            diagnostics::DEFAULT_USAGE_DOMAINS
+                .iter()
+                .map(|d| d.to_string())
+                .collect()
+        });
+        let locale = match &options.locale {
+            Some(locale) => Some(Region::from_locale(locale).ok_or_else(|| {
+                format!("Unsupported locale {locale:?}, expected en-GB or en-US")
+            })?),
+            None => None,
+        };
+        Ok(Self {
+            usage_domains,
+            locale,
+            languages: options.languages.clone().unwrap_or_default(),
+        })
+    }
+}
+
+/// A diagnostic found in a file, with lines and columns counted from 1 in chars.
+#[derive(Debug, Serialize)]
+struct Finding {
+    path: String,
+    line: u32,
+    column: u32,
+    end_line: u32,
+    end_column: u32,
+    severity: &'static str,
+    code: String,
+    message: String,
+    replacements: Vec<String>,
+}
+
+impl Finding {
+    fn new(path: &Path, diagnostic: Diagnostic) -> Self {
+        let severity = match diagnostic.severity {
+            Some(DiagnosticSeverity::ERROR) => "error",
+            Some(DiagnosticSeverity::WARNING) => "warning",
+            Some(DiagnosticSeverity::HINT) => "hint",
+            _ => "information",
+        };
+        let code = match diagnostic.code {
+            Some(NumberOrString::String(code)) => code,
+            Some(NumberOrString::Number(code)) => code.to_string(),
+            None => String::new(),
+        };
+        let replacements = diagnostic
+            .data
+            .and_then(|d| serde_json::from_value::<DiagnosticData>(d).ok())
+            .unwrap_or_default()
+            .replacements;
+        let range = diagnostic.range;
+        Self {
+            // forward slashes so that the output is the same on every platform
+            path: path.to_string_lossy().replace('\\', "/"),
+            line: range.start.line + 1,
+            column: range.start.character + 1,
+            end_line: range.end.line + 1,
+            end_column: range.end.character + 1,
+            severity,
+            code,
+            message: diagnostic.message,
+            replacements,
+        }
+    }
+}
+
+/// Check the files, printing what was found and returning whether anything was, as `lint` does.
+///
+/// Failing to load the database or the options, or to read a file, is an error.
+pub fn run(args: CheckArgs) -> Result<bool, String> {
+    let mut options = match &args.config {
+        Some(path) => {
+            let config = std::fs::read_to_string(path)
+                .map_err(|err| format!("Failed to read {path:?}: {err}"))?;
+            serde_json::from_str::<InitializationOptions>(&config)
+                .map_err(|err| format!("Invalid options in {path:?}: {err}"))?
+        }
+        None => InitializationOptions::default(),
+    };
+    if args.locale.is_some() {
+        options.locale = args.locale;
+    }
+    if !args.usage_domains.is_empty() {
+        options.usage_domains = Some(args.usage_domains);
+    }
+    let rules = Rules::new(&options)?;
+    let dict = args.database.load(&options)?;
+    let mut findings = Vec::new();
+    for path in &args.files {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {path:?}: {err}"))?;
+        findings.extend(check(&dict, &rules, path, &text));
+    }
+    let output = match args.format {
+        Format::Human => human(&findings, args.files.len()),
+        Format::Json => json(&findings)?,
+        Format::Sarif => {
+            serde_json::to_string_pretty(&sarif(&findings)).map_err(|err| err.to_string())?
+        }
+    };
+    if !output.is_empty() {
+        println!("{output}");
+    }
+    Ok(!findings.is_empty())
+}
+
+/// The findings in the prose of the file.
+fn check(dict: &Dict, rules: &Rules, path: &Path, text: &str) -> Vec<Finding> {
+    let language = prose::language_id(path)
+        .and_then(|language_id| prose::language(&rules.languages, &language_id));
+    let content = match language {
+        Some(language) => prose::extract(text, &language),
+        None => text.to_owned(),
+    };
+    let mut diagnostics = diagnostics::analyse(dict, &content, &rules.usage_domains, rules.locale);
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.range.start, diagnostic.range.end));
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| Finding::new(path, diagnostic))
+        .collect()
+}
+
+/// A line for each finding followed by a summary.
+fn human(findings: &[Finding], files: usize) -> String {
+    let mut lines = findings
+        .iter()
+        .map(|f| {
+            let mut line = format!(
+                "{}:{}:{}: {}: {} [{}]",
+                f.path, f.line, f.column, f.severity, f.message, f.code
+            );
+            if !f.replacements.is_empty() {
+                let replacements = f
+                    .replacements
+                    .iter()
+                    .map(|r| format!("{r:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                line.push_str(&format!("\n  replace with: {replacements}"));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+    lines.push(format!(
+        "{} in {}",
+        plural(findings.len(), "finding"),
+        plural(files, "file")
+    ));
+    lines.join("\n")
+}
+
+/// A line of JSON for each finding.
+fn json(findings: &[Finding]) -> Result<String, String> {
+    findings
+        .iter()
+        .map(|finding| serde_json::to_string(finding).map_err(|err| err.to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// A SARIF 2.1.0 log of the findings, with a rule for each kind of diagnostic and a fix for each
+/// replacement.
+fn sarif(findings: &[Finding]) -> serde_json::Value {
+    let rules = [
+        (
+            "register",
+            "Words only used in the configured usage domains, such as slang",
+        ),
+        (
+            "region",
+            "Words specific to another English speaking region",
+        ),
+    ]
+    .map(|(id, description)| {
+        serde_json::json!({
+            "id": id,
+            "shortDescription": { "text": description },
+        })
+    });
+    let results = findings
+        .iter()
+        .map(|f| {
+            let location = serde_json::json!({ "uri": f.path });
+            let region = serde_json::json!({
+                "startLine": f.line,
+                "startColumn": f.column,
+                "endLine": f.end_line,
+                "endColumn": f.end_column,
+            });
+            let fixes = f
+                .replacements
+                .iter()
+                .map(|replacement| {
+                    serde_json::json!({
+                        "description": { "text": format!("Replace with {replacement:?}") },
+                        "artifactChanges": [{
+                            "artifactLocation": location,
+                            "replacements": [{
+                                "deletedRegion": region,
+                                "insertedContent": { "text": replacement },
+                            }],
+                        }],
+                    })
+                })
+                .collect::<Vec<_>>();
+            let level = match f.severity {
+                "error" => "error",
+                "warning" => "warning",
+                _ => "note",
+            };
+            serde_json::json!({
+                "ruleId": f.code,
+                "level": level,
+                "message": { "text": f.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": location,
+                        "region": region,
+                    },
+                }],
+                "fixes": fixes,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": diagnostics::SOURCE,
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    fn findings() -> Vec<Finding> {
+        let rules = Rules::new(&InitializationOptions {
+            locale: Some("en-US".to_owned()),
+            ..Default::default()
+        })
+        .unwrap();
+        let (_dir, dict) = crate::fixture::usage();
+        let markdown = "# A `bloke`\n\nThe bloke liked the colour.\n";
+        let rust = "// Colour it in\nlet colour = \"bloke\";\n";
+        [("docs/man.md", markdown), ("src/lib.rs", rust)]
+            .into_iter()
+            .flat_map(|(path, text)| check(&dict, &rules, Path::new(path), text))
+            .collect()
+    }
+
+    #[test]
+    fn finds() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[clap(flatten)]
+            check: CheckArgs,
+        }
+        let (dir, _dict) = crate::fixture::usage();
+        let run = |text: &str| {
+            let file = dir.path().join("doc.md");
+            std::fs::write(&file, text).unwrap();
+            let wordnet = dir.path().to_str().unwrap();
+            let args = ["check", "--locale", "en-US", "--wordnet", wordnet];
+            let cli =
+                <Cli as clap::Parser>::parse_from(args.into_iter().chain([file.to_str().unwrap()]));
+            run(cli.check)
+        };
+        assert_eq!(run("The man liked the color.\n"), Ok(false));
+        assert_eq!(run("The bloke liked the colour.\n"), Ok(true));
+    }
+
+    #[test]
+    fn formats() {
+        let findings = findings();
+        let expected = expect![[r#"
+            docs/man.md:3:5: information: "bloke" is only used as: slang [register]
+              replace with: "man"
+            docs/man.md:3:21: information: "colour" is British English, expected American English [region]
+              replace with: "color"
+            src/lib.rs:1:4: information: "Colour" is British English, expected American English [region]
+              replace with: "Color"
+            src/lib.rs:2:15: information: "bloke" is only used as: slang [register]
+              replace with: "man"
+            4 findings in 2 files"#]];
+        expected.assert_eq(&human(&findings, 2));
+        let expected = expect![[r#"
+            {"path":"docs/man.md","line":3,"column":5,"end_line":3,"end_column":10,"severity":"information","code":"register","message":"\"bloke\" is only used as: slang","replacements":["man"]}
+            {"path":"docs/man.md","line":3,"column":21,"end_line":3,"end_column":27,"severity":"information","code":"region","message":"\"colour\" is British English, expected American English","replacements":["color"]}
+            {"path":"src/lib.rs","line":1,"column":4,"end_line":1,"end_column":10,"severity":"information","code":"region","message":"\"Colour\" is British English, expected American English","replacements":["Color"]}
+            {"path":"src/lib.rs","line":2,"column":15,"end_line":2,"end_column":20,"severity":"information","code":"register","message":"\"bloke\" is only used as: slang","replacements":["man"]}"#]];
+        expected.assert_eq(&json(&findings).unwrap());
+    }
+
+    #[test]
+    fn sarif_log() {
+        let findings = findings();
+        let log = sarif(&findings[..1]);
+        let expected = expect![[r#"
+            {
+              "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+              "runs": [
+                {
+                  "columnKind": "unicodeCodePoints",
+                  "results": [
+                    {
+                      "fixes": [
+                        {
+                          "artifactChanges": [
+                            {
+                              "artifactLocation": {
+                                "uri": "docs/man.md"
+                              },
+                              "replacements": [
+                                {
+                                  "deletedRegion": {
+                                    "endColumn": 10,
+                                    "endLine": 3,
+                                    "startColumn": 5,
+                                    "startLine": 3
+                                  },
+                                  "insertedContent": {
+                                    "text": "man"
+                                  }
+                                }
+                              ]
+                            }
+                          ],
+                          "description": {
+                            "text": "Replace with \"man\""
+                          }
+                        }
+                      ],
+                      "level": "note",
+                      "locations": [
+                        {
+                          "physicalLocation": {
+                            "artifactLocation": {
+                              "uri": "docs/man.md"
+                            },
+                            "region": {
+                              "endColumn": 10,
+                              "endLine": 3,
+                              "startColumn": 5,
+                              "startLine": 3
+                            }
+                          }
+                        }
+                      ],
+                      "message": {
+                        "text": "\"bloke\" is only used as: slang"
+                      },
+                      "ruleId": "register"
+                    }
+                  ],
+                  "tool": {
+                    "driver": {
+                      "name": "wordnet-ls",
+                      "rules": [
+                        {
+                          "id": "register",
+                          "shortDescription": {
+                            "text": "Words only used in the configured usage domains, such as slang"
+                          }
+                        },
+                        {
+                          "id": "region",
+                          "shortDescription": {
+                            "text": "Words specific to another English speaking region"
+                          }
+                        }
+                      ]
+                    }
+                  }
+                }
+              ],
+              "version": "2.1.0"
+            }"#]];
+        expected.assert_eq(&serde_json::to_string_pretty(&log).unwrap());
+    }
+}
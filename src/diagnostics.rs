@@ -0,0 +1,623 @@
+use lsp_types::CodeAction;
+use lsp_types::CodeActionKind;
+use lsp_types::CodeActionOrCommand;
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::NumberOrString;
+use lsp_types::Position;
+use lsp_types::Range;
+use lsp_types::TextEdit;
+use lsp_types::Url;
+use lsp_types::WorkspaceEdit;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use wordnet_ls::wordnet::word_ranges;
+use wordnet_ls::wordnet::Token;
+
+use crate::Dict;
+
+pub const SOURCE: &str = "wordnet-ls";
+
+/// Usage domains that are flagged when no usage domains are configured.
+pub const DEFAULT_USAGE_DOMAINS: [&str; 3] = ["slang", "colloquialism", "archaism"];
+
+/// Extra information attached to diagnostics so that code actions can be computed without
+/// needing to redo the analysis.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DiagnosticData {
+    pub replacements: Vec<String>,
+}
+
+/// Run all the analyses over the prose of a document, as the server publishes them.
+pub fn analyse(
+    dict: &Dict,
+    content: &str,
+    usage_domains: &[String],
+    locale: Option<Region>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = register(dict, content, usage_domains);
+    diagnostics.extend(regional(dict, content, locale));
+    diagnostics
+}
+
+/// Flag words where every sense is marked with one of the given usage domains.
+pub fn register(dict: &Dict, content: &str, usage_domains: &[String]) -> Vec<Diagnostic> {
+    let mut cache = HashMap::new();
+    let mut diagnostics = Vec::new();
+    for (range, token) in get_tokens_with_ranges(dict, content) {
+        let lower = token.key();
+        let word = token.text;
+        let found = cache
+            .entry(lower.clone())
+            .or_insert_with(|| {
+                dict.usage_domains(&lower, usage_domains).map(|domains| {
+                    let replacements = dict.neutral_alternatives(&lower, usage_domains);
+                    (domains, replacements)
+                })
+            })
+            .clone();
+        let Some((domains, replacements)) = found else {
+            continue;
+        };
+        let capitalise = word.chars().next().is_some_and(|c| c.is_uppercase());
+        let replacements = replacements
+            .into_iter()
+            .map(|r| {
+                let r = r.replace('_', " ");
+                if capitalise {
+                    capitalise_first(&r)
+                } else {
+                    r
+                }
+            })
+            .collect::<Vec<_>>();
+        let domains = domains.into_iter().collect::<Vec<_>>().join(", ");
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            code: Some(NumberOrString::String("register".to_owned())),
+            source: Some(SOURCE.to_owned()),
+            message: format!("{word:?} is only used as: {domains}"),
+            data: serde_json::to_value(DiagnosticData { replacements }).ok(),
+            ..Default::default()
+        });
+    }
+    diagnostics
+}
+
+/// An English speaking region that words can be specific to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Region {
+    British,
+    American,
+}
+
+impl Region {
+    /// Parse a locale such as `en-GB` or `en_US`.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        match locale.to_lowercase().replace('_', "-").as_str() {
+            "en-gb" | "gb" | "uk" | "british" => Some(Region::British),
+            "en-us" | "us" | "american" => Some(Region::American),
+            _ => None,
+        }
+    }
+
+    /// Whether the name of a region domain synset refers to this region.
+    pub fn matches_domain(&self, domain: &str) -> bool {
+        let names: &[&str] = match self {
+            Region::British => &[
+                "united_kingdom",
+                "uk",
+                "u.k.",
+                "britain",
+                "great_britain",
+                "united_kingdom_of_great_britain_and_northern_ireland",
+            ],
+            Region::American => &[
+                "united_states",
+                "united_states_of_america",
+                "us",
+                "u.s.",
+                "usa",
+                "u.s.a.",
+                "america",
+            ],
+        };
+        names.contains(&domain.to_lowercase().as_str())
+    }
+
+    pub fn other(&self) -> Self {
+        match self {
+            Region::British => Region::American,
+            Region::American => Region::British,
+        }
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Region::British => f.write_str("British English"),
+            Region::American => f.write_str("American English"),
+        }
+    }
+}
+
+/// Pairs of British and American spellings that commonly differ.
+///
+/// Only inflections double the l in British English, e.g. travelled, as some stems double it in
+/// American English instead, e.g. fulfill.
+const SPELLING_VARIANTS: [(&str, &str); 13] = [
+    ("our", "or"),
+    ("tre", "ter"),
+    ("ise", "ize"),
+    ("isation", "ization"),
+    ("yse", "yze"),
+    ("ogue", "og"),
+    ("ae", "e"),
+    ("oe", "e"),
+    ("lled", "led"),
+    ("lling", "ling"),
+    ("ller", "ler"),
+    ("ence", "ense"),
+    ("mme", "m"),
+];
+
+/// Candidate spellings of the word in the given region, these are not guaranteed to be words.
+pub fn spelling_candidates(word: &str, region: Region) -> Vec<String> {
+    let mut candidates = Vec::new();
+    for (british, american) in SPELLING_VARIANTS {
+        let (from, to) = match region {
+            Region::British => (american, british),
+            Region::American => (british, american),
+        };
+        for (i, _) in word.match_indices(from) {
+            let mut candidate = word[..i].to_owned();
+            candidate.push_str(to);
+            candidate.push_str(&word[i + from.len()..]);
+            candidates.push(candidate);
+        }
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// A word that is specific to a region along with the equivalent words in the other region.
+#[derive(Debug, Clone)]
+pub struct RegionalVariant {
+    pub region: Region,
+    pub alternatives: Vec<String>,
+}
+
+/// Flag words that do not belong to the locale, or the most used region in the document if there
+/// is no locale.
+pub fn regional(dict: &Dict, content: &str, locale: Option<Region>) -> Vec<Diagnostic> {
+    let mut cache = HashMap::new();
+    let words = get_tokens_with_ranges(dict, content)
+        .into_iter()
+        .filter_map(|(range, token)| {
+            let lower = token.key();
+            let word = token.text;
+            let variant = cache
+                .entry(lower.clone())
+                .or_insert_with(|| dict.regional_variant(&lower))
+                .clone()?;
+            Some((range, word, variant))
+        })
+        .collect::<Vec<_>>();
+    regional_diagnostics(words, locale)
+}
+
+fn regional_diagnostics(
+    words: Vec<(Range, String, RegionalVariant)>,
+    locale: Option<Region>,
+) -> Vec<Diagnostic> {
+    let count = |region| words.iter().filter(|(_, _, v)| v.region == region).count();
+    let british = count(Region::British);
+    let american = count(Region::American);
+    let target = match locale {
+        Some(locale) => Some(locale),
+        // a document using only one region is consistent
+        None if british == 0 || american == 0 => return Vec::new(),
+        None => match british.cmp(&american) {
+            std::cmp::Ordering::Less => Some(Region::American),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(Region::British),
+        },
+    };
+    words
+        .into_iter()
+        .filter(|(_, _, variant)| Some(variant.region) != target)
+        .map(|(range, word, variant)| {
+            let capitalise = word.chars().next().is_some_and(|c| c.is_uppercase());
+            let replacements = variant
+                .alternatives
+                .iter()
+                .map(|r| {
+                    let r = r.replace('_', " ");
+                    if capitalise {
+                        capitalise_first(&r)
+                    } else {
+                        r
+                    }
+                })
+                .collect::<Vec<_>>();
+            let message = match target {
+                Some(target) => format!("{word:?} is {}, expected {target}", variant.region),
+                None => format!(
+                    "{word:?} is {}, mixed with {} in this document",
+                    variant.region,
+                    variant.region.other()
+                ),
+            };
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                code: Some(NumberOrString::String("region".to_owned())),
+                source: Some(SOURCE.to_owned()),
+                message,
+                data: serde_json::to_value(DiagnosticData { replacements }).ok(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Build the quick fixes for our diagnostics, replacing the flagged word with each suggestion.
+pub fn quick_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    for diagnostic in diagnostics {
+        if diagnostic.source.as_deref() != Some(SOURCE) {
+            continue;
+        }
+        let Some(data) = diagnostic
+            .data
+            .clone()
+            .and_then(|d| serde_json::from_value::<DiagnosticData>(d).ok())
+        else {
+            continue;
+        };
+        for replacement in data.replacements {
+            let edit = TextEdit {
+                range: diagnostic.range,
+                new_text: replacement.clone(),
+            };
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with {replacement:?}"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
+        }
+    }
+    actions
+}
+
+/// Find all words in the content along with their ranges, split by [`word_ranges`] as the
+/// tokens of [`crate::get_words_from_tokens`] are, character positions are counted in chars.
+pub fn get_words_with_ranges(content: &str) -> Vec<(Range, String)> {
+    let mut words = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        for range in word_ranges(line) {
+            words.push((
+                line_range(line_number, line, range.clone()),
+                line[range].to_owned(),
+            ));
+        }
+    }
+    words
+}
+
+/// Tokenize the content line by line, joining collocations, along with their ranges.
+pub fn get_tokens_with_ranges(dict: &Dict, content: &str) -> Vec<(Range, Token)> {
+    let mut tokens = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        for token in dict.wordnet.tokenize(line) {
+            tokens.push((line_range(line_number, line, token.range.clone()), token));
+        }
+    }
+    tokens
+}
+
+/// Convert a byte range within a line to a range of chars.
+fn line_range(line_number: usize, line: &str, range: std::ops::Range<usize>) -> Range {
+    let start = line[..range.start].chars().count();
+    let end = start + line[range].chars().count();
+    Range {
+        start: Position::new(line_number as u32, start as u32),
+        end: Position::new(line_number as u32, end as u32),
+    }
+}
+
+fn capitalise_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn words_with_ranges() {
+        let words = get_words_with_ranges("A 'runner' runs,\n\nwell-being isn't it?");
+        let expected = expect![[r#"
+            [
+                (
+                    Range {
+                        start: Position {
+                            line: 0,
+                            character: 0,
+                        },
+                        end: Position {
+                            line: 0,
+                            character: 1,
+                        },
+                    },
+                    "A",
+                ),
+                (
+                    Range {
+                        start: Position {
+                            line: 0,
+                            character: 3,
+                        },
+                        end: Position {
+                            line: 0,
+                            character: 9,
+                        },
+                    },
+                    "runner",
+                ),
+                (
+                    Range {
+                        start: Position {
+                            line: 0,
+                            character: 11,
+                        },
+                        end: Position {
+                            line: 0,
+                            character: 15,
+                        },
+                    },
+                    "runs",
+                ),
+                (
+                    Range {
+                        start: Position {
+                            line: 2,
+                            character: 0,
+                        },
+                        end: Position {
+                            line: 2,
+                            character: 10,
+                        },
+                    },
+                    "well-being",
+                ),
+                (
+                    Range {
+                        start: Position {
+                            line: 2,
+                            character: 11,
+                        },
+                        end: Position {
+                            line: 2,
+                            character: 16,
+                        },
+                    },
+                    "isn't",
+                ),
+                (
+                    Range {
+                        start: Position {
+                            line: 2,
+                            character: 17,
+                        },
+                        end: Position {
+                            line: 2,
+                            character: 19,
+                        },
+                    },
+                    "it",
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&words);
+    }
+
+    #[test]
+    fn spelling_candidates_both_ways() {
+        let candidates = [
+            ("colour", Region::American),
+            ("color", Region::British),
+            ("centre", Region::American),
+            ("travelled", Region::American),
+            ("organisation", Region::American),
+            ("fulfill", Region::British),
+            ("fulfil", Region::American),
+        ]
+        .map(|(w, r)| (w, spelling_candidates(w, r)));
+        let expected = expect![[r#"
+            [
+                (
+                    "colour",
+                    [
+                        "color",
+                    ],
+                ),
+                (
+                    "color",
+                    [
+                        "colour",
+                    ],
+                ),
+                (
+                    "centre",
+                    [
+                        "center",
+                    ],
+                ),
+                (
+                    "travelled",
+                    [
+                        "traveled",
+                    ],
+                ),
+                (
+                    "organisation",
+                    [
+                        "organization",
+                    ],
+                ),
+                (
+                    "fulfill",
+                    [],
+                ),
+                (
+                    "fulfil",
+                    [],
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&candidates);
+    }
+
+    #[test]
+    fn regional_majority() {
+        let range = Range::default();
+        let variant = |region, alternative: &str| RegionalVariant {
+            region,
+            alternatives: vec![alternative.to_owned()],
+        };
+        let words = vec![
+            (
+                range,
+                "Colour".to_owned(),
+                variant(Region::British, "color"),
+            ),
+            (
+                range,
+                "center".to_owned(),
+                variant(Region::American, "centre"),
+            ),
+            (
+                range,
+                "color".to_owned(),
+                variant(Region::American, "colour"),
+            ),
+        ];
+        let messages = |locale| {
+            regional_diagnostics(words.clone(), locale)
+                .into_iter()
+                .map(|d| (d.message, d.data))
+                .collect::<Vec<_>>()
+        };
+        let expected = expect![[r#"
+            [
+                (
+                    "\"Colour\" is British English, expected American English",
+                    Some(
+                        Object {
+                            "replacements": Array [
+                                String("Color"),
+                            ],
+                        },
+                    ),
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&messages(None));
+        let expected = expect![[r#"
+            [
+                (
+                    "\"center\" is American English, expected British English",
+                    Some(
+                        Object {
+                            "replacements": Array [
+                                String("centre"),
+                            ],
+                        },
+                    ),
+                ),
+                (
+                    "\"color\" is American English, expected British English",
+                    Some(
+                        Object {
+                            "replacements": Array [
+                                String("colour"),
+                            ],
+                        },
+                    ),
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&messages(Some(Region::British)));
+    }
+
+    #[test]
+    fn quick_fix_from_data() {
+        let uri = Url::parse("file:///tmp/test.txt").unwrap();
+        let diagnostic = Diagnostic {
+            range: Range {
+                start: Position::new(0, 0),
+                end: Position::new(0, 5),
+            },
+            source: Some(SOURCE.to_owned()),
+            message: "register".to_owned(),
+            data: serde_json::to_value(DiagnosticData {
+                replacements: vec!["money".to_owned()],
+            })
+            .ok(),
+            ..Default::default()
+        };
+        let other = Diagnostic {
+            source: Some("other".to_owned()),
+            ..diagnostic.clone()
+        };
+        let actions = quick_fixes(&uri, &[diagnostic, other])
+            .into_iter()
+            .map(|a| match a {
+                CodeActionOrCommand::CodeAction(a) => (a.title, a.edit.unwrap().changes),
+                CodeActionOrCommand::Command(c) => (c.title, None),
+            })
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                (
+                    "Replace with \"money\"",
+                    Some(
+                        {
+                            "file:///tmp/test.txt": [
+                                TextEdit {
+                                    range: Range {
+                                        start: Position {
+                                            line: 0,
+                                            character: 0,
+                                        },
+                                        end: Position {
+                                            line: 0,
+                                            character: 5,
+                                        },
+                                    },
+                                    new_text: "money",
+                                },
+                            ],
+                        },
+                    ),
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&actions);
+    }
+}
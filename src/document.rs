@@ -0,0 +1,311 @@
+use lsp_types::ClientCapabilities;
+use lsp_types::Position;
+use lsp_types::PositionEncodingKind;
+use lsp_types::Range;
+use ropey::Rope;
+use ropey::RopeSlice;
+
+/// The unit that the character of a position is counted in, as negotiated with the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl Encoding {
+    /// The encoding to use with the client, preferring UTF-8 and falling back to UTF-16 which all
+    /// clients must support.
+    pub fn negotiate(capabilities: &ClientCapabilities) -> Self {
+        let offered = capabilities
+            .general
+            .as_ref()
+            .and_then(|general| general.position_encodings.as_ref());
+        [Self::Utf8, Self::Utf32]
+            .into_iter()
+            .find(|encoding| offered.is_some_and(|offered| offered.contains(&encoding.kind())))
+            .unwrap_or(Self::Utf16)
+    }
+
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    fn len(self, c: char) -> usize {
+        match self {
+            Self::Utf8 => c.len_utf8(),
+            Self::Utf16 => c.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+}
+
+/// The text of an open document, converting between the positions the client sends and offsets
+/// into the text.
+///
+/// Lines are split at `\n` like [`str::lines`], with a `\r` before it not counted as part of the
+/// line.
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: Rope,
+    encoding: Encoding,
+    language_id: Option<String>,
+}
+
+impl Document {
+    pub fn new(text: &str, encoding: Encoding) -> Self {
+        Self {
+            text: Rope::from_str(text),
+            encoding,
+            language_id: None,
+        }
+    }
+
+    pub fn with_language(mut self, language_id: String) -> Self {
+        self.language_id = Some(language_id);
+        self
+    }
+
+    pub fn language_id(&self) -> Option<&str> {
+        self.language_id.as_deref()
+    }
+
+    pub fn text(&self) -> String {
+        self.text.to_string()
+    }
+
+    /// The text of the line, without its line ending.
+    pub fn line(&self, line: usize) -> Option<String> {
+        self.line_slice(line).map(String::from)
+    }
+
+    fn line_slice(&self, line: usize) -> Option<RopeSlice<'_>> {
+        let mut slice = self.text.get_line(line)?;
+        for ending in ['\n', '\r'] {
+            let len = slice.len_chars();
+            if len > 0 && slice.char(len - 1) == ending {
+                slice = slice.slice(..len - 1);
+            }
+        }
+        Some(slice)
+    }
+
+    /// The char offset of the position.
+    ///
+    /// As the specification asks, a character past the end of the line is taken to be the end of
+    /// the line and a line past the end of the document the end of the document. A position
+    /// within a char is rounded down to its start.
+    pub fn offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        let Some(slice) = self.line_slice(line) else {
+            return self.text.len_chars();
+        };
+        let start = self.text.line_to_char(line);
+        let mut units = 0;
+        for (i, c) in slice.chars().enumerate() {
+            units += self.encoding.len(c);
+            if units > position.character as usize {
+                return start + i;
+            }
+        }
+        start + slice.len_chars()
+    }
+
+    /// The position of the char offset, in the negotiated encoding.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len_chars());
+        let line = self.text.char_to_line(offset);
+        let start = self.text.line_to_char(line);
+        let character = self
+            .text
+            .slice(start..offset)
+            .chars()
+            .map(|c| self.encoding.len(c))
+            .sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    /// Convert the position from the negotiated encoding to one counting chars.
+    pub fn char_position(&self, position: Position) -> Position {
+        let offset = self.offset(position);
+        let line = self.text.char_to_line(offset);
+        let character = offset - self.text.line_to_char(line);
+        Position::new(line as u32, character as u32)
+    }
+
+    /// Convert the range from one counting chars to the negotiated encoding.
+    pub fn encode_range(&self, range: Range) -> Range {
+        let encode = |position: Position| {
+            let line = position.line as usize;
+            let offset = match self.line_slice(line) {
+                Some(slice) => {
+                    self.text.line_to_char(line)
+                        + slice.len_chars().min(position.character as usize)
+                }
+                None => self.text.len_chars(),
+            };
+            self.position(offset)
+        };
+        Range::new(encode(range.start), encode(range.end))
+    }
+
+    /// Replace the range with the text, or the whole document if there is no range.
+    pub fn apply(&mut self, range: Option<Range>, text: &str) -> Result<(), String> {
+        let Some(range) = range else {
+            self.text = Rope::from_str(text);
+            return Ok(());
+        };
+        let start = self.offset(range.start);
+        let end = self.offset(range.end);
+        if start > end {
+            return Err(format!("range {range:?} ends before it starts"));
+        }
+        self.text.remove(start..end);
+        self.text.insert(start, text);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+    use proptest::prelude::*;
+
+    const ENCODINGS: [Encoding; 3] = [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32];
+
+    /// Pieces of text that documents are built from, keeping `\r\n` together as clients do.
+    const PIECES: [&str; 8] = ["a", "b", " ", "é", "😀", "\u{301}", "\n", "\r\n"];
+
+    fn pieces() -> impl Strategy<Value = Vec<&'static str>> {
+        prop::collection::vec(prop::sample::select(&PIECES[..]), 0..20)
+    }
+
+    /// The position after the text, worked out independently of the rope.
+    fn end_position(text: &str, encoding: Encoding) -> Position {
+        let line = text.matches('\n').count();
+        let last = &text[text.rfind('\n').map_or(0, |i| i + 1)..];
+        let character = last.chars().map(|c| encoding.len(c)).sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    /// A range of pieces to replace with other pieces.
+    fn edits() -> impl Strategy<Value = Vec<(usize, usize, Vec<&'static str>)>> {
+        prop::collection::vec((0..30usize, 0..30usize, pieces()), 0..10)
+    }
+
+    proptest! {
+        #[test]
+        fn incremental_matches_full_sync(initial in pieces(), edits in edits()) {
+            for encoding in ENCODINGS {
+                let mut model = initial.clone();
+                let mut document = Document::new(&model.concat(), encoding);
+                for (a, b, insert) in &edits {
+                    let start = (*a).min(*b).min(model.len());
+                    let end = (*a).max(*b).min(model.len());
+                    let range = Range::new(
+                        end_position(&model[..start].concat(), encoding),
+                        end_position(&model[..end].concat(), encoding),
+                    );
+                    document.apply(Some(range), &insert.concat()).unwrap();
+                    model.splice(start..end, insert.iter().copied());
+
+                    let mut full = Document::new("", encoding);
+                    full.apply(None, &model.concat()).unwrap();
+                    prop_assert_eq!(document.text(), full.text());
+                    let end = full.position(usize::MAX);
+                    prop_assert_eq!(document.position(usize::MAX), end);
+                    prop_assert_eq!(end, end_position(&model.concat(), encoding));
+                }
+                prop_assert_eq!(document.text(), model.concat());
+            }
+        }
+
+        #[test]
+        fn positions_round_trip(text in pieces(), split in 0..20usize) {
+            for encoding in ENCODINGS {
+                let document = Document::new(&text.concat(), encoding);
+                let prefix = text[..split.min(text.len())].concat();
+                let position = end_position(&prefix, encoding);
+                let offset = document.offset(position);
+                prop_assert_eq!(offset, prefix.chars().count());
+                prop_assert_eq!(document.position(offset), position);
+            }
+        }
+    }
+
+    #[test]
+    fn emoji_and_crlf() {
+        let text = "😀 dog\r\ncat";
+        let positions = ENCODINGS.map(|encoding| {
+            let document = Document::new(text, encoding);
+            // the start of "dog", the end of the first line and past the end of it
+            [
+                document.char_position(document.position(2)),
+                document.char_position(Position::new(0, 100)),
+                document
+                    .encode_range(Range::new(Position::new(0, 2), Position::new(0, 100)))
+                    .end,
+            ]
+        });
+        let expected = expect![[r#"
+            [
+                [
+                    Position {
+                        line: 0,
+                        character: 2,
+                    },
+                    Position {
+                        line: 0,
+                        character: 5,
+                    },
+                    Position {
+                        line: 0,
+                        character: 8,
+                    },
+                ],
+                [
+                    Position {
+                        line: 0,
+                        character: 2,
+                    },
+                    Position {
+                        line: 0,
+                        character: 5,
+                    },
+                    Position {
+                        line: 0,
+                        character: 6,
+                    },
+                ],
+                [
+                    Position {
+                        line: 0,
+                        character: 2,
+                    },
+                    Position {
+                        line: 0,
+                        character: 5,
+                    },
+                    Position {
+                        line: 0,
+                        character: 5,
+                    },
+                ],
+            ]
+        "#]];
+        expected.assert_debug_eq(&positions);
+    }
+
+    #[test]
+    fn split_char_rounds_down() {
+        let document = Document::new("😀a", Encoding::Utf16);
+        assert_eq!(document.offset(Position::new(0, 1)), 0);
+        assert_eq!(document.offset(Position::new(0, 2)), 1);
+        assert_eq!(document.offset(Position::new(5, 0)), 2);
+    }
+}
@@ -0,0 +1,140 @@
+use lsp_types::request::Request as _;
+use lsp_types::ClientCapabilities;
+use lsp_types::Registration;
+use lsp_types::ServerCapabilities;
+use lsp_types::Unregistration;
+use serde_json::json;
+
+use crate::InitializationOptions;
+
+/// Capabilities that can be turned on and off in the options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Feature {
+    Completion,
+    Hover,
+    CodeActions,
+    GotoDefinition,
+}
+
+impl Feature {
+    pub const ALL: [Self; 4] = [
+        Self::Completion,
+        Self::Hover,
+        Self::CodeActions,
+        Self::GotoDefinition,
+    ];
+
+    /// The feature that handles requests for the method.
+    pub fn for_method(method: &str) -> Option<Self> {
+        use lsp_types::request::*;
+        match method {
+            Completion::METHOD | ResolveCompletionItem::METHOD => Some(Self::Completion),
+            HoverRequest::METHOD => Some(Self::Hover),
+            CodeActionRequest::METHOD | ExecuteCommand::METHOD => Some(Self::CodeActions),
+            GotoDefinition::METHOD => Some(Self::GotoDefinition),
+            _ => None,
+        }
+    }
+
+    pub fn enabled(self, options: &InitializationOptions) -> bool {
+        match self {
+            Self::Completion => options.enable_completion,
+            Self::Hover => options.enable_hover,
+            Self::CodeActions => options.enable_code_actions,
+            Self::GotoDefinition => options.enable_goto_definition,
+        }
+        .unwrap_or(true)
+    }
+
+    /// Whether the client lets the feature be registered after initialization, so that it can be
+    /// toggled without restarting.
+    pub fn dynamic(self, capabilities: &ClientCapabilities) -> bool {
+        let text_document = capabilities.text_document.as_ref();
+        let dynamic = |registration: Option<Option<bool>>| registration.flatten().unwrap_or(false);
+        match self {
+            Self::Completion => dynamic(
+                text_document
+                    .and_then(|t| t.completion.as_ref())
+                    .map(|c| c.dynamic_registration),
+            ),
+            Self::Hover => dynamic(
+                text_document
+                    .and_then(|t| t.hover.as_ref())
+                    .map(|h| h.dynamic_registration),
+            ),
+            Self::CodeActions => {
+                dynamic(
+                    text_document
+                        .and_then(|t| t.code_action.as_ref())
+                        .map(|c| c.dynamic_registration),
+                ) && dynamic(
+                    capabilities
+                        .workspace
+                        .as_ref()
+                        .and_then(|w| w.execute_command.as_ref())
+                        .map(|e| e.dynamic_registration),
+                )
+            }
+            Self::GotoDefinition => dynamic(
+                text_document
+                    .and_then(|t| t.definition.as_ref())
+                    .map(|d| d.dynamic_registration),
+            ),
+        }
+    }
+
+    /// Remove the feature from the capabilities given when initializing.
+    pub fn clear(self, capabilities: &mut ServerCapabilities) {
+        match self {
+            Self::Completion => capabilities.completion_provider = None,
+            Self::Hover => capabilities.hover_provider = None,
+            Self::CodeActions => {
+                capabilities.code_action_provider = None;
+                capabilities.execute_command_provider = None;
+            }
+            Self::GotoDefinition => capabilities.definition_provider = None,
+        }
+    }
+
+    fn methods(self) -> &'static [&'static str] {
+        use lsp_types::request::*;
+        match self {
+            Self::Completion => &[Completion::METHOD],
+            Self::Hover => &[HoverRequest::METHOD],
+            Self::CodeActions => &[CodeActionRequest::METHOD, ExecuteCommand::METHOD],
+            Self::GotoDefinition => &[GotoDefinition::METHOD],
+        }
+    }
+
+    pub fn registrations(self) -> Vec<Registration> {
+        self.methods()
+            .iter()
+            .map(|&method| {
+                let options = match method {
+                    lsp_types::request::Completion::METHOD => {
+                        json!({ "documentSelector": null, "resolveProvider": true })
+                    }
+                    lsp_types::request::ExecuteCommand::METHOD => {
+                        json!({ "commands": ["define"] })
+                    }
+                    _ => json!({ "documentSelector": null }),
+                };
+                Registration {
+                    id: method.to_owned(),
+                    method: method.to_owned(),
+                    register_options: Some(options),
+                }
+            })
+            .collect()
+    }
+
+    pub fn unregistrations(self) -> Vec<Unregistration> {
+        self.methods()
+            .iter()
+            .map(|&method| Unregistration {
+                id: method.to_owned(),
+                method: method.to_owned(),
+            })
+            .collect()
+    }
+}
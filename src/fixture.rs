@@ -0,0 +1,68 @@
+use tempfile::TempDir;
+use wordnet_ls::wordnet::LexicalRelation;
+use wordnet_ls::wordnet::PartOfSpeech;
+use wordnet_ls::wordnet::SemanticRelation;
+use wordnet_ls::wordnet::WordNet;
+use wordnet_ls::wordnet::WordNetBuilder;
+
+use crate::Dict;
+
+/// Write the database to a temporary directory and load it, for the tests.
+///
+/// The directory is returned with the dictionary and has to outlive it, as the dictionary keeps
+/// reading the files.
+pub fn dict(builder: &WordNetBuilder) -> (TempDir, Dict) {
+    let dir = tempfile::tempdir().unwrap();
+    builder.write(dir.path()).unwrap();
+    let dict = Dict::new(WordNet::new(dir.path()).unwrap());
+    (dir, dict)
+}
+
+/// Dogs and the words they are related to, for the commands that look words up.
+pub fn dogs() -> (TempDir, Dict) {
+    let mut builder = WordNetBuilder::new();
+    let canine = builder.add_synset(PartOfSpeech::Noun, &["canine", "canid"], "a carnivore", &[]);
+    let dog = builder.add_synset(
+        PartOfSpeech::Noun,
+        &["dog", "domestic dog"],
+        "a member of the genus Canis that has been domesticated by man since prehistoric times",
+        &["the dog barked all night"],
+    );
+    let paw = builder.add_synset(PartOfSpeech::Noun, &["paw"], "a clawed foot", &[]);
+    builder.add_relation(dog, SemanticRelation::Hypernym, canine);
+    builder.add_relation(canine, SemanticRelation::Hyponym, dog);
+    builder.add_relation(dog, SemanticRelation::PartMeronym, paw);
+    builder.add_relation(paw, SemanticRelation::PartHolonym, dog);
+    let cat = builder.add_synset(PartOfSpeech::Noun, &["cat"], "a mewing animal", &[]);
+    builder.add_lexical_relation(dog, 0, LexicalRelation::Antonym, cat, 0);
+    let wild_dog = builder.add_synset(PartOfSpeech::Noun, &["wild dog"], "a feral dog", &[]);
+    builder.add_lexical_relation(dog, 1, LexicalRelation::Antonym, wild_dog, 0);
+    let follow = builder.add_synset(PartOfSpeech::Verb, &["dog"], "follow closely", &[]);
+    builder.add_lexical_relation(
+        dog,
+        0,
+        LexicalRelation::DerivationallyRelatedForm,
+        follow,
+        0,
+    );
+    builder.add_lexical_relation(
+        follow,
+        0,
+        LexicalRelation::DerivationallyRelatedForm,
+        dog,
+        0,
+    );
+    dict(&builder)
+}
+
+/// Words only used in slang or in a region, for checking documents.
+pub fn usage() -> (TempDir, Dict) {
+    let mut builder = WordNetBuilder::new();
+    let slang = builder.add_synset(PartOfSpeech::Noun, &["slang"], "informal language", &[]);
+    let man = builder.add_synset(PartOfSpeech::Noun, &["man"], "an adult male", &[]);
+    let bloke = builder.add_synset(PartOfSpeech::Noun, &["bloke"], "a man", &[]);
+    builder.add_relation(bloke, SemanticRelation::DomainOfSynsetUsage, slang);
+    builder.add_relation(bloke, SemanticRelation::Hypernym, man);
+    builder.add_synset(PartOfSpeech::Noun, &["colour", "color"], "a hue", &[]);
+    dict(&builder)
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Directories that WordNet is commonly installed to by package managers and the source release.
+const INSTALL_PREFIXES: [&str; 4] = [
+    "/usr/share/wordnet",
+    "/usr/local/share/wordnet",
+    "/usr/local/WordNet-3.0",
+    "/opt/homebrew/share/wordnet",
+];
+
+/// Where to look for the database, in order of preference: the configured location, the
+/// `WNSEARCHDIR` and `WNHOME` environment variables used by the WordNet tools, the XDG data
+/// directories and then common install prefixes.
+pub fn candidates(
+    configured: Option<PathBuf>,
+    env: impl Fn(&str) -> Option<OsString>,
+) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    candidates.extend(configured);
+    candidates.extend(env("WNSEARCHDIR").map(PathBuf::from));
+    candidates.extend(env("WNHOME").map(|home| PathBuf::from(home).join("dict")));
+    let data_home = env("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local/share")));
+    let data_dirs = env("XDG_DATA_DIRS").unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    candidates.extend(
+        data_home
+            .into_iter()
+            .chain(std::env::split_paths(&data_dirs))
+            .map(|dir| dir.join("wordnet")),
+    );
+    candidates.extend(INSTALL_PREFIXES.iter().map(PathBuf::from));
+    let mut seen = BTreeSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.clone()));
+    candidates
+}
+
+/// The first candidate that holds a database, either an archive, a directory of the database files
+/// or one with them in a `dict` subdirectory.
+pub fn find(candidates: &[PathBuf]) -> Option<PathBuf> {
+    candidates.iter().find_map(|candidate| {
+        if candidate.is_file() {
+            Some(candidate.clone())
+        } else {
+            [candidate.clone(), candidate.join("dict")]
+                .into_iter()
+                .find(|dir| is_database(dir))
+        }
+    })
+}
+
+fn is_database(dir: &Path) -> bool {
+    dir.join("data.noun").is_file() && dir.join("index.noun").is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+    use wordnet_ls::wordnet::PartOfSpeech;
+    use wordnet_ls::wordnet::WordNetBuilder;
+
+    #[test]
+    fn candidates_in_order() {
+        let env = |name: &str| match name {
+            "WNHOME" => Some("/opt/wordnet".into()),
+            "XDG_DATA_HOME" => Some("/home/user/.local/share".into()),
+            "XDG_DATA_DIRS" => Some("/usr/share:/var/lib/flatpak/exports/share".into()),
+            _ => None,
+        };
+        let expected = expect![[r#"
+            [
+                "~/wordnet",
+                "/opt/wordnet/dict",
+                "/home/user/.local/share/wordnet",
+                "/usr/share/wordnet",
+                "/var/lib/flatpak/exports/share/wordnet",
+                "/usr/local/share/wordnet",
+                "/usr/local/WordNet-3.0",
+                "/opt/homebrew/share/wordnet",
+            ]
+        "#]];
+        expected.assert_debug_eq(&candidates(Some(PathBuf::from("~/wordnet")), env));
+    }
+
+    #[test]
+    fn find_dict_subdirectory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = WordNetBuilder::new();
+        builder.add_synset(PartOfSpeech::Noun, &["dog"], "a barking animal", &[]);
+        builder.write(&dir.path().join("WordNet-3.1/dict")).unwrap();
+
+        let missing = dir.path().join("missing");
+        let found = find(&[missing.clone(), dir.path().join("WordNet-3.1")]);
+        assert_eq!(found, Some(dir.path().join("WordNet-3.1/dict")));
+        assert_eq!(find(&[missing]), None);
+    }
+}
@@ -1,4 +1,7 @@
 use clap::Parser;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use lsp_server::Connection;
 use lsp_server::ErrorCode;
 use lsp_server::Message;
 use lsp_server::Notification;
@@ -6,46 +9,203 @@ use lsp_server::Request;
 use lsp_server::RequestId;
 use lsp_server::Response;
 use lsp_server::ResponseError;
-use lsp_server::{Connection, IoThreads};
+use lsp_types::notification::DidChangeConfiguration;
 use lsp_types::notification::LogMessage;
 use lsp_types::notification::Notification as _;
+use lsp_types::notification::PublishDiagnostics;
 use lsp_types::notification::ShowMessage;
+use lsp_types::request::RegisterCapability;
 use lsp_types::request::Request as _;
+use lsp_types::request::UnregisterCapability;
+use lsp_types::request::WorkspaceConfiguration;
 use lsp_types::CompletionItem;
 use lsp_types::CompletionList;
+use lsp_types::ConfigurationItem;
+use lsp_types::ConfigurationParams;
+use lsp_types::Diagnostic;
 use lsp_types::ExecuteCommandOptions;
 use lsp_types::InitializeParams;
 use lsp_types::InitializeResult;
 use lsp_types::Location;
+use lsp_types::MessageType;
 use lsp_types::Position;
-use lsp_types::PositionEncodingKind;
+use lsp_types::PublishDiagnosticsParams;
 use lsp_types::Range;
+use lsp_types::Registration;
+use lsp_types::RegistrationParams;
 use lsp_types::ServerCapabilities;
 use lsp_types::ServerInfo;
 use lsp_types::ShowDocumentParams;
+use lsp_types::ShowMessageParams;
 use lsp_types::TextDocumentPositionParams;
 use lsp_types::TextDocumentSyncKind;
+use lsp_types::UnregistrationParams;
 use lsp_types::Url;
 use serde::Deserialize;
 use serde::Serialize;
-use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::fmt::Write as _;
 use std::fs::File;
 use std::io::Write as _;
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic;
+use std::sync::atomic::AtomicI32;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use wordnet_ls::wordnet::word_ranges;
+use wordnet_ls::wordnet::Lesk;
 use wordnet_ls::wordnet::LexicalRelation;
+use wordnet_ls::wordnet::LexicalRelationship;
+use wordnet_ls::wordnet::Loading;
+use wordnet_ls::wordnet::Mapping;
 use wordnet_ls::wordnet::PartOfSpeech;
 use wordnet_ls::wordnet::SemanticRelation;
+use wordnet_ls::wordnet::Signatures;
 use wordnet_ls::wordnet::SynSet;
+use wordnet_ls::wordnet::Token;
 use wordnet_ls::wordnet::WordNet;
 
+mod browse;
+mod check;
+mod diagnostics;
+mod document;
+mod features;
+#[cfg(test)]
+mod fixture;
+mod locate;
+mod markup;
+mod prose;
+mod query;
+mod transport;
+
+use browse::BrowseArgs;
+use check::CheckArgs;
+use diagnostics::Region;
+use diagnostics::RegionalVariant;
+use document::Document;
+use document::Encoding;
+use features::Feature;
+use prose::Language;
+use prose::Syntax;
+use query::Query;
+use query::QueryArgs;
+
 #[derive(Debug, Clone, Parser)]
 struct Args {
-    #[clap(long)]
+    /// Talk to a single client over stdin and stdout, the default.
+    #[clap(long, group = "transport")]
     stdio: bool,
+    /// Accept clients on a TCP address (e.g. 127.0.0.1:9257), sharing the loaded database between
+    /// them.
+    #[clap(long, group = "transport")]
+    listen: Option<String>,
+    /// Accept clients on a Unix domain socket, sharing the loaded database between them.
+    #[clap(long, group = "transport")]
+    socket: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+enum Command {
+    /// Check a database for dangling pointers, missing reciprocal relations and invalid entries,
+    /// printing each problem as a line of JSON.
+    Lint {
+        /// Directory of the WordNet database.
+        dir: PathBuf,
+    },
+    /// Align the synsets of two databases, such as different releases, printing the synsets that
+    /// were added, removed or changed as JSON.
+    Diff {
+        /// Directory of the old database.
+        from: PathBuf,
+        /// Directory of the new database.
+        to: PathBuf,
+        /// Print the mapping from each old synset to the new one, as lines of JSON, instead.
+        #[clap(long)]
+        mapping: bool,
+    },
+    /// Print the senses of a word, exiting with 1 if it isn't found.
+    Define {
+        word: String,
+        /// Print the words related to each sense too.
+        #[clap(long)]
+        all: bool,
+        #[clap(flatten)]
+        options: QueryArgs,
+    },
+    /// Print the synonyms of a word.
+    Synonyms {
+        word: String,
+        #[clap(flatten)]
+        options: QueryArgs,
+    },
+    /// Print the antonyms of a word.
+    Antonyms {
+        word: String,
+        #[clap(flatten)]
+        options: QueryArgs,
+    },
+    /// Print the more general words for a word.
+    Hypernyms {
+        word: String,
+        #[clap(flatten)]
+        options: QueryArgs,
+    },
+    /// Print the base forms of a word, with their parts of speech.
+    Lemmatize {
+        word: String,
+        #[clap(flatten)]
+        options: QueryArgs,
+    },
+    /// Print the words starting with a prefix, as completion offers them.
+    Complete {
+        prefix: String,
+        /// Number of words to print, 100 by default.
+        #[clap(long)]
+        limit: Option<usize>,
+        #[clap(flatten)]
+        options: QueryArgs,
+    },
+    /// Check documents as the server does for diagnostics, exiting with 1 if anything is found.
+    Check(CheckArgs),
+    /// Browse the senses of words and follow their relationships in the terminal.
+    Browse(BrowseArgs),
+}
+
+/// Print the problems found in the database, returning whether there were any.
+fn lint(dir: &Path) -> Result<bool, String> {
+    let wn = WordNet::new(dir).map_err(|e| e.to_string())?;
+    let problems = wn.lint();
+    let mut stdout = std::io::stdout().lock();
+    for problem in &problems {
+        let line = serde_json::to_string(problem).map_err(|e| e.to_string())?;
+        writeln!(stdout, "{line}").map_err(|e| e.to_string())?;
+    }
+    Ok(!problems.is_empty())
+}
+
+/// Print how the synsets differ between the databases.
+fn diff(from: &Path, to: &Path, mapping: bool) -> Result<(), String> {
+    let from = WordNet::new(from).map_err(|e| e.to_string())?;
+    let to = WordNet::new(to).map_err(|e| e.to_string())?;
+    let aligned = Mapping::align(&from, &to);
+    let mut stdout = std::io::stdout().lock();
+    if mapping {
+        for mapped in aligned.iter() {
+            let line = serde_json::to_string(mapped).map_err(|e| e.to_string())?;
+            writeln!(stdout, "{line}").map_err(|e| e.to_string())?;
+        }
+    } else {
+        let diff = aligned.diff(&from, &to);
+        let json = serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())?;
+        writeln!(stdout, "{json}").map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
 fn log(c: &Connection, message: impl Serialize) {
@@ -57,6 +217,28 @@ fn log(c: &Connection, message: impl Serialize) {
         .unwrap();
 }
 
+fn show_message(c: &Connection, typ: MessageType, message: String) {
+    c.sender
+        .send(Message::Notification(Notification::new(
+            ShowMessage::METHOD.to_string(),
+            ShowMessageParams { typ, message },
+        )))
+        .unwrap();
+}
+
+fn send_diagnostics(c: &Connection, uri: Url, diagnostics: Vec<Diagnostic>, version: Option<i32>) {
+    c.sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version,
+            },
+        )))
+        .unwrap();
+}
+
 fn server_capabilities() -> ServerCapabilities {
     ServerCapabilities {
         hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
@@ -81,63 +263,48 @@ fn server_capabilities() -> ServerCapabilities {
     }
 }
 
-fn connect(stdio: bool) -> (lsp_types::InitializeParams, Connection, IoThreads) {
-    let (connection, io) = if stdio {
-        Connection::stdio()
-    } else {
-        panic!("No connection mode given, e.g. --stdio");
-    };
-    let (id, params) = connection.initialize_start().unwrap();
+fn initialize(connection: &Connection) -> Result<lsp_types::InitializeParams, String> {
+    let (id, params) = connection
+        .initialize_start()
+        .map_err(|err| err.to_string())?;
     let mut caps = server_capabilities();
-    let init_params = serde_json::from_value::<InitializeParams>(params).unwrap();
-    if let Some(general) = &init_params.capabilities.general {
-        let pe = general
-            .position_encodings
-            .clone()
-            .unwrap_or_default()
-            .iter()
-            .find(|&pe| *pe == PositionEncodingKind::UTF8)
-            .cloned()
-            .unwrap_or(PositionEncodingKind::UTF16);
-        caps.position_encoding = Some(pe);
-    }
-    let init_opts = if let Some(io) = &init_params.initialization_options {
-        match serde_json::from_value::<InitializationOptions>(io.clone()) {
+    let init_params = match serde_json::from_value::<InitializeParams>(params) {
+        Ok(init_params) => init_params,
+        Err(err) => {
+            let error = invalid_params(format!("Invalid initialize params: {err}"));
+            let message = error.message.clone();
+            connection
+                .sender
+                .send(Message::Response(Response {
+                    id,
+                    result: None,
+                    error: Some(error),
+                }))
+                .map_err(|err| err.to_string())?;
+            return Err(message);
+        }
+    };
+    caps.position_encoding = Some(Encoding::negotiate(&init_params.capabilities).kind());
+    let init_opts = match &init_params.initialization_options {
+        Some(io) => match serde_json::from_value::<InitializationOptions>(io.clone()) {
             Ok(v) => v,
             Err(err) => {
-                connection
-                    .sender
-                    .send(Message::Notification(Notification::new(
-                        ShowMessage::METHOD.to_string(),
-                        format!("Invalid initialization options: {err}"),
-                    )))
-                    .unwrap();
-                panic!("Invalid initialization options: {err}")
+                show_message(
+                    connection,
+                    MessageType::ERROR,
+                    format!("Invalid initialization options, using the defaults: {err}"),
+                );
+                InitializationOptions::default()
             }
-        }
-    } else {
-        connection
-            .sender
-            .send(Message::Notification(Notification::new(
-                ShowMessage::METHOD.to_string(),
-                "No initialization options given, need it for wordnet location at least"
-                    .to_string(),
-            )))
-            .unwrap();
-        panic!("No initialization options given, need it for wordnet location at least")
+        },
+        None => InitializationOptions::default(),
     };
-    if !init_opts.enable_completion.unwrap_or(true) {
-        caps.completion_provider = None;
-    }
-    if !init_opts.enable_hover.unwrap_or(true) {
-        caps.hover_provider = None;
-    }
-    if !init_opts.enable_code_actions.unwrap_or(true) {
-        caps.code_action_provider = None;
-        caps.execute_command_provider = None;
-    }
-    if !init_opts.enable_goto_definition.unwrap_or(true) {
-        caps.definition_provider = None;
+    for feature in Feature::ALL {
+        // features the client can register dynamically are registered once initialized instead,
+        // so that they can be toggled by changing the configuration
+        if feature.dynamic(&init_params.capabilities) || !feature.enabled(&init_opts) {
+            feature.clear(&mut caps);
+        }
     }
     let init_result = InitializeResult {
         capabilities: caps,
@@ -147,549 +314,1348 @@ fn connect(stdio: bool) -> (lsp_types::InitializeParams, Connection, IoThreads)
         }),
     };
     connection
-        .initialize_finish(id, serde_json::to_value(init_result).unwrap())
-        .unwrap();
+        .initialize_finish(id, serde_json::json!(init_result))
+        .map_err(|err| err.to_string())?;
     // log(&c, format!("{:?}", params.initialization_options));
-    (init_params, connection, io)
+    Ok(init_params)
+}
+
+/// Serve a client from initialization until it exits.
+fn session(connection: Connection, dicts: &Dicts) -> Result<(), String> {
+    let params = initialize(&connection)?;
+    Server::new(&connection, params, dicts.clone()).serve(connection)
 }
 
 struct Server {
-    dict: Dict,
-    open_files: BTreeMap<String, String>,
+    /// The database, once it has loaded.
+    dict: Option<Arc<Dict>>,
+    dicts: Dicts,
+    /// Incremented each time the database is loaded, to ignore loads that were superseded.
+    generation: u64,
+    loaded_sender: Sender<Loaded>,
+    loaded_receiver: Receiver<Loaded>,
+    /// Requests that arrived before the database loaded.
+    deferred: Vec<Request>,
+    /// Whether the client shows the progress of loading the database.
+    work_done_progress: bool,
+    /// The open documents, shared with the requests being handled.
+    open_files: Arc<BTreeMap<String, Document>>,
+    /// How the client counts the characters of positions.
+    encoding: Encoding,
+    /// The configured syntax of languages, by `languageId`.
+    languages: Arc<BTreeMap<String, Syntax>>,
     shutdown: bool,
+    /// The initialization options, which the workspace configuration is layered over.
+    init_options: serde_json::Value,
+    options: InitializationOptions,
+    enable_diagnostics: bool,
+    usage_domains: Vec<String>,
+    locale: Option<Region>,
+    collapse_hover: bool,
+    completion_limit: usize,
+    /// Features the client lets us register after initialization.
+    dynamic: BTreeSet<Feature>,
+    registered: BTreeSet<Feature>,
+    /// Whether the client supports `workspace/configuration` requests.
+    pull_configuration: bool,
+    requester: Requester,
+    configuration_requests: BTreeSet<RequestId>,
+    in_flight: InFlight,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 struct InitializationOptions {
-    wordnet: PathBuf,
+    wordnet: Option<PathBuf>,
     enable_completion: Option<bool>,
     enable_hover: Option<bool>,
     enable_code_actions: Option<bool>,
     enable_goto_definition: Option<bool>,
+    enable_diagnostics: Option<bool>,
+    usage_domains: Option<Vec<String>>,
+    locale: Option<String>,
+    collapse_hover: Option<bool>,
+    lexicons: Option<Vec<PathBuf>>,
+    mmap: Option<bool>,
+    completion_limit: Option<usize>,
+    languages: Option<BTreeMap<String, Syntax>>,
 }
 
+/// Section of the workspace configuration holding the options.
+const CONFIGURATION_SECTION: &str = "wordnet-ls";
+
+/// Number of completion items given when no limit is configured.
+const COMPLETION_LIMIT: usize = 100;
+
 impl Server {
-    fn new(c: &Connection, params: lsp_types::InitializeParams) -> Self {
-        let init_opts = if let Some(io) = params.initialization_options {
-            match serde_json::from_value::<InitializationOptions>(io) {
-                Ok(v) => v,
-                Err(err) => {
-                    c.sender
-                        .send(Message::Notification(Notification::new(
-                            ShowMessage::METHOD.to_string(),
-                            format!("Invalid initialization options: {err}"),
-                        )))
-                        .unwrap();
-                    panic!("Invalid initialization options: {err}")
-                }
-            }
-        } else {
-            c.sender
-                .send(Message::Notification(Notification::new(
-                    ShowMessage::METHOD.to_string(),
-                    "No initialization options given, need it for wordnet location at least"
-                        .to_string(),
-                )))
-                .unwrap();
-            panic!("No initialization options given, need it for wordnet location at least")
-        };
-        let wordnet_location = if init_opts.wordnet.starts_with("~/") {
-            dirs::home_dir()
-                .unwrap()
-                .join(init_opts.wordnet.strip_prefix("~/").unwrap())
-        } else {
-            init_opts.wordnet
-        };
-        Self {
-            dict: Dict::new(&wordnet_location),
-            open_files: BTreeMap::new(),
+    fn new(c: &Connection, params: lsp_types::InitializeParams, dicts: Dicts) -> Self {
+        // invalid options were already reported when initializing
+        let (init_options, options) = params
+            .initialization_options
+            .and_then(|io| Some((io.clone(), serde_json::from_value(io).ok()?)))
+            .unwrap_or_else(|| (serde_json::json!({}), InitializationOptions::default()));
+        let capabilities = params.capabilities;
+        let workspace = capabilities.workspace.as_ref();
+        let (loaded_sender, loaded_receiver) = crossbeam_channel::unbounded();
+        let mut server = Self {
+            dict: None,
+            dicts,
+            generation: 0,
+            loaded_sender,
+            loaded_receiver,
+            deferred: Vec::new(),
+            work_done_progress: capabilities
+                .window
+                .as_ref()
+                .and_then(|w| w.work_done_progress)
+                .unwrap_or(false),
+            open_files: Arc::default(),
+            encoding: Encoding::negotiate(&capabilities),
+            languages: Arc::default(),
             shutdown: false,
+            init_options,
+            options: options.clone(),
+            enable_diagnostics: true,
+            usage_domains: Vec::new(),
+            locale: None,
+            collapse_hover: false,
+            completion_limit: COMPLETION_LIMIT,
+            dynamic: Feature::ALL
+                .into_iter()
+                .filter(|feature| feature.dynamic(&capabilities))
+                .collect(),
+            registered: BTreeSet::new(),
+            pull_configuration: workspace.and_then(|w| w.configuration).unwrap_or(false),
+            requester: Requester {
+                sender: c.sender.clone(),
+                next_id: Arc::default(),
+                pending: Arc::default(),
+            },
+            configuration_requests: BTreeSet::new(),
+            in_flight: InFlight::default(),
+        };
+        server.load(&options);
+        server.apply(c, options);
+        let notify_configuration = workspace
+            .and_then(|w| w.did_change_configuration)
+            .and_then(|d| d.dynamic_registration)
+            .unwrap_or(false);
+        if notify_configuration {
+            // clients only send configuration changes for the pull model when registered for them
+            server.requester.send(
+                RegisterCapability::METHOD,
+                RegistrationParams {
+                    registrations: vec![Registration {
+                        id: DidChangeConfiguration::METHOD.to_owned(),
+                        method: DidChangeConfiguration::METHOD.to_owned(),
+                        register_options: None,
+                    }],
+                },
+            );
         }
+        if server.pull_configuration {
+            server.request_configuration();
+        }
+        server
     }
 
-    fn serve(mut self, c: Connection) -> Result<(), String> {
-        loop {
-            match c.receiver.recv().unwrap() {
-                Message::Request(r) => {
-                    // log(&c, format!("Got request {r:?}"));
-                    if self.shutdown {
-                        c.sender
-                            .send(Message::Response(Response {
-                                id: r.id,
-                                result: None,
-                                error: Some(ResponseError {
-                                    code: ErrorCode::InvalidRequest as i32,
-                                    message: String::from("received request after shutdown"),
-                                    data: None,
-                                }),
-                            }))
-                            .unwrap();
-                        continue;
-                    }
-
-                    match &r.method[..] {
-                        lsp_types::request::HoverRequest::METHOD => {
-                            let tdp =
-                                serde_json::from_value::<lsp_types::TextDocumentPositionParams>(
-                                    r.params,
-                                )
-                                .unwrap();
-
-                            let words = self
-                                .get_words_from_document(&tdp)
-                                .into_iter()
-                                .filter(|w| self.dict.wordnet.lemmatize(w).any(|w| !w.is_empty()))
-                                .collect::<Vec<_>>();
-                            let response = if let Some(text) = self.dict.hover(&words) {
-                                let resp = lsp_types::Hover {
-                                    contents: lsp_types::HoverContents::Markup(
-                                        lsp_types::MarkupContent {
-                                            kind: lsp_types::MarkupKind::Markdown,
-                                            value: text,
-                                        },
-                                    ),
-                                    range: None,
-                                };
-                                Message::Response(Response {
-                                    id: r.id,
-                                    result: Some(serde_json::to_value(resp).unwrap()),
-                                    error: None,
-                                })
-                            } else {
-                                Message::Response(Response {
-                                    id: r.id,
-                                    result: None,
-                                    error: None,
-                                })
-                            };
-
-                            c.sender.send(response).unwrap()
-                        }
-                        lsp_types::request::GotoDefinition::METHOD => {
-                            let tdp =
-                                serde_json::from_value::<lsp_types::TextDocumentPositionParams>(
-                                    r.params,
-                                )
-                                .unwrap();
+    /// Use the options, reloading the database if its location changed and registering or
+    /// unregistering the features that were toggled.
+    fn apply(&mut self, c: &Connection, options: InitializationOptions) {
+        if DatabaseOptions::from(&options) != DatabaseOptions::from(&self.options) {
+            self.load(&options);
+        }
+        self.enable_diagnostics = options.enable_diagnostics.unwrap_or(true);
+        self.usage_domains = options.usage_domains.clone().unwrap_or_else(|| {
+            diagnostics::DEFAULT_USAGE_DOMAINS
+                .iter()
+                .map(|d| d.to_string())
+                .collect()
+        });
+        self.locale = match options
+            .locale
+            .as_deref()
+            .map(|l| (l, Region::from_locale(l)))
+        {
+            Some((_, Some(region))) => Some(region),
+            Some((locale, None)) => {
+                show_message(
+                    c,
+                    MessageType::WARNING,
+                    format!("Unsupported locale {locale:?}, expected en-GB or en-US"),
+                );
+                None
+            }
+            None => None,
+        };
+        self.collapse_hover = options.collapse_hover.unwrap_or(false);
+        self.completion_limit = options.completion_limit.unwrap_or(COMPLETION_LIMIT);
+        self.languages = Arc::new(options.languages.clone().unwrap_or_default());
 
-                            let words = self.get_words_from_document(&tdp);
-                            let response = match self.dict.all_info_file(&words) {
-                                Some(filename) => {
-                                    let resp =
-                                        lsp_types::GotoDefinitionResponse::Scalar(Location {
-                                            uri: Url::from_file_path(filename).unwrap(),
-                                            range: Range::default(),
-                                        });
-                                    Message::Response(Response {
-                                        id: r.id,
-                                        result: serde_json::to_value(resp).ok(),
-                                        error: None,
-                                    })
-                                }
-                                None => Message::Response(Response {
-                                    id: r.id,
-                                    result: None,
-                                    error: None,
-                                }),
-                            };
+        let (register, unregister): (Vec<Feature>, Vec<Feature>) = self
+            .dynamic
+            .iter()
+            .filter(|feature| feature.enabled(&options) != self.registered.contains(feature))
+            .partition(|feature| feature.enabled(&options));
+        if !register.is_empty() {
+            let registrations = register.iter().flat_map(|f| f.registrations()).collect();
+            self.requester.send(
+                RegisterCapability::METHOD,
+                RegistrationParams { registrations },
+            );
+        }
+        if !unregister.is_empty() {
+            let unregisterations = unregister
+                .iter()
+                .flat_map(|f| f.unregistrations())
+                .collect();
+            self.requester.send(
+                UnregisterCapability::METHOD,
+                UnregistrationParams { unregisterations },
+            );
+        }
+        self.registered.extend(register);
+        self.registered
+            .retain(|feature| !unregister.contains(feature));
+        self.options = options;
 
-                            c.sender.send(response).unwrap()
-                        }
-                        lsp_types::request::Completion::METHOD => {
-                            let mut tdp = serde_json::from_value::<
-                                lsp_types::TextDocumentPositionParams,
-                            >(r.params)
-                            .unwrap();
+        self.publish_all_diagnostics(c);
+    }
 
-                            tdp.position.character -= 1;
-                            let response = match self.get_words_from_document(&tdp).first() {
-                                Some(word) => {
-                                    let limit = 100;
-                                    let completion_items = self.dict.complete(
-                                        word,
-                                        word.chars().next().map_or(false, |c| c.is_uppercase()),
-                                        limit,
-                                    );
-                                    let resp =
-                                        lsp_types::CompletionResponse::List(CompletionList {
-                                            is_incomplete: completion_items.len() == limit,
-                                            items: completion_items,
-                                        });
-                                    Message::Response(Response {
-                                        id: r.id,
-                                        result: serde_json::to_value(resp).ok(),
-                                        error: None,
-                                    })
-                                }
-                                None => Message::Response(Response {
-                                    id: r.id,
-                                    result: None,
-                                    error: None,
-                                }),
-                            };
+    fn publish_all_diagnostics(&self, c: &Connection) {
+        for uri in self.open_files.keys() {
+            let uri = Url::parse(uri).unwrap();
+            if self.enable_diagnostics {
+                if let Err(err) = self.publish_diagnostics(c, uri, None) {
+                    log(c, err.message);
+                }
+            } else {
+                send_diagnostics(c, uri, Vec::new(), None);
+            }
+        }
+    }
 
-                            c.sender.send(response).unwrap()
-                        }
-                        lsp_types::request::ResolveCompletionItem::METHOD => {
-                            let mut ci =
-                                serde_json::from_value::<lsp_types::CompletionItem>(r.params)
-                                    .unwrap();
-
-                            let response = if let Some(doc) = self.dict.hover(&[ci.label.clone()]) {
-                                ci.documentation = Some(lsp_types::Documentation::MarkupContent(
-                                    lsp_types::MarkupContent {
-                                        kind: lsp_types::MarkupKind::Markdown,
-                                        value: doc,
-                                    },
-                                ));
-                                Message::Response(Response {
-                                    id: r.id,
-                                    result: serde_json::to_value(ci).ok(),
-                                    error: None,
-                                })
-                            } else {
-                                Message::Response(Response {
-                                    id: r.id,
-                                    result: None,
-                                    error: None,
-                                })
-                            };
+    /// Layer the settings from the workspace configuration over the initialization options.
+    fn configure(&mut self, c: &Connection, settings: serde_json::Value) {
+        let mut merged = self.init_options.clone();
+        if let (Some(merged), serde_json::Value::Object(settings)) =
+            (merged.as_object_mut(), settings)
+        {
+            merged.extend(settings);
+        }
+        match serde_json::from_value::<InitializationOptions>(merged) {
+            Ok(options) => self.apply(c, options),
+            Err(err) => show_message(
+                c,
+                MessageType::ERROR,
+                format!("Invalid configuration: {err}"),
+            ),
+        }
+    }
 
-                            c.sender.send(response).unwrap()
-                        }
-                        lsp_types::request::CodeActionRequest::METHOD => {
-                            let cap =
-                                serde_json::from_value::<lsp_types::CodeActionParams>(r.params)
-                                    .unwrap();
-
-                            let tdp = TextDocumentPositionParams {
-                                text_document: cap.text_document,
-                                position: cap.range.start,
-                            };
+    fn request_configuration(&mut self) {
+        let id = self.requester.send(
+            WorkspaceConfiguration::METHOD,
+            ConfigurationParams {
+                items: vec![ConfigurationItem {
+                    scope_uri: None,
+                    section: Some(CONFIGURATION_SECTION.to_owned()),
+                }],
+            },
+        );
+        self.configuration_requests.insert(id);
+    }
 
-                            let words = self.get_words_from_document(&tdp);
-                            let completion_items = words
-                                .into_iter()
-                                .filter(|w| self.dict.wordnet.contains(w))
-                                .map(|w| {
-                                    let args = serde_json::to_value(DefineCommandArguments {
-                                        word: w.to_owned(),
-                                    })
-                                    .unwrap();
-                                    lsp_types::CodeActionOrCommand::Command(lsp_types::Command {
-                                        title: format!("Define {w:?}"),
-                                        command: "define".to_owned(),
-                                        arguments: Some(vec![args]),
-                                    })
-                                })
-                                .collect::<Vec<_>>();
-                            let response = Message::Response(Response {
-                                id: r.id,
-                                result: Some(serde_json::to_value(completion_items).unwrap()),
-                                error: None,
-                            });
-
-                            c.sender.send(response).unwrap()
-                        }
-                        lsp_types::request::ExecuteCommand::METHOD => {
-                            let mut cap =
-                                serde_json::from_value::<lsp_types::ExecuteCommandParams>(r.params)
-                                    .unwrap();
-
-                            let response = match cap.command.as_str() {
-                                "define" => {
-                                    let arg = cap.arguments.swap_remove(0);
-                                    match serde_json::from_value::<DefineCommandArguments>(arg) {
-                                        Ok(args) => match self.dict.all_info_file(&[args.word]) {
-                                            Some(filename) => {
-                                                let params = ShowDocumentParams {
-                                                    uri: Url::from_file_path(filename).unwrap(),
-                                                    external: None,
-                                                    take_focus: None,
-                                                    selection: None,
-                                                };
-                                                c.sender
-                                                    .send(Message::Request(Request {
-                                                        id: RequestId::from(0),
-                                                        method:
-                                                            lsp_types::request::ShowDocument::METHOD
-                                                                .to_owned(),
-                                                        params: serde_json::to_value(params)
-                                                            .unwrap(),
-                                                    }))
-                                                    .unwrap();
-                                                Message::Response(Response {
-                                                    id: r.id,
-                                                    result: None,
-                                                    error: None,
-                                                })
-                                            }
-                                            None => Message::Response(Response {
-                                                id: r.id,
-                                                result: None,
-                                                error: None,
-                                            }),
-                                        },
-                                        _ => Message::Response(Response {
-                                            id: r.id,
-                                            result: None,
-                                            error: Some(ResponseError {
-                                                code: ErrorCode::InvalidRequest as i32,
-                                                message: String::from("invalid arguments"),
-                                                data: None,
-                                            }),
-                                        }),
-                                    }
-                                }
-                                _ => Message::Response(Response {
-                                    id: r.id,
-                                    result: None,
-                                    error: Some(ResponseError {
-                                        code: ErrorCode::InvalidRequest as i32,
-                                        message: String::from("unknown command"),
-                                        data: None,
-                                    }),
-                                }),
-                            };
+    /// The state for handling requests, if the database has loaded.
+    fn snapshot(&self) -> Option<Snapshot> {
+        Some(Snapshot {
+            dict: self.dict.clone()?,
+            open_files: self.open_files.clone(),
+            encoding: self.encoding,
+            languages: self.languages.clone(),
+            collapse_hover: self.collapse_hover,
+            completion_limit: self.completion_limit,
+            requester: self.requester.clone(),
+        })
+    }
 
-                            c.sender.send(response).unwrap()
-                        }
-                        lsp_types::request::Shutdown::METHOD => {
-                            self.shutdown = true;
-                            let none: Option<()> = None;
-                            c.sender
-                                .send(Message::Response(Response::new_ok(r.id, none)))
-                                .unwrap()
-                        }
-                        _ => log(&c, format!("Unmatched request received: {}", r.method)),
+    fn serve(mut self, c: Connection) -> Result<(), String> {
+        let loaded = self.loaded_receiver.clone();
+        loop {
+            crossbeam_channel::select! {
+                recv(c.receiver) -> message => {
+                    let Ok(message) = message else {
+                        return Err(String::from("Client disconnected without exiting"));
+                    };
+                    if let Some(result) = self.handle_message(&c, message) {
+                        return result;
                     }
                 }
-                Message::Response(r) => log(&c, format!("Unmatched response received: {}", r.id)),
-                Message::Notification(n) => {
-                    match &n.method[..] {
-                        lsp_types::notification::DidOpenTextDocument::METHOD => {
-                            let dotdp = serde_json::from_value::<
-                                lsp_types::DidOpenTextDocumentParams,
-                            >(n.params)
-                            .unwrap();
-                            self.open_files.insert(
-                                dotdp.text_document.uri.to_string(),
-                                dotdp.text_document.text,
-                            );
-                            // log(
-                            //     &c,
-                            //     format!(
-                            //         "got open document notification for {:?}",
-                            //         dotdp.text_document.uri
-                            //     ),
-                            // );
-                        }
-                        lsp_types::notification::DidChangeTextDocument::METHOD => {
-                            let dctdp = serde_json::from_value::<
-                                lsp_types::DidChangeTextDocumentParams,
-                            >(n.params)
-                            .unwrap();
-                            let doc = dctdp.text_document.uri.to_string();
-                            let content = self.open_files.get_mut(&doc).unwrap();
-                            for change in dctdp.content_changes {
-                                if let Some(range) = change.range {
-                                    let start = resolve_position(content, range.start);
-                                    let end = resolve_position(content, range.end);
-                                    content.replace_range(start..end, &change.text);
-                                } else {
-                                    // full content replace
-                                    *content = change.text;
-                                }
-                            }
-                            // log(&c, format!("got change document notification for {doc:?}"))
-                        }
-                        lsp_types::notification::DidCloseTextDocument::METHOD => {
-                            let dctdp = serde_json::from_value::<
-                                lsp_types::DidCloseTextDocumentParams,
-                            >(n.params)
-                            .unwrap();
-                            self.open_files.remove(&dctdp.text_document.uri.to_string());
-                            // log(
-                            //     &c,
-                            //     format!(
-                            //         "got close document notification for {:?}",
-                            //         dctdp.text_document.uri
-                            //     ),
-                            // );
-                        }
-                        lsp_types::notification::Exit::METHOD => {
-                            if self.shutdown {
-                                return Ok(());
-                            } else {
-                                return Err(String::from(
-                                    "Received exit notification before shutdown request",
-                                ));
-                            }
-                        }
-                        _ => log(&c, format!("Unmatched notification received: {}", n.method)),
+                recv(loaded) -> loaded => {
+                    // the server holds a sender, so this can't be disconnected
+                    if let Ok(loaded) = loaded {
+                        self.loaded(&c, loaded);
                     }
                 }
             }
         }
     }
 
-    fn get_file_content(&self, uri: &Url) -> String {
-        if let Some(content) = self.open_files.get(&uri.to_string()) {
-            content.to_owned()
-        } else {
-            std::fs::read_to_string(uri.to_file_path().unwrap()).unwrap()
+    /// Handle a message from the client, returning the result of serving once it exits.
+    fn handle_message(&mut self, c: &Connection, message: Message) -> Option<Result<(), String>> {
+        match message {
+            Message::Request(r) => {
+                // log(&c, format!("Got request {r:?}"));
+                let result = if self.shutdown {
+                    Err(ResponseError {
+                        code: ErrorCode::InvalidRequest as i32,
+                        message: String::from("received request after shutdown"),
+                        data: None,
+                    })
+                } else if Feature::for_method(&r.method).is_some_and(|f| !f.enabled(&self.options))
+                {
+                    // the client may not have seen the feature being disabled yet
+                    Ok(serde_json::Value::Null)
+                } else if r.method == lsp_types::request::Shutdown::METHOD {
+                    self.shutdown = true;
+                    for deferred in std::mem::take(&mut self.deferred) {
+                        let result = self
+                            .in_flight
+                            .run(&deferred.id, || Err(request_cancelled()));
+                        c.sender
+                            .send(Message::Response(response(deferred.id, result)))
+                            .unwrap();
+                    }
+                    Ok(serde_json::Value::Null)
+                } else {
+                    self.in_flight.start(r.id.clone());
+                    match self.snapshot() {
+                        Some(snapshot) => self.dispatch(c, snapshot, r),
+                        // answered once the database has loaded
+                        None => self.deferred.push(r),
+                    }
+                    return None;
+                };
+                c.sender
+                    .send(Message::Response(response(r.id, result)))
+                    .unwrap()
+            }
+            Message::Response(r) => {
+                let Some(method) = self.requester.complete(&r.id) else {
+                    log(c, format!("Unmatched response received: {}", r.id));
+                    return None;
+                };
+                let configuration = self.configuration_requests.remove(&r.id);
+                if let Some(error) = r.error {
+                    show_message(
+                        c,
+                        MessageType::WARNING,
+                        format!("The client failed to handle {method}: {}", error.message),
+                    );
+                } else if configuration {
+                    // one value per item requested, null when the section isn't set
+                    let settings = r.result.and_then(|result| result.get(0).cloned());
+                    if let Some(settings) = settings.filter(|s| !s.is_null()) {
+                        self.configure(c, settings);
+                    }
+                }
+            }
+            Message::Notification(n) => {
+                if n.method == lsp_types::notification::Exit::METHOD {
+                    if self.shutdown {
+                        return Some(Ok(()));
+                    } else {
+                        return Some(Err(String::from(
+                            "Received exit notification before shutdown request",
+                        )));
+                    }
+                }
+                // notifications can't be answered with an error, so just log it
+                if let Err(err) = self.handle_notification(c, &n.method, n.params) {
+                    log(c, format!("Failed to handle {}: {}", n.method, err.message));
+                }
+            }
         }
+        None
     }
 
-    fn get_words_from_document(&self, tdp: &lsp_types::TextDocumentPositionParams) -> Vec<String> {
-        let content = self.get_file_content(&tdp.text_document.uri);
-        get_words_from_content(
-            &content,
-            tdp.position.line as usize,
-            tdp.position.character as usize,
-        )
+    /// Handle the request on the worker pool, against the documents as they are now, so that slow
+    /// requests don't hold up others or changes to the documents.
+    fn dispatch(&self, c: &Connection, snapshot: Snapshot, r: Request) {
+        let in_flight = self.in_flight.clone();
+        let sender = c.sender.clone();
+        rayon::spawn(move || {
+            let result = in_flight.run(&r.id, || {
+                // a panicking job would abort the process, taking every session down with it
+                std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    snapshot.handle_request(&r.method, r.params)
+                }))
+                .unwrap_or_else(|panic| {
+                    Err(internal_error(format!(
+                        "Failed to handle {}: {}",
+                        r.method,
+                        panic_message(&*panic)
+                    )))
+                })
+            });
+            // the client may have gone away in the meantime
+            let _ = sender.send(Message::Response(response(r.id, result)));
+        });
     }
-}
 
-fn get_words_from_content(content: &str, line: usize, character: usize) -> Vec<String> {
-    let line = match content.lines().nth(line) {
-        None => return Vec::new(),
-        Some(l) => l,
-    };
+    /// Load the database on another thread, so that messages are handled meanwhile.
+    fn load(&mut self, options: &InitializationOptions) {
+        self.generation += 1;
+        let generation = self.generation;
+        let dicts = self.dicts.clone();
+        let options = options.clone();
+        let sender = self.loaded_sender.clone();
+        let progress = self.work_done_progress.then(|| {
+            Progress::begin(
+                &self.requester,
+                format!("wordnet-ls/loading/{generation}"),
+                "Loading WordNet",
+            )
+        });
+        std::thread::spawn(move || {
+            let dict = dicts.load(&options, |message| {
+                if let Some(progress) = &progress {
+                    progress.report(message);
+                }
+            });
+            if let Some(progress) = progress {
+                progress.end();
+            }
+            let _ = sender.send(Loaded { generation, dict });
+        });
+    }
 
-    let mut words = Vec::new();
-    let mut current_word = String::new();
-    if let Some(word) = get_word_from_line(line, character) {
-        for single_word in word.split_whitespace() {
-            if !current_word.is_empty() {
-                current_word.push('_');
+    fn loaded(&mut self, c: &Connection, loaded: Loaded) {
+        if loaded.generation != self.generation {
+            // superseded by a later change to the options
+            return;
+        }
+        let (dict, errors) = match loaded.dict {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                // run without the database, failing the requests that were waiting for it
+                show_message(c, MessageType::ERROR, error.clone());
+                for r in std::mem::take(&mut self.deferred) {
+                    let result = self.in_flight.run(&r.id, || Err(internal_error(&error)));
+                    c.sender
+                        .send(Message::Response(response(r.id, result)))
+                        .unwrap();
+                }
+                (Arc::new(Dict::new(WordNet::empty())), Vec::new())
             }
-            current_word.push_str(single_word);
-            words.push(current_word.clone());
-            // now try and simplify the word
-            for c in WORD_PUNC.chars() {
-                if let Some(w) = current_word.strip_prefix(c) {
-                    words.push(w.to_owned());
-                    if let Some(w) = w.strip_suffix(c) {
-                        words.push(w.to_owned());
-                    }
+        };
+        for error in errors {
+            show_message(c, MessageType::ERROR, error);
+        }
+        self.dict = Some(dict);
+        self.publish_all_diagnostics(c);
+        for r in std::mem::take(&mut self.deferred) {
+            if let Some(snapshot) = self.snapshot() {
+                self.dispatch(c, snapshot, r);
+            }
+        }
+    }
+
+    fn handle_notification(
+        &mut self,
+        c: &Connection,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<(), ResponseError> {
+        match method {
+            lsp_types::notification::DidOpenTextDocument::METHOD => {
+                let dotdp = parse_params::<lsp_types::DidOpenTextDocumentParams>(params)?;
+                Arc::make_mut(&mut self.open_files).insert(
+                    dotdp.text_document.uri.to_string(),
+                    Document::new(&dotdp.text_document.text, self.encoding)
+                        .with_language(dotdp.text_document.language_id),
+                );
+                self.publish_diagnostics(
+                    c,
+                    dotdp.text_document.uri,
+                    Some(dotdp.text_document.version),
+                )?;
+                // log(
+                //     &c,
+                //     format!(
+                //         "got open document notification for {:?}",
+                //         dotdp.text_document.uri
+                //     ),
+                // );
+            }
+            lsp_types::notification::DidChangeTextDocument::METHOD => {
+                let dctdp = parse_params::<lsp_types::DidChangeTextDocumentParams>(params)?;
+                let doc = dctdp.text_document.uri.to_string();
+                let document = Arc::make_mut(&mut self.open_files)
+                    .get_mut(&doc)
+                    .ok_or_else(|| invalid_params(format!("{doc} is not open")))?;
+                for change in dctdp.content_changes {
+                    document
+                        .apply(change.range, &change.text)
+                        .map_err(|err| invalid_params(format!("{err} in {doc}")))?;
                 }
-                if let Some(w) = current_word.strip_suffix(c) {
-                    words.push(w.to_owned());
+                self.publish_diagnostics(
+                    c,
+                    dctdp.text_document.uri,
+                    Some(dctdp.text_document.version),
+                )?;
+                // log(&c, format!("got change document notification for {doc:?}"))
+            }
+            lsp_types::notification::DidCloseTextDocument::METHOD => {
+                let dctdp = parse_params::<lsp_types::DidCloseTextDocumentParams>(params)?;
+                Arc::make_mut(&mut self.open_files).remove(&dctdp.text_document.uri.to_string());
+                if self.enable_diagnostics {
+                    // clear out any diagnostics we published for the file
+                    send_diagnostics(c, dctdp.text_document.uri, Vec::new(), None);
+                }
+                // log(
+                //     &c,
+                //     format!(
+                //         "got close document notification for {:?}",
+                //         dctdp.text_document.uri
+                //     ),
+                // );
+            }
+            lsp_types::notification::Cancel::METHOD => {
+                let params = parse_params::<lsp_types::CancelParams>(params)?;
+                self.in_flight.cancel(&match params.id {
+                    lsp_types::NumberOrString::Number(id) => RequestId::from(id),
+                    lsp_types::NumberOrString::String(id) => RequestId::from(id),
+                });
+            }
+            DidChangeConfiguration::METHOD => {
+                let params = parse_params::<lsp_types::DidChangeConfigurationParams>(params)?;
+                if self.pull_configuration {
+                    self.request_configuration();
+                } else {
+                    let settings = match params.settings.get(CONFIGURATION_SECTION) {
+                        Some(section) => section.clone(),
+                        None => params.settings,
+                    };
+                    self.configure(c, settings);
                 }
             }
+            _ => log(c, format!("Unmatched notification received: {method}")),
         }
+        Ok(())
     }
-    // sort by length to try and find the simplest
-    words.sort_unstable_by(|s1, s2| {
-        if s1.len() < s2.len() {
-            Ordering::Less
-        } else {
-            s1.cmp(s2)
+
+    fn publish_diagnostics(
+        &self,
+        c: &Connection,
+        uri: Url,
+        version: Option<i32>,
+    ) -> Result<(), ResponseError> {
+        // published for all the open documents once the database has loaded
+        let Some(snapshot) = self.snapshot().filter(|_| self.enable_diagnostics) else {
+            return Ok(());
+        };
+        let document = snapshot.get_document(&uri)?;
+        let content = snapshot.prose(&document);
+        let dict = &snapshot.dict;
+        let mut diagnostics =
+            diagnostics::analyse(dict, &content, &self.usage_domains, self.locale);
+        for diagnostic in &mut diagnostics {
+            diagnostic.range = document.encode_range(diagnostic.range);
         }
-    });
-    words.dedup();
-    words
+        send_diagnostics(c, uri, diagnostics, version);
+        Ok(())
+    }
+}
+
+/// What handling requests needs of the server, cheap to clone so that requests can be handled on
+/// other threads.
+#[derive(Clone)]
+struct Snapshot {
+    dict: Arc<Dict>,
+    open_files: Arc<BTreeMap<String, Document>>,
+    encoding: Encoding,
+    languages: Arc<BTreeMap<String, Syntax>>,
+    collapse_hover: bool,
+    completion_limit: usize,
+    requester: Requester,
 }
 
-const WORD_PUNC: &str = "_-'./";
-
-fn get_word_from_line(line: &str, character: usize) -> Option<String> {
-    let mut current_word = String::new();
-    let mut found = false;
-    let mut match_chars = WORD_PUNC.to_owned();
-    let word_char = |match_with: &str, c: char| c.is_alphanumeric() || match_with.contains(c);
-    for (i, c) in line.chars().enumerate() {
-        if word_char(&match_chars, c) {
-            for c in c.to_lowercase() {
-                current_word.push(c);
+impl Snapshot {
+    fn handle_request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ResponseError> {
+        match method {
+            lsp_types::request::HoverRequest::METHOD => {
+                let tdp = parse_params::<lsp_types::TextDocumentPositionParams>(params)?;
+
+                let (tokens, offset) = self.get_tokens_from_document(&tdp)?;
+                let words = get_words_from_tokens(&tokens, offset)
+                    .into_iter()
+                    .filter(|w| self.dict.wordnet.lemmatize(w).any(|w| !w.is_empty()))
+                    .collect::<Vec<_>>();
+                let part_of_speech = tokens
+                    .iter()
+                    .zip(self.dict.wordnet.tag(&tokens))
+                    .find(|(token, _)| token.range.contains(&offset))
+                    .and_then(|(_, pos)| pos);
+                let context = HoverContext {
+                    words: self.get_context_from_document(&tdp)?,
+                    part_of_speech,
+                    collapse: self.collapse_hover,
+                };
+                match self.dict.hover_in_context(&words, &context) {
+                    Some(text) => to_result(lsp_types::Hover {
+                        contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+                            kind: lsp_types::MarkupKind::Markdown,
+                            value: text,
+                        }),
+                        range: None,
+                    }),
+                    None => Ok(serde_json::Value::Null),
+                }
             }
-        } else {
-            if found {
-                return Some(current_word);
+            lsp_types::request::GotoDefinition::METHOD => {
+                let tdp = parse_params::<lsp_types::TextDocumentPositionParams>(params)?;
+
+                let (tokens, offset) = self.get_tokens_from_document(&tdp)?;
+                let words = get_words_from_tokens(&tokens, offset);
+                match self.all_info_uri(&words)? {
+                    Some(uri) => to_result(lsp_types::GotoDefinitionResponse::Scalar(Location {
+                        uri,
+                        range: Range::default(),
+                    })),
+                    None => Ok(serde_json::Value::Null),
+                }
             }
-            current_word.clear();
-        }
+            lsp_types::request::Completion::METHOD => {
+                let tdp = parse_params::<lsp_types::TextDocumentPositionParams>(params)?;
+
+                let (tokens, offset) = self.get_tokens_from_document(&tdp)?;
+                match get_prefix_from_tokens(&tokens, offset) {
+                    Some(prefix) => {
+                        let limit = self.completion_limit;
+                        let completion_items = self.dict.complete(
+                            &prefix.to_lowercase(),
+                            prefix.chars().next().is_some_and(|c| c.is_uppercase()),
+                            limit,
+                        );
+                        to_result(lsp_types::CompletionResponse::List(CompletionList {
+                            is_incomplete: completion_items.len() == limit,
+                            items: completion_items,
+                        }))
+                    }
+                    None => Ok(serde_json::Value::Null),
+                }
+            }
+            lsp_types::request::ResolveCompletionItem::METHOD => {
+                let mut ci = parse_params::<lsp_types::CompletionItem>(params)?;
+
+                match self.dict.hover(&[ci.label.clone()]) {
+                    Some(doc) => {
+                        ci.documentation = Some(lsp_types::Documentation::MarkupContent(
+                            lsp_types::MarkupContent {
+                                kind: lsp_types::MarkupKind::Markdown,
+                                value: doc,
+                            },
+                        ));
+                        to_result(ci)
+                    }
+                    None => Ok(serde_json::Value::Null),
+                }
+            }
+            lsp_types::request::CodeActionRequest::METHOD => {
+                let cap = parse_params::<lsp_types::CodeActionParams>(params)?;
+
+                let mut actions =
+                    diagnostics::quick_fixes(&cap.text_document.uri, &cap.context.diagnostics);
+
+                let tdp = TextDocumentPositionParams {
+                    text_document: cap.text_document,
+                    position: cap.range.start,
+                };
+
+                let (tokens, offset) = self.get_tokens_from_document(&tdp)?;
+                let commands = get_words_from_tokens(&tokens, offset)
+                    .into_iter()
+                    .filter(|w| self.dict.wordnet.contains(w))
+                    .map(|w| {
+                        let args = serde_json::json!(DefineCommandArguments { word: w.to_owned() });
+                        lsp_types::CodeActionOrCommand::Command(lsp_types::Command {
+                            title: format!("Define {w:?}"),
+                            command: "define".to_owned(),
+                            arguments: Some(vec![args]),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                actions.extend(commands);
+                to_result(actions)
+            }
+            lsp_types::request::ExecuteCommand::METHOD => {
+                let cap = parse_params::<lsp_types::ExecuteCommandParams>(params)?;
 
-        if i == character {
-            if word_char(&match_chars, c) {
-                match_chars.push(' ');
-                found = true
-            } else {
-                return None;
+                match cap.command.as_str() {
+                    "define" => {
+                        let args = cap
+                            .arguments
+                            .into_iter()
+                            .next()
+                            .and_then(|arg| {
+                                serde_json::from_value::<DefineCommandArguments>(arg).ok()
+                            })
+                            .ok_or_else(|| invalid_params("invalid arguments"))?;
+                        if let Some(uri) = self.all_info_uri(&[args.word])? {
+                            let params = ShowDocumentParams {
+                                uri,
+                                external: None,
+                                take_focus: None,
+                                selection: None,
+                            };
+                            self.requester
+                                .send(lsp_types::request::ShowDocument::METHOD, params);
+                        }
+                        Ok(serde_json::Value::Null)
+                    }
+                    _ => Err(invalid_params("unknown command")),
+                }
             }
+            #[cfg(test)]
+            "wordnet-ls/test/panic" => panic!("panicked on purpose"),
+            _ => Err(ResponseError {
+                code: ErrorCode::MethodNotFound as i32,
+                message: format!("Unknown request {method}"),
+                data: None,
+            }),
         }
+    }
 
-        if !word_char(&match_chars, c) && found {
-            return Some(current_word);
+    fn get_document(&self, uri: &Url) -> Result<Document, ResponseError> {
+        if let Some(document) = self.open_files.get(&uri.to_string()) {
+            Ok(document.clone())
+        } else {
+            let path = uri
+                .to_file_path()
+                .map_err(|()| invalid_params(format!("{uri} is not open or a file")))?;
+            std::fs::read_to_string(&path)
+                .map(|content| Document::new(&content, self.encoding))
+                .map_err(|err| internal_error(format!("Failed to read {path:?}: {err}")))
         }
     }
 
-    // got to end of line
-    if found {
-        return Some(current_word);
+    /// The document's language, if only part of it is prose.
+    fn language(&self, document: &Document) -> Option<Language<'_>> {
+        document
+            .language_id()
+            .and_then(|language_id| prose::language(&self.languages, language_id))
     }
 
-    None
-}
+    /// The prose of the document, with the code or markup blanked out.
+    fn prose(&self, document: &Document) -> String {
+        match self.language(document) {
+            Some(language) => prose::extract(&document.text(), &language),
+            None => document.text(),
+        }
+    }
 
-fn main() {
-    let args = Args::parse();
-    let (p, c, io) = connect(args.stdio);
-    let server = Server::new(&c, p);
-    let s = server.serve(c);
-    io.join().unwrap();
-    match s {
-        Ok(()) => (),
-        Err(s) => {
-            eprintln!("{}", s);
-            std::process::exit(1)
+    fn prose_line(&self, document: &Document, line: usize) -> Option<String> {
+        match self.language(document) {
+            // comments, code blocks and the like can span lines
+            Some(language) => prose::extract(&document.text(), &language)
+                .lines()
+                .nth(line)
+                .map(str::to_owned),
+            None => document.line(line),
         }
     }
-}
 
-struct Dict {
-    wordnet: WordNet,
-    all_words: Vec<String>,
-}
+    /// The tokens of the line at the position, along with the byte offset of the position in
+    /// the line.
+    fn get_tokens_from_document(
+        &self,
+        tdp: &lsp_types::TextDocumentPositionParams,
+    ) -> Result<(Vec<Token>, usize), ResponseError> {
+        let document = self.get_document(&tdp.text_document.uri)?;
+        let position = document.char_position(tdp.position);
+        let Some(line) = self.prose_line(&document, position.line as usize) else {
+            return Ok((Vec::new(), 0));
+        };
+        let offset = line
+            .char_indices()
+            .nth(position.character as usize)
+            .map_or(line.len(), |(i, _)| i);
+        Ok((self.dict.wordnet.tokenize(&line), offset))
+    }
 
-impl Dict {
-    fn new(value: &Path) -> Self {
-        let wn = WordNet::new(value).unwrap();
-        let all_words = wn.all_words();
-        Self {
-            wordnet: wn,
-            all_words,
-        }
+    fn get_context_from_document(
+        &self,
+        tdp: &lsp_types::TextDocumentPositionParams,
+    ) -> Result<Vec<String>, ResponseError> {
+        let document = self.get_document(&tdp.text_document.uri)?;
+        Ok(get_context_from_content(
+            &self.prose(&document),
+            document.char_position(tdp.position),
+            CONTEXT_WINDOW,
+        ))
     }
 
-    fn hover(&self, words: &[String]) -> Option<String> {
-        let first_word = words.first()?;
-        let lemmas = self.wordnet.lemmatize(first_word);
-        if lemmas.all(|w| w.is_empty()) {
-            return None;
-        }
-        let mut content = String::new();
-        if words.len() > 1 {
-            writeln!(
-                content,
-                "View full definition for: {}\n",
-                words[1..].join(", ")
-            )
-            .unwrap();
-        }
-        lemmas.for_each(|pos, lemmas| {
-            lemmas.into_iter().for_each(|lemma| {
-                let synsets = self.wordnet.synsets_for(&lemma, pos);
-                let hover = self.render_hover(&lemma, synsets);
-                writeln!(content, "{hover}\n").unwrap();
-            });
-        });
-        Some(content.trim().to_owned())
+    /// Write all the info about the words to a file to show as a document.
+    fn all_info_uri(&self, words: &[String]) -> Result<Option<Url>, ResponseError> {
+        let filename = self.dict.all_info_file(words).map_err(|err| {
+            internal_error(format!("Failed to write the info for {words:?}: {err}"))
+        })?;
+        filename
+            .map(|filename| {
+                Url::from_file_path(&filename)
+                    .map_err(|()| internal_error(format!("Invalid file path {filename:?}")))
+            })
+            .transpose()
     }
+}
 
-    fn render_hover(&self, word: &str, synsets: Vec<SynSet>) -> String {
-        let mut blocks = Vec::new();
+/// Sends requests to the client with unique ids, from any thread.
+#[derive(Clone)]
+struct Requester {
+    sender: Sender<Message>,
+    next_id: Arc<AtomicI32>,
+    /// Methods of the requests awaiting a response.
+    pending: Arc<Mutex<BTreeMap<RequestId, String>>>,
+}
+
+impl Requester {
+    fn send(&self, method: &str, params: impl Serialize) -> RequestId {
+        let id = RequestId::from(self.next_id.fetch_add(1, atomic::Ordering::Relaxed) + 1);
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(id.clone(), method.to_owned());
+        // the client may have gone away, in which case the server stops on the next message
+        let _ = self.sender.send(Message::Request(Request::new(
+            id.clone(),
+            method.to_owned(),
+            params,
+        )));
+        id
+    }
+
+    /// The method of the request the response is for, if it was sent by this requester.
+    fn complete(&self, id: &RequestId) -> Option<String> {
+        self.pending.lock().unwrap().remove(id)
+    }
+}
+
+/// A database that finished loading in the background.
+struct Loaded {
+    generation: u64,
+    /// The database with the errors from loading it, or why loading it failed.
+    dict: Result<(Arc<Dict>, Vec<String>), String>,
+}
+
+/// Reports the progress of some work to the client.
+struct Progress {
+    requester: Requester,
+    token: lsp_types::ProgressToken,
+}
+
+impl Progress {
+    fn begin(requester: &Requester, token: String, title: &str) -> Self {
+        let token = lsp_types::NumberOrString::String(token);
+        requester.send(
+            lsp_types::request::WorkDoneProgressCreate::METHOD,
+            lsp_types::WorkDoneProgressCreateParams {
+                token: token.clone(),
+            },
+        );
+        let progress = Self {
+            requester: requester.clone(),
+            token,
+        };
+        progress.notify(lsp_types::WorkDoneProgress::Begin(
+            lsp_types::WorkDoneProgressBegin {
+                title: title.to_owned(),
+                ..Default::default()
+            },
+        ));
+        progress
+    }
+
+    fn report(&self, message: &str) {
+        self.notify(lsp_types::WorkDoneProgress::Report(
+            lsp_types::WorkDoneProgressReport {
+                message: Some(message.to_owned()),
+                ..Default::default()
+            },
+        ));
+    }
+
+    fn end(self) {
+        self.notify(lsp_types::WorkDoneProgress::End(
+            lsp_types::WorkDoneProgressEnd::default(),
+        ));
+    }
+
+    fn notify(&self, progress: lsp_types::WorkDoneProgress) {
+        let params = lsp_types::ProgressParams {
+            token: self.token.clone(),
+            value: lsp_types::ProgressParamsValue::WorkDone(progress),
+        };
+        // the client may have gone away, in which case the server stops on the next message
+        let _ = self
+            .requester
+            .sender
+            .send(Message::Notification(Notification::new(
+                lsp_types::notification::Progress::METHOD.to_owned(),
+                params,
+            )));
+    }
+}
+
+/// Requests being handled, and whether the client has cancelled them.
+#[derive(Default, Clone)]
+struct InFlight(Arc<Mutex<BTreeMap<RequestId, bool>>>);
+
+impl InFlight {
+    fn start(&self, id: RequestId) {
+        self.0.lock().unwrap().insert(id, false);
+    }
+
+    /// Mark the request as cancelled, if it is still being handled.
+    fn cancel(&self, id: &RequestId) {
+        if let Some(cancelled) = self.0.lock().unwrap().get_mut(id) {
+            *cancelled = true;
+        }
+    }
+
+    fn is_cancelled(&self, id: &RequestId) -> bool {
+        self.0.lock().unwrap().get(id).copied().unwrap_or(false)
+    }
+
+    /// Handle the request unless it was cancelled while waiting to be, replacing the result if it
+    /// was cancelled while being handled.
+    fn run(
+        &self,
+        id: &RequestId,
+        handle: impl FnOnce() -> Result<serde_json::Value, ResponseError>,
+    ) -> Result<serde_json::Value, ResponseError> {
+        let result = if self.is_cancelled(id) {
+            Err(request_cancelled())
+        } else {
+            handle()
+        };
+        match self.0.lock().unwrap().remove(id) {
+            Some(true) => Err(request_cancelled()),
+            _ => result,
+        }
+    }
+}
+
+fn request_cancelled() -> ResponseError {
+    ResponseError {
+        code: ErrorCode::RequestCanceled as i32,
+        message: String::from("request cancelled"),
+        data: None,
+    }
+}
+
+fn response(id: RequestId, result: Result<serde_json::Value, ResponseError>) -> Response {
+    match result {
+        Ok(result) => Response::new_ok(id, result),
+        Err(error) => Response {
+            id,
+            result: None,
+            error: Some(error),
+        },
+    }
+}
+
+fn parse_params<P: serde::de::DeserializeOwned>(
+    params: serde_json::Value,
+) -> Result<P, ResponseError> {
+    serde_json::from_value(params).map_err(|err| invalid_params(format!("Invalid params: {err}")))
+}
+
+fn to_result(value: impl Serialize) -> Result<serde_json::Value, ResponseError> {
+    serde_json::to_value(value).map_err(internal_error)
+}
+
+fn invalid_params(message: impl std::fmt::Display) -> ResponseError {
+    ResponseError {
+        code: ErrorCode::InvalidParams as i32,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+/// The message a panic was raised with.
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error")
+}
+
+fn internal_error(message: impl std::fmt::Display) -> ResponseError {
+    ResponseError {
+        code: ErrorCode::InternalError as i32,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+/// Options that determine which database is loaded.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DatabaseOptions {
+    wordnet: Option<PathBuf>,
+    mmap: Option<bool>,
+    lexicons: Option<Vec<PathBuf>>,
+}
+
+impl From<&InitializationOptions> for DatabaseOptions {
+    fn from(options: &InitializationOptions) -> Self {
+        Self {
+            wordnet: options.wordnet.clone(),
+            mmap: options.mmap,
+            lexicons: options.lexicons.clone(),
+        }
+    }
+}
+
+/// Databases loaded by any session, so that later sessions with the same options start warm.
+#[derive(Default, Clone)]
+struct Dicts(Arc<Mutex<BTreeMap<DatabaseOptions, LoadedDict>>>);
+
+struct LoadedDict {
+    dict: Arc<Dict>,
+    errors: Vec<String>,
+}
+
+impl Dicts {
+    /// The database for the options, loading it if no session has yet, along with any errors
+    /// from loading it, or an error if loading it panicked (e.g. on a malformed database).
+    fn load(
+        &self,
+        options: &InitializationOptions,
+        report: impl Fn(&str),
+    ) -> Result<(Arc<Dict>, Vec<String>), String> {
+        // hold the lock while loading so that concurrent sessions don't load the same database,
+        // the map is only changed once a database has loaded so is fine to use if poisoned
+        let mut dicts = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let key = DatabaseOptions::from(options);
+        if let Some(loaded) = dicts.get(&key) {
+            return Ok((loaded.dict.clone(), loaded.errors.clone()));
+        }
+        let loaded = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            report("Reading the database");
+            let (wordnet, errors) = load_wordnet(options);
+            report("Indexing words");
+            LoadedDict {
+                dict: Arc::new(Dict::new(wordnet)),
+                errors,
+            }
+        }))
+        .map_err(|panic| {
+            format!(
+                "Failed to load WordNet, running without it: {}",
+                panic_message(&*panic)
+            )
+        })?;
+        let loaded = dicts.entry(key).or_insert(loaded);
+        Ok((loaded.dict.clone(), loaded.errors.clone()))
+    }
+}
+
+/// Load the database from the configured or discovered location, running without it if it can't
+/// be loaded, along with the errors from loading it.
+fn load_wordnet(options: &InitializationOptions) -> (WordNet, Vec<String>) {
+    let mut errors = Vec::new();
+    let loading = if options.mmap.unwrap_or(true) {
+        Loading::Mmap
+    } else {
+        Loading::Read
+    };
+    let configured = options.wordnet.clone().map(expand_home);
+    if let Some(configured) = &configured {
+        if locate::find(std::slice::from_ref(configured)).is_none() {
+            errors.push(format!(
+                "WordNet wasn't found at the configured location {configured:?}, looking for it \
+                 elsewhere"
+            ));
+        }
+    }
+    let candidates = locate::candidates(configured, |name| std::env::var_os(name));
+    let mut wordnet = match locate::find(&candidates) {
+        Some(location) => WordNet::with_loading(&location, loading).unwrap_or_else(|err| {
+            errors.push(format!(
+                "Failed to load WordNet from {location:?}, running without it: {err}"
+            ));
+            WordNet::empty()
+        }),
+        None => missing_wordnet(&candidates, &mut errors),
+    };
+    for lexicon in options.lexicons.clone().unwrap_or_default() {
+        if let Err(err) = wordnet.add_lexicon(&expand_home(lexicon)) {
+            errors.push(err.to_string());
+        }
+    }
+    (wordnet, errors)
+}
+
+/// Without a database found, use the copy bundled in the binary.
+#[cfg(feature = "bundled")]
+fn missing_wordnet(_candidates: &[PathBuf], errors: &mut Vec<String>) -> WordNet {
+    WordNet::bundled().unwrap_or_else(|err| {
+        errors.push(format!("Failed to load the bundled WordNet: {err}"));
+        WordNet::empty()
+    })
+}
+
+/// Without a database the server keeps running, but only lexicon words are known.
+#[cfg(not(feature = "bundled"))]
+fn missing_wordnet(candidates: &[PathBuf], errors: &mut Vec<String>) -> WordNet {
+    let tried = candidates
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    errors.push(format!(
+        "Could not find WordNet, set the wordnet initialization option or WNSEARCHDIR. Tried: \
+         {tried}"
+    ));
+    WordNet::empty()
+}
+
+fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path,
+    }
+}
+
+/// Number of words either side of the cursor to use as context for disambiguating hovers.
+const CONTEXT_WINDOW: usize = 10;
+
+/// Words surrounding the position, not including the word at the position itself.
+fn get_context_from_content(content: &str, position: Position, window: usize) -> Vec<String> {
+    let words = diagnostics::get_words_with_ranges(content);
+    let index = words.partition_point(|(range, _)| range.end < position);
+    let start = index.saturating_sub(window);
+    let end = (index + window + 1).min(words.len());
+    words[start..end]
+        .iter()
+        .filter(|(range, _)| !(range.start <= position && position <= range.end))
+        .map(|(_, word)| word.to_lowercase())
+        .collect()
+}
+
+/// The words to look up for the token at the byte offset: the collocation it is part of, if any,
+/// and then the single word.
+fn get_words_from_tokens(tokens: &[Token], offset: usize) -> Vec<String> {
+    let Some(token) = tokens.iter().find(|t| t.range.contains(&offset)) else {
+        return Vec::new();
+    };
+    let mut words = Vec::new();
+    if token.is_collocation() {
+        words.push(token.lemma().map_or_else(|| token.key(), str::to_owned));
+    }
+    let offset = offset - token.range.start;
+    if let Some(range) = word_ranges(&token.text)
+        .into_iter()
+        .find(|r| r.contains(&offset))
+    {
+        words.push(token.text[range].to_lowercase());
+    }
+    words
+}
+
+/// The start of the word that ends at, or goes past, the byte offset, as written.
+fn get_prefix_from_tokens(tokens: &[Token], offset: usize) -> Option<&str> {
+    let token = tokens
+        .iter()
+        .find(|t| t.range.start < offset && offset <= t.range.end)?;
+    let offset = offset - token.range.start;
+    word_ranges(&token.text)
+        .into_iter()
+        .find(|r| r.start < offset && offset <= r.end)
+        .map(|r| &token.text[r.start..offset])
+}
+
+fn main() {
+    let args = Args::parse();
+    match args.command {
+        Some(Command::Lint { dir }) => exit_found(lint(&dir)),
+        Some(Command::Diff { from, to, mapping }) => match diff(&from, &to, mapping) {
+            Ok(()) => return,
+            Err(s) => {
+                eprintln!("{}", s);
+                std::process::exit(1)
+            }
+        },
+        Some(Command::Define { word, all, options }) => {
+            exit_query(query::run(Query::Define { all }, &word, options))
+        }
+        Some(Command::Synonyms { word, options }) => {
+            exit_query(query::run(Query::Synonyms, &word, options))
+        }
+        Some(Command::Antonyms { word, options }) => {
+            exit_query(query::run(Query::Antonyms, &word, options))
+        }
+        Some(Command::Hypernyms { word, options }) => {
+            exit_query(query::run(Query::Hypernyms, &word, options))
+        }
+        Some(Command::Lemmatize { word, options }) => {
+            exit_query(query::run(Query::Lemmatize, &word, options))
+        }
+        Some(Command::Complete {
+            prefix,
+            limit,
+            options,
+        }) => exit_query(query::run(Query::Complete { limit }, &prefix, options)),
+        Some(Command::Check(args)) => exit_found(check::run(args)),
+        Some(Command::Browse(args)) => exit_query(browse::run(args).map(|()| true)),
+        None => {}
+    }
+    let dicts = Dicts::default();
+    // each session on a socket reports its own errors, the server keeps accepting others
+    let serve = move |c| {
+        if let Err(s) = session(c, &dicts) {
+            eprintln!("{}", s);
+        }
+    };
+    let listening = if let Some(addr) = args.listen {
+        transport::listen_tcp(&addr, serve)
+    } else if let Some(path) = args.socket {
+        transport::listen_unix(&path, serve)
+    } else {
+        // stdio is the default, whether or not --stdio is given
+        let (c, io) = Connection::stdio();
+        let s = session(c, &Dicts::default());
+        io.join().unwrap();
+        s.map_err(std::io::Error::other)
+    };
+    if let Err(s) = listening {
+        eprintln!("{}", s);
+        std::process::exit(1)
+    }
+}
+
+/// Exit with 1 if the word queried wasn't found, or 2 if the query failed.
+fn exit_query(found: Result<bool, String>) -> ! {
+    match found {
+        Ok(true) => std::process::exit(0),
+        Ok(false) => std::process::exit(1),
+        Err(s) => {
+            eprintln!("{}", s);
+            std::process::exit(2)
+        }
+    }
+}
+
+/// Exit with 1 if lint or check found problems, or 2 if looking for them failed.
+fn exit_found(problems: Result<bool, String>) -> ! {
+    match problems {
+        Ok(false) => std::process::exit(0),
+        Ok(true) => std::process::exit(1),
+        Err(s) => {
+            eprintln!("{}", s);
+            std::process::exit(2)
+        }
+    }
+}
+
+/// What is known about where a word being hovered is used.
+#[derive(Debug, Default)]
+struct HoverContext {
+    /// Words surrounding the hovered word.
+    words: Vec<String>,
+    /// Part of speech the word was tagged with.
+    part_of_speech: Option<PartOfSpeech>,
+    /// Only show the definitions for the tagged part of speech.
+    collapse: bool,
+}
+
+struct Dict {
+    wordnet: WordNet,
+    all_words: Vec<String>,
+    signatures: Signatures,
+}
+
+impl Dict {
+    fn new(wn: WordNet) -> Self {
+        let all_words = wn.all_words();
+        Self {
+            wordnet: wn,
+            all_words,
+            signatures: Signatures::default(),
+        }
+    }
+
+    fn hover(&self, words: &[String]) -> Option<String> {
+        self.hover_in_context(words, &HoverContext::default())
+    }
+
+    /// Hover for the words, with the tagged part of speech first and the senses ordered by how well
+    /// they match the surrounding words.
+    fn hover_in_context(&self, words: &[String], context: &HoverContext) -> Option<String> {
+        let first_word = words.first()?;
+        let lemmas = self.wordnet.lemmatize(first_word);
+        if lemmas.all(|w| w.is_empty()) {
+            return None;
+        }
+        let mut content = String::new();
+        if words.len() > 1 {
+            writeln!(
+                content,
+                "View full definition for: {}\n",
+                words[1..].join(", ")
+            )
+            .unwrap();
+        }
+        let lesk = Lesk::extended(&self.wordnet).with_signatures(&self.signatures);
+        let tagged = context.part_of_speech;
+        let collapse = context.collapse;
+        let context = lesk.context(&context.words);
+        let mut entries = Vec::new();
+        lemmas.for_each(|pos, lemmas| {
+            lemmas.into_iter().for_each(|lemma| {
+                let synsets = self.wordnet.synsets_for(&lemma, pos);
+                let ranked = if context.is_empty() {
+                    synsets.into_iter().map(|ss| (0, ss)).collect()
+                } else {
+                    lesk.rank(synsets, &context)
+                };
+                entries.push((pos, lemma, ranked));
+            });
+        });
+        // entries for the tagged part of speech go first, then the one with the most likely sense
+        let best_score = |ranked: &[(usize, SynSet)]| ranked.first().map_or(0, |(score, _)| *score);
+        entries.sort_by_key(|(pos, _, ranked)| {
+            (
+                std::cmp::Reverse(Some(*pos) == tagged),
+                std::cmp::Reverse(best_score(ranked)),
+            )
+        });
+        let collapse = collapse
+            && tagged.is_some_and(|tagged| entries.iter().any(|(pos, _, _)| *pos == tagged));
+        let mut collapsed = Vec::new();
+        for (i, (pos, lemma, ranked)) in entries.into_iter().enumerate() {
+            if collapse && Some(pos) != tagged {
+                collapsed.push(format!("**{lemma}** _{pos}_"));
+                continue;
+            }
+            let most_likely = i == 0 && best_score(&ranked) > 0;
+            let synsets = ranked.into_iter().map(|(_, ss)| ss).collect();
+            let hover = self.render_hover(&lemma, synsets, most_likely);
+            writeln!(content, "{hover}\n").unwrap();
+        }
+        if !collapsed.is_empty() {
+            writeln!(content, "Also: {}", collapsed.join(", ")).unwrap();
+        }
+        Some(content.trim().to_owned())
+    }
+
+    fn render_hover(&self, word: &str, synsets: Vec<SynSet>, mark_first: bool) -> String {
+        let mut blocks = Vec::new();
 
         for pos in PartOfSpeech::iter() {
             let ss_pos = synsets
@@ -706,7 +1672,11 @@ impl Dict {
                         .iter()
                         .enumerate()
                         .map(|(i, ss)| {
-                            let mut s = format!("{}. {}.", i + 1, ss.definition);
+                            let mut s = if i == 0 && mark_first {
+                                format!("{}. _(most likely)_ {}.", i + 1, ss.definition)
+                            } else {
+                                format!("{}. {}.", i + 1, ss.definition)
+                            };
                             let examples = ss.examples.join("; ");
                             if !examples.is_empty() {
                                 s.push_str(" e.g. ");
@@ -762,12 +1732,36 @@ impl Dict {
         blocks.join("\n\n")
     }
 
-    fn all_info_file(&self, words: &[String]) -> Option<PathBuf> {
-        let info = self.all_info(words)?;
-        let filename = PathBuf::from(format!("/tmp/wordnet-ls-{}.md", words[0]));
-        let mut file = File::create(&filename).unwrap();
-        file.write_all(info.as_bytes()).unwrap();
-        Some(filename)
+    /// The words of the synsets related to the synset, by relationship.
+    fn semantic_relations(&self, synset: &SynSet) -> BTreeMap<SemanticRelation, BTreeSet<String>> {
+        let mut relations = BTreeMap::<SemanticRelation, BTreeSet<String>>::new();
+        for r in &synset.relationships {
+            // dangling pointers are reported by lint rather than here
+            if let Some(related) = self.wordnet.resolve(r.part_of_speech, r.synset_offset) {
+                relations
+                    .entry(r.relation)
+                    .or_default()
+                    .extend(related.synonyms());
+            }
+        }
+        relations
+    }
+
+    /// The word a lexical relationship points at, `None` if the pointer dangles.
+    fn resolve_lemma(&self, r: &LexicalRelationship) -> Option<String> {
+        let mut synset = self.wordnet.resolve(r.part_of_speech, r.synset_offset)?;
+        (r.target < synset.lemmas.len()).then(|| synset.lemmas.remove(r.target).word)
+    }
+
+    fn all_info_file(&self, words: &[String]) -> std::io::Result<Option<PathBuf>> {
+        let Some(info) = self.all_info(words) else {
+            return Ok(None);
+        };
+        // words can contain slashes, e.g. and/or
+        let filename = PathBuf::from(format!("/tmp/wordnet-ls-{}.md", words[0].replace('/', "_")));
+        let mut file = File::create(&filename)?;
+        file.write_all(info.as_bytes())?;
+        Ok(Some(filename))
     }
 
     fn all_info(&self, words: &[String]) -> Option<String> {
@@ -786,7 +1780,7 @@ impl Dict {
                     let synsets = self.wordnet.synsets_for(&lemma, pos);
                     writeln!(content, "# {lemma}").unwrap();
                     for (i, synset) in synsets.into_iter().enumerate() {
-                        let definition = synset.definition;
+                        let definition = &synset.definition;
                         let pos = synset.part_of_speech.to_string();
 
                         let i = i + 1;
@@ -798,17 +1792,8 @@ impl Dict {
                             writeln!(content).unwrap();
                         }
 
-                        let mut relationships: BTreeMap<SemanticRelation, BTreeSet<String>> =
-                            BTreeMap::new();
-                        for r in synset.relationships {
-                            relationships.entry(r.relation).or_default().extend(
-                                self.wordnet
-                                    .resolve(r.part_of_speech, r.synset_offset)
-                                    .unwrap()
-                                    .synonyms(),
-                            );
-                        }
-                        let relationships = relationships
+                        let relationships = self
+                            .semantic_relations(&synset)
                             .into_iter()
                             .map(|(r, w)| (r.to_string(), w))
                             .collect::<BTreeMap<_, _>>();
@@ -836,15 +1821,8 @@ impl Dict {
                                     l.word.clone(),
                                     l.relationships
                                         .iter()
-                                        .map(|lr| {
-                                            (
-                                                lr.relation,
-                                                self.wordnet
-                                                    .resolve(lr.part_of_speech, lr.synset_offset)
-                                                    .unwrap()
-                                                    .synonyms()[lr.target]
-                                                    .clone(),
-                                            )
+                                        .filter_map(|lr| {
+                                            Some((lr.relation, self.resolve_lemma(lr)?))
                                         })
                                         .filter(|(_, w)| *w != l.word)
                                         .collect::<BTreeMap<LexicalRelation, String>>(),
@@ -907,17 +1885,171 @@ impl Dict {
             })
             .collect()
     }
-}
 
-fn resolve_position(content: &str, pos: Position) -> usize {
-    let count = content
-        .lines()
-        .map(|l| l.len())
-        .take(pos.line as usize)
-        .sum::<usize>();
-    pos.line as usize + count + pos.character as usize
+    /// The configured usage domains that every sense of the word is marked with, `None` if any
+    /// sense is unmarked or the word is unknown.
+    fn usage_domains(&self, word: &str, usage_domains: &[String]) -> Option<BTreeSet<String>> {
+        let synsets = self.all_synsets(word);
+        let mut found = BTreeSet::new();
+        for (lemma, synset) in &synsets {
+            let domains = self
+                .synset_domains(
+                    synset,
+                    lemma,
+                    SemanticRelation::DomainOfSynsetUsage,
+                    LexicalRelation::DomainOfSynsetUsage,
+                )
+                .into_iter()
+                .filter(|domain| usage_domains.iter().any(|d| d.eq_ignore_ascii_case(domain)))
+                .collect::<BTreeSet<_>>();
+            if domains.is_empty() {
+                return None;
+            }
+            found.extend(domains);
+        }
+        (!synsets.is_empty()).then_some(found)
+    }
+
+    /// Names of the domains the synset is in, either for the whole synset or just the lemma.
+    fn synset_domains(
+        &self,
+        synset: &SynSet,
+        lemma: &str,
+        semantic_relation: SemanticRelation,
+        lexical_relation: LexicalRelation,
+    ) -> BTreeSet<String> {
+        let semantic = synset
+            .with_relationship(semantic_relation)
+            .into_iter()
+            .map(|r| (r.part_of_speech, r.synset_offset));
+        let lexical = synset
+            .lemmas
+            .iter()
+            .filter(|l| l.word.eq_ignore_ascii_case(lemma))
+            .flat_map(|l| l.with_relationship(lexical_relation))
+            .map(|r| (r.part_of_speech, r.synset_offset));
+        semantic
+            .chain(lexical)
+            .filter_map(|(pos, offset)| self.wordnet.resolve(pos, offset))
+            .flat_map(|ss| ss.synonyms())
+            .collect()
+    }
+
+    /// Whether the word is specific to a region, either by being a regional spelling of a synonym
+    /// or by having every sense marked with the domain of a region.
+    fn regional_variant(&self, word: &str) -> Option<RegionalVariant> {
+        let synsets = self.all_synsets(word);
+        if synsets.is_empty() {
+            return None;
+        }
+
+        for region in [Region::British, Region::American] {
+            let alternatives = diagnostics::spelling_candidates(word, region.other())
+                .into_iter()
+                .filter(|candidate| {
+                    self.wordnet
+                        .lemmatize(candidate)
+                        .iter()
+                        .flatten()
+                        .any(|lemma| {
+                            synsets
+                                .iter()
+                                .any(|(_, ss)| ss.lemmas.iter().any(|l| l.word == *lemma))
+                        })
+                })
+                .collect::<Vec<_>>();
+            if !alternatives.is_empty() {
+                return Some(RegionalVariant {
+                    region,
+                    alternatives,
+                });
+            }
+        }
+
+        let region = self.region_domain(word)?;
+        let mut alternatives = synsets
+            .iter()
+            .flat_map(|(lemma, ss)| ss.synonyms().into_iter().filter(move |s| s != lemma))
+            .filter(|s| s != word && self.region_domain(s) != Some(region))
+            .collect::<Vec<_>>();
+        alternatives.sort_unstable();
+        alternatives.dedup();
+        alternatives.truncate(MAX_REPLACEMENTS);
+        Some(RegionalVariant {
+            region,
+            alternatives,
+        })
+    }
+
+    /// The region that every sense of the word is marked with.
+    fn region_domain(&self, word: &str) -> Option<Region> {
+        let synsets = self.all_synsets(word);
+        [Region::British, Region::American]
+            .into_iter()
+            .find(|region| {
+                !synsets.is_empty()
+                    && synsets.iter().all(|(lemma, ss)| {
+                        self.synset_domains(
+                            ss,
+                            lemma,
+                            SemanticRelation::DomainOfSynsetRegion,
+                            LexicalRelation::DomainOfSynsetRegion,
+                        )
+                        .iter()
+                        .any(|domain| region.matches_domain(domain))
+                    })
+            })
+    }
+
+    /// All of the synsets for the lemmas of a word, along with the lemma they were found for.
+    fn all_synsets(&self, word: &str) -> Vec<(String, SynSet)> {
+        let lemmas = self.wordnet.lemmatize(word);
+        let mut synsets = Vec::new();
+        for pos in PartOfSpeech::iter() {
+            for lemma in lemmas.get(pos) {
+                for synset in self.wordnet.synsets_for(lemma, pos) {
+                    synsets.push((lemma.clone(), synset));
+                }
+            }
+        }
+        synsets
+    }
+
+    /// Alternatives for a word that are not themselves marked with the usage domains, preferring
+    /// synonyms and falling back to hypernyms.
+    fn neutral_alternatives(&self, word: &str, usage_domains: &[String]) -> Vec<String> {
+        let mut synonyms = BTreeSet::new();
+        let mut hypernyms = BTreeSet::new();
+        for (lemma, synset) in self.all_synsets(word) {
+            synonyms.extend(synset.synonyms().into_iter().filter(|s| *s != lemma));
+            hypernyms.extend(
+                synset
+                    .with_relationship(SemanticRelation::Hypernym)
+                    .into_iter()
+                    .filter_map(|r| self.wordnet.resolve(r.part_of_speech, r.synset_offset))
+                    .flat_map(|ss| ss.synonyms()),
+            );
+        }
+        let neutral = |words: BTreeSet<String>| {
+            words
+                .into_iter()
+                .filter(|w| w != word)
+                .filter(|w| self.usage_domains(w, usage_domains).is_none())
+                .take(MAX_REPLACEMENTS)
+                .collect::<Vec<_>>()
+        };
+        let alternatives = neutral(synonyms);
+        if alternatives.is_empty() {
+            neutral(hypernyms)
+        } else {
+            alternatives
+        }
+    }
 }
 
+/// Limit the number of quick fixes offered for a single diagnostic.
+const MAX_REPLACEMENTS: usize = 10;
+
 #[derive(Debug, Serialize, Deserialize)]
 struct DefineCommandArguments {
     word: String,
@@ -930,10 +2062,211 @@ mod tests {
     use super::*;
     use expect_test::{expect, Expect};
 
+    /// A small database with the words the hover, definition and completion tests look up.
+    fn dict() -> (tempfile::TempDir, Dict) {
+        use wordnet_ls::wordnet::WordNetBuilder;
+        use PartOfSpeech::{Adjective, Noun, Verb};
+
+        let mut builder = WordNetBuilder::new();
+        let colloquialism = builder.add_synset(
+            Noun,
+            &["colloquialism"],
+            "a colloquial expression; characteristic of spoken or written communication that seeks to imitate informal speech",
+            &[],
+        );
+        let adult = builder.add_synset(
+            Noun,
+            &["adult", "grownup"],
+            "a fully developed person from maturity onward",
+            &[],
+        );
+        let woman = builder.add_synset(
+            Noun,
+            &["woman", "adult female"],
+            "an adult female person (as opposed to a man)",
+            &["the woman kept house while the man hunted"],
+        );
+        let man = builder.add_synset(
+            Noun,
+            &["man", "adult male"],
+            "an adult person who is male (as opposed to a woman)",
+            &["there were two women and six men on the bus"],
+        );
+        for person in [woman, man] {
+            builder.add_relation(person, SemanticRelation::Hypernym, adult);
+            builder.add_relation(adult, SemanticRelation::Hyponym, person);
+        }
+        builder.add_lexical_relation(woman, 0, LexicalRelation::Antonym, man, 0);
+        builder.add_lexical_relation(man, 0, LexicalRelation::Antonym, woman, 0);
+        let partner = builder.add_synset(
+            Noun,
+            &["woman"],
+            "a female person who plays a significant role (wife or mistress or girlfriend) in the life of a particular man",
+            &["he was faithful to his woman"],
+        );
+        builder.add_relation(
+            partner,
+            SemanticRelation::DomainOfSynsetUsage,
+            colloquialism,
+        );
+        let womanhood = builder.add_synset(
+            Noun,
+            &["womanhood", "woman", "fair sex"],
+            "women as a class",
+            &[
+                "woman is the glory of creation",
+                "the fair sex gathered on the veranda",
+            ],
+        );
+        builder.add_lexical_relation(
+            womanhood,
+            0,
+            LexicalRelation::DerivationallyRelatedForm,
+            woman,
+            0,
+        );
+        builder.add_lexical_relation(
+            woman,
+            0,
+            LexicalRelation::DerivationallyRelatedForm,
+            womanhood,
+            0,
+        );
+
+        let score = builder.add_synset(
+            Noun,
+            &["score"],
+            "the act of scoring in a game or sport",
+            &[],
+        );
+        let run = builder.add_synset(
+            Noun,
+            &["run", "tally"],
+            "a score in baseball made by a runner touching all four bases safely",
+            &["the Yankees scored 3 runs in the bottom of the 9th"],
+        );
+        builder.add_relation(run, SemanticRelation::Hypernym, score);
+        builder.add_relation(score, SemanticRelation::Hyponym, run);
+        let travel = builder.add_synset(Verb, &["travel", "go", "move"], "change location", &[]);
+        let run = builder.add_synset(
+            Verb,
+            &["run"],
+            "move fast by using one's feet, with one foot off the ground at any given time",
+            &["Don't run--you'll be out of breath"],
+        );
+        builder.add_relation(run, SemanticRelation::Hypernym, travel);
+        let idle = builder.add_synset(
+            Verb,
+            &["idle", "laze"],
+            "run disconnected or idle",
+            &["the engine is idling"],
+        );
+        let operate = builder.add_synset(
+            Verb,
+            &["run", "operate", "function"],
+            "perform as expected when applied",
+            &["The washing machine won't go unless it's plugged in"],
+        );
+        builder.add_lexical_relation(operate, 0, LexicalRelation::Antonym, idle, 0);
+        builder.add_lexical_relation(idle, 0, LexicalRelation::Antonym, operate, 0);
+
+        let edge_tool = builder.add_synset(Noun, &["edge tool"], "any cutting tool", &[]);
+        let ax = builder.add_synset(
+            Noun,
+            &["ax", "axe"],
+            "an edge tool with a heavy bladed head mounted across a handle",
+            &[],
+        );
+        builder.add_relation(ax, SemanticRelation::Hypernym, edge_tool);
+        builder.add_relation(edge_tool, SemanticRelation::Hyponym, ax);
+        builder.add_synset(
+            Noun,
+            &["axis"],
+            "a straight line through a body or figure that satisfies certain conditions",
+            &[],
+        );
+        builder.add_synset(
+            Verb,
+            &["ax", "axe"],
+            "chop or split with an ax",
+            &["axe wood"],
+        );
+        builder.add_exception(Noun, "axes", "axis");
+
+        builder.add_synset(
+            Noun,
+            &["iodine", "I"],
+            "a nonmetallic element belonging to the halogens",
+            &[],
+        );
+        builder.add_synset(
+            Verb,
+            &["be"],
+            "have the quality of being",
+            &["John is rich"],
+        );
+        builder.add_exception(Verb, "is", "be");
+
+        let living = builder.add_synset(
+            Noun,
+            &["living"],
+            "people who are still living",
+            &["save your pity for the living"],
+        );
+        let dead = builder.add_synset(
+            Noun,
+            &["dead"],
+            "people who are no longer living",
+            &["they buried the dead"],
+        );
+        builder.add_lexical_relation(living, 0, LexicalRelation::Antonym, dead, 0);
+        builder.add_synset(
+            Verb,
+            &["live", "be"],
+            "have life, be alive",
+            &["My grandfather lived until the end of war"],
+        );
+        builder.add_synset(
+            Adjective,
+            &["living"],
+            "pertaining to living persons",
+            &["within living memory"],
+        );
+        builder.add_synset(
+            Noun,
+            &["living thing", "animate thing"],
+            "a living (or once living) entity",
+            &[],
+        );
+        builder.add_synset(
+            Noun,
+            &["living room", "sitting room"],
+            "a room in a private house or establishment where people can sit and talk and relax",
+            &[],
+        );
+
+        builder.add_synset(Noun, &["beta"], "the 2nd letter of the Greek alphabet", &[]);
+        builder.add_synset(
+            Adjective,
+            &["beta"],
+            "second in order of importance",
+            &["the candidate, considered a beta male, was perceived to be unable to lead his party to victory"],
+        );
+        builder.add_synset(
+            Noun,
+            &["Boston", "Hub of the Universe"],
+            "state capital and largest city of Massachusetts",
+            &[],
+        );
+        builder.add_synset(Noun, &["bostonian"], "a resident of Boston", &[]);
+
+        fixture::dict(&builder)
+    }
+
     #[test]
     fn hover_woman() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let hover = dict.hover(&["woman".to_owned()]).unwrap();
         let expected = expect![[r#"
             **woman** _noun_
@@ -947,10 +2280,25 @@ mod tests {
         expected.assert_eq(&hover);
     }
 
+    #[test]
+    fn hover_woman_fixture() {
+        let (_dir, dict) = dict();
+        let hover = dict.hover(&["woman".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            **woman** _noun_
+            1. an adult female person (as opposed to a man). e.g. the woman kept house while the man hunted.
+            2. a female person who plays a significant role (wife or mistress or girlfriend) in the life of a particular man. e.g. he was faithful to his woman.
+            3. women as a class. e.g. woman is the glory of creation; the fair sex gathered on the veranda.
+
+            - **synonyms**: adult female, fair sex, womanhood
+            - **antonyms**: man"#]];
+        expected.assert_eq(&hover);
+    }
+
     #[test]
     fn all_info_woman() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let info = dict.all_info(&["woman".to_owned()]).unwrap();
         let expected = expect![[r#"
             # woman
@@ -986,17 +2334,40 @@ mod tests {
     }
 
     #[test]
-    fn hover_run() {
-        let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
-        let hover = dict.hover(&["run".to_owned()]).unwrap();
+    fn all_info_woman_fixture() {
+        let (_dir, dict) = dict();
+        let info = dict.all_info(&["woman".to_owned()]).unwrap();
         let expected = expect![[r#"
-            **run** _noun_
-            1. a score in baseball made by a runner touching all four bases safely. e.g. the Yankees scored 3 runs in the bottom of the 9th; their first tally came in the 3rd inning.
-            2. the act of testing something. e.g. in the experimental trials the amount of carbon was measured separately; he called each flip of the coin a new trial.
-            3. a race run on foot. e.g. she broke the record for the half-mile run.
-            4. an unbroken series of events. e.g. had a streak of bad luck; Nicklaus had a run of birdies.
-            5. (American football) a play in which a player attempts to carry the ball through or past the opposing team. e.g. the defensive line braced to stop the run; the coach put great emphasis on running.
+            # woman
+
+            1. _noun_ an adult female person (as opposed to a man). e.g. the woman kept house while the man hunted.
+            - **hypernym**: adult, grownup
+            - **synonyms**:
+              - adult_female
+
+            2. _noun_ a female person who plays a significant role (wife or mistress or girlfriend) in the life of a particular man. e.g. he was faithful to his woman.
+            - **domain of synset usage**: colloquialism
+
+            3. _noun_ women as a class. e.g. woman is the glory of creation; the fair sex gathered on the veranda.
+            - **synonyms**:
+              - fair_sex
+              - womanhood:
+                - **derivationally related form**: woman"#]];
+        expected.assert_eq(&info);
+    }
+
+    #[test]
+    fn hover_run() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
+        let hover = dict.hover(&["run".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            **run** _noun_
+            1. a score in baseball made by a runner touching all four bases safely. e.g. the Yankees scored 3 runs in the bottom of the 9th; their first tally came in the 3rd inning.
+            2. the act of testing something. e.g. in the experimental trials the amount of carbon was measured separately; he called each flip of the coin a new trial.
+            3. a race run on foot. e.g. she broke the record for the half-mile run.
+            4. an unbroken series of events. e.g. had a streak of bad luck; Nicklaus had a run of birdies.
+            5. (American football) a play in which a player attempts to carry the ball through or past the opposing team. e.g. the defensive line braced to stop the run; the coach put great emphasis on running.
             6. a regular trip. e.g. the ship made its run in record time.
             7. the act of running; traveling on foot at a fast pace. e.g. he broke into a run; his daily run keeps him fit.
             8. the continuous period of time during which something (a machine or a factory) operates or continues in operation. e.g. the assembly line was on a 12-hour run.
@@ -1059,10 +2430,29 @@ mod tests {
         expected.assert_eq(&hover);
     }
 
+    #[test]
+    fn hover_run_fixture() {
+        let (_dir, dict) = dict();
+        let hover = dict.hover(&["run".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            **run** _noun_
+            1. a score in baseball made by a runner touching all four bases safely. e.g. the Yankees scored 3 runs in the bottom of the 9th.
+
+            - **synonyms**: tally
+
+            **run** _verb_
+            1. move fast by using one's feet, with one foot off the ground at any given time. e.g. Don't run--you'll be out of breath.
+            2. perform as expected when applied. e.g. The washing machine won't go unless it's plugged in.
+
+            - **synonyms**: function, operate
+            - **antonyms**: idle"#]];
+        expected.assert_eq(&hover);
+    }
+
     #[test]
     fn all_info_run() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let info = dict.all_info(&["run".to_owned()]).unwrap();
         let expected = expect![[r#"
             # run
@@ -1430,14 +2820,38 @@ mod tests {
         expected.assert_eq(&info);
     }
 
+    #[test]
+    fn all_info_run_fixture() {
+        let (_dir, dict) = dict();
+        let info = dict.all_info(&["run".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            # run
+
+            1. _noun_ a score in baseball made by a runner touching all four bases safely. e.g. the Yankees scored 3 runs in the bottom of the 9th.
+            - **hypernym**: score
+            - **synonyms**:
+              - tally
+
+            # run
+
+            1. _verb_ move fast by using one's feet, with one foot off the ground at any given time. e.g. Don't run--you'll be out of breath.
+            - **hypernym**: go, move, travel
+
+            2. _verb_ perform as expected when applied. e.g. The washing machine won't go unless it's plugged in.
+            - **synonyms**:
+              - function
+              - operate"#]];
+        expected.assert_eq(&info);
+    }
+
     #[test]
     fn all_info_all_words() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let len = dict
             .all_words
             .iter()
-            .map(|w| dict.all_info(&[w.clone()]).unwrap().len())
+            .map(|w| dict.all_info(std::slice::from_ref(w)).unwrap().len())
             .sum::<usize>();
         let expected = expect![[r#"
             54641063
@@ -1445,10 +2859,24 @@ mod tests {
         expected.assert_debug_eq(&len);
     }
 
+    #[test]
+    fn all_info_all_words_fixture() {
+        let (_dir, dict) = dict();
+        let len = dict
+            .all_words
+            .iter()
+            .map(|w| dict.all_info(std::slice::from_ref(w)).unwrap().len())
+            .sum::<usize>();
+        let expected = expect![[r#"
+            5899
+        "#]];
+        expected.assert_debug_eq(&len);
+    }
+
     #[test]
     fn hover_axes() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let hover = dict.hover(&["axes".to_owned()]).unwrap();
         let expected = expect![[r#"
             **ax** _noun_
@@ -1485,10 +2913,40 @@ mod tests {
         expected.assert_eq(&hover);
     }
 
+    #[test]
+    fn hover_axes_fixture() {
+        let (_dir, dict) = dict();
+        let hover = dict.hover(&["axes".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            **ax** _noun_
+            1. an edge tool with a heavy bladed head mounted across a handle.
+
+            - **synonyms**: axe
+
+            **axe** _noun_
+            1. an edge tool with a heavy bladed head mounted across a handle.
+
+            - **synonyms**: ax
+
+            **axis** _noun_
+            1. a straight line through a body or figure that satisfies certain conditions.
+
+            **ax** _verb_
+            1. chop or split with an ax. e.g. axe wood.
+
+            - **synonyms**: axe
+
+            **axe** _verb_
+            1. chop or split with an ax. e.g. axe wood.
+
+            - **synonyms**: ax"#]];
+        expected.assert_eq(&hover);
+    }
+
     #[test]
     fn hover_is() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let hover = dict.hover(&["is".to_owned()]).unwrap();
         let expected = expect![[r#"
             **i** _noun_
@@ -1518,10 +2976,28 @@ mod tests {
         expected.assert_eq(&hover);
     }
 
+    #[test]
+    fn hover_is_fixture() {
+        let (_dir, dict) = dict();
+        let hover = dict.hover(&["is".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            **i** _noun_
+            1. a nonmetallic element belonging to the halogens.
+
+            - **synonyms**: I, iodine
+
+            **be** _verb_
+            1. have the quality of being. e.g. John is rich.
+            2. have life, be alive. e.g. My grandfather lived until the end of war.
+
+            - **synonyms**: live"#]];
+        expected.assert_eq(&hover);
+    }
+
     #[test]
     fn hover_multiple_words() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let hover = dict
             .hover(&["living".to_owned(), "living_thing".to_owned()])
             .unwrap();
@@ -1560,10 +3036,34 @@ mod tests {
         expected.assert_eq(&hover);
     }
 
+    #[test]
+    fn hover_multiple_words_fixture() {
+        let (_dir, dict) = dict();
+        let hover = dict
+            .hover(&["living".to_owned(), "living_thing".to_owned()])
+            .unwrap();
+        let expected = expect![[r#"
+            View full definition for: living_thing
+
+            **living** _noun_
+            1. people who are still living. e.g. save your pity for the living.
+
+            - **antonyms**: dead
+
+            **live** _verb_
+            1. have life, be alive. e.g. My grandfather lived until the end of war.
+
+            - **synonyms**: be
+
+            **living** _adjective_
+            1. pertaining to living persons. e.g. within living memory."#]];
+        expected.assert_eq(&hover);
+    }
+
     #[test]
     fn all_info_axes() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let info = dict.all_info(&["axes".to_owned()]).unwrap();
         let expected = expect![[r#"
             # ax
@@ -1644,10 +3144,47 @@ mod tests {
         expected.assert_eq(&info);
     }
 
+    #[test]
+    fn all_info_axes_fixture() {
+        let (_dir, dict) = dict();
+        let info = dict.all_info(&["axes".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            # ax
+
+            1. _noun_ an edge tool with a heavy bladed head mounted across a handle.
+            - **hypernym**: edge_tool
+            - **synonyms**:
+              - axe
+
+            # axe
+
+            1. _noun_ an edge tool with a heavy bladed head mounted across a handle.
+            - **hypernym**: edge_tool
+            - **synonyms**:
+              - ax
+
+            # axis
+
+            1. _noun_ a straight line through a body or figure that satisfies certain conditions.
+
+            # ax
+
+            1. _verb_ chop or split with an ax. e.g. axe wood.
+            - **synonyms**:
+              - axe
+
+            # axe
+
+            1. _verb_ chop or split with an ax. e.g. axe wood.
+            - **synonyms**:
+              - ax"#]];
+        expected.assert_eq(&info);
+    }
+
     #[test]
     fn all_info_multiple_words() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let info = dict
             .all_info(&["axes".to_owned(), "beta".to_owned()])
             .unwrap();
@@ -1751,14 +3288,196 @@ mod tests {
         expected.assert_eq(&info);
     }
 
+    #[test]
+    fn all_info_multiple_words_fixture() {
+        let (_dir, dict) = dict();
+        let info = dict
+            .all_info(&["axes".to_owned(), "beta".to_owned()])
+            .unwrap();
+        let expected = expect![[r#"
+            # ax
+
+            1. _noun_ an edge tool with a heavy bladed head mounted across a handle.
+            - **hypernym**: edge_tool
+            - **synonyms**:
+              - axe
+
+            # axe
+
+            1. _noun_ an edge tool with a heavy bladed head mounted across a handle.
+            - **hypernym**: edge_tool
+            - **synonyms**:
+              - ax
+
+            # axis
+
+            1. _noun_ a straight line through a body or figure that satisfies certain conditions.
+
+            # ax
+
+            1. _verb_ chop or split with an ax. e.g. axe wood.
+            - **synonyms**:
+              - axe
+
+            # axe
+
+            1. _verb_ chop or split with an ax. e.g. axe wood.
+            - **synonyms**:
+              - ax
+
+            # beta
+
+            1. _noun_ the 2nd letter of the Greek alphabet.
+
+            # beta
+
+            1. _adjective_ second in order of importance. e.g. the candidate, considered a beta male, was perceived to be unable to lead his party to victory."#]];
+        expected.assert_eq(&info);
+    }
+
+    #[test]
+    fn all_info_dangling_lexical_pointer() {
+        let mut builder = wordnet_ls::wordnet::WordNetBuilder::new();
+        let dog = builder.add_synset(PartOfSpeech::Noun, &["dog", "hound"], "a canine", &[]);
+        let cat = builder.add_synset(PartOfSpeech::Noun, &["cat"], "a feline", &[]);
+        builder.add_lexical_relation(dog, 1, LexicalRelation::Antonym, cat, 0);
+        builder.add_lexical_relation(dog, 1, LexicalRelation::DerivationallyRelatedForm, cat, 0);
+        let dir = tempfile::tempdir().unwrap();
+        builder.write(dir.path()).unwrap();
+        // point the antonym past the lemmas of cat and the derivation at no synset at all
+        let data = dir.path().join("data.noun");
+        let content = std::fs::read_to_string(&data).unwrap();
+        let (start, _) = content.match_indices("! ").next().unwrap();
+        let antonym = &content[start..start + 16];
+        let content = content
+            .replace(antonym, &antonym.replace("0201", "0205"))
+            .replace(&antonym.replace('!', "+"), "+ 99999999 n 0201");
+        std::fs::write(&data, content).unwrap();
+        let dict = Dict::new(WordNet::new(dir.path()).unwrap());
+        let info = dict.all_info(&["dog".to_owned()]).unwrap();
+        let expected = expect![[r#"
+            # dog
+
+            1. _noun_ a canine.
+            - **synonyms**:
+              - hound"#]];
+        expected.assert_eq(&info);
+    }
+
+    #[test]
+    fn regional_variants() {
+        let mut builder = wordnet_ls::wordnet::WordNetBuilder::new();
+        builder.add_synset(PartOfSpeech::Noun, &["colour", "color"], "a hue", &[]);
+        builder.add_synset(PartOfSpeech::Verb, &["fulfil", "fulfill"], "carry out", &[]);
+        builder.add_synset(
+            PartOfSpeech::Noun,
+            &["traveller", "traveler"],
+            "a voyager",
+            &[],
+        );
+        let (_dir, dict) = fixture::dict(&builder);
+        let variants = [
+            "colour",
+            "color",
+            "fulfil",
+            "fulfill",
+            "traveller",
+            "traveler",
+        ]
+        .map(|word| format!("{word}: {:?}", dict.regional_variant(word)));
+        let expected = expect![[r#"
+            [
+                "colour: Some(RegionalVariant { region: British, alternatives: [\"color\"] })",
+                "color: Some(RegionalVariant { region: American, alternatives: [\"colour\"] })",
+                "fulfil: None",
+                "fulfill: None",
+                "traveller: Some(RegionalVariant { region: British, alternatives: [\"traveler\"] })",
+                "traveler: Some(RegionalVariant { region: American, alternatives: [\"traveller\"] })",
+            ]
+        "#]];
+        expected.assert_debug_eq(&variants);
+    }
+
+    #[test]
+    fn configured_wordnet_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, errors) = load_wordnet(&InitializationOptions {
+            wordnet: Some(dir.path().join("missing")),
+            ..Default::default()
+        });
+        assert_eq!(
+            errors[0],
+            format!(
+                "WordNet wasn't found at the configured location {:?}, looking for it elsewhere",
+                dir.path().join("missing")
+            )
+        );
+    }
+
+    /// A database with a few collocations to tokenize with.
+    fn collocations() -> (tempfile::TempDir, Dict) {
+        let mut builder = wordnet_ls::wordnet::WordNetBuilder::new();
+        builder.add_synset(
+            PartOfSpeech::Noun,
+            &["living_thing"],
+            "a living entity",
+            &[],
+        );
+        builder.add_synset(
+            PartOfSpeech::Adjective,
+            &["for_sale"],
+            "available to buy",
+            &[],
+        );
+        fixture::dict(&builder)
+    }
+
     fn check_get_words(content: &str, expected: Expect) {
+        let (_dir, dict) = collocations();
+        let tokens = dict.wordnet.tokenize(content);
         let words = (0..content.len())
-            .map(|i| (i, get_words_from_content(content, 0, i)))
+            .map(|i| (i, get_words_from_tokens(&tokens, i)))
             .map(|(i, ret)| format!("{i}: {ret:?}"))
             .collect::<Vec<_>>();
         expected.assert_debug_eq(&words)
     }
 
+    #[test]
+    fn get_prefix() {
+        let (_dir, dict) = collocations();
+        let content = "A living thing, Runn";
+        let tokens = dict.wordnet.tokenize(content);
+        let prefixes = (0..=content.len())
+            .map(|i| format!("{i}: {:?}", get_prefix_from_tokens(&tokens, i)))
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                "0: None",
+                "1: Some(\"A\")",
+                "2: None",
+                "3: Some(\"l\")",
+                "4: Some(\"li\")",
+                "5: Some(\"liv\")",
+                "6: Some(\"livi\")",
+                "7: Some(\"livin\")",
+                "8: Some(\"living\")",
+                "9: None",
+                "10: Some(\"t\")",
+                "11: Some(\"th\")",
+                "12: Some(\"thi\")",
+                "13: Some(\"thin\")",
+                "14: Some(\"thing\")",
+                "15: None",
+                "16: None",
+                "17: Some(\"R\")",
+                "18: Some(\"Ru\")",
+                "19: Some(\"Run\")",
+                "20: Some(\"Runn\")",
+            ]
+        "#]];
+        expected.assert_debug_eq(&prefixes)
+    }
+
     #[test]
     fn get_word() {
         let text = "runner";
@@ -1780,14 +3499,14 @@ mod tests {
         let text = "a runner runs";
         let expected = expect![[r#"
             [
-                "0: [\"a\", \"a_runner\", \"a_runner_runs\"]",
+                "0: [\"a\"]",
                 "1: []",
-                "2: [\"runner\", \"runner_runs\"]",
-                "3: [\"runner\", \"runner_runs\"]",
-                "4: [\"runner\", \"runner_runs\"]",
-                "5: [\"runner\", \"runner_runs\"]",
-                "6: [\"runner\", \"runner_runs\"]",
-                "7: [\"runner\", \"runner_runs\"]",
+                "2: [\"runner\"]",
+                "3: [\"runner\"]",
+                "4: [\"runner\"]",
+                "5: [\"runner\"]",
+                "6: [\"runner\"]",
+                "7: [\"runner\"]",
                 "8: []",
                 "9: [\"runs\"]",
                 "10: [\"runs\"]",
@@ -1808,15 +3527,15 @@ mod tests {
                 "2: [\"new\"]",
                 "3: []",
                 "4: []",
-                "5: [\"for\", \"for_sale\", \"for_sale.\"]",
-                "6: [\"for\", \"for_sale\", \"for_sale.\"]",
-                "7: [\"for\", \"for_sale\", \"for_sale.\"]",
-                "8: []",
-                "9: [\"sale\", \"sale.\"]",
-                "10: [\"sale\", \"sale.\"]",
-                "11: [\"sale\", \"sale.\"]",
-                "12: [\"sale\", \"sale.\"]",
-                "13: [\"sale\", \"sale.\"]",
+                "5: [\"for_sale\", \"for\"]",
+                "6: [\"for_sale\", \"for\"]",
+                "7: [\"for_sale\", \"for\"]",
+                "8: [\"for_sale\"]",
+                "9: [\"for_sale\", \"sale\"]",
+                "10: [\"for_sale\", \"sale\"]",
+                "11: [\"for_sale\", \"sale\"]",
+                "12: [\"for_sale\", \"sale\"]",
+                "13: []",
             ]
         "#]];
         check_get_words(text, expected)
@@ -1849,18 +3568,18 @@ mod tests {
         let text = "living thing";
         let expected = expect![[r#"
             [
-                "0: [\"living\", \"living_thing\"]",
-                "1: [\"living\", \"living_thing\"]",
-                "2: [\"living\", \"living_thing\"]",
-                "3: [\"living\", \"living_thing\"]",
-                "4: [\"living\", \"living_thing\"]",
-                "5: [\"living\", \"living_thing\"]",
-                "6: []",
-                "7: [\"thing\"]",
-                "8: [\"thing\"]",
-                "9: [\"thing\"]",
-                "10: [\"thing\"]",
-                "11: [\"thing\"]",
+                "0: [\"living_thing\", \"living\"]",
+                "1: [\"living_thing\", \"living\"]",
+                "2: [\"living_thing\", \"living\"]",
+                "3: [\"living_thing\", \"living\"]",
+                "4: [\"living_thing\", \"living\"]",
+                "5: [\"living_thing\", \"living\"]",
+                "6: [\"living_thing\"]",
+                "7: [\"living_thing\", \"thing\"]",
+                "8: [\"living_thing\", \"thing\"]",
+                "9: [\"living_thing\", \"thing\"]",
+                "10: [\"living_thing\", \"thing\"]",
+                "11: [\"living_thing\", \"thing\"]",
             ]
         "#]];
         check_get_words(text, expected)
@@ -1871,11 +3590,11 @@ mod tests {
         let text = "'hood";
         let expected = expect![[r#"
             [
-                "0: [\"hood\", \"'hood\"]",
-                "1: [\"hood\", \"'hood\"]",
-                "2: [\"hood\", \"'hood\"]",
-                "3: [\"hood\", \"'hood\"]",
-                "4: [\"hood\", \"'hood\"]",
+                "0: []",
+                "1: [\"hood\"]",
+                "2: [\"hood\"]",
+                "3: [\"hood\"]",
+                "4: [\"hood\"]",
             ]
         "#]];
         check_get_words(text, expected)
@@ -1886,1557 +3605,37 @@ mod tests {
         let text = "'hood'";
         let expected = expect![[r#"
             [
-                "0: [\"'hood\", \"hood\", \"hood'\", \"'hood'\"]",
-                "1: [\"'hood\", \"hood\", \"hood'\", \"'hood'\"]",
-                "2: [\"'hood\", \"hood\", \"hood'\", \"'hood'\"]",
-                "3: [\"'hood\", \"hood\", \"hood'\", \"'hood'\"]",
-                "4: [\"'hood\", \"hood\", \"hood'\", \"'hood'\"]",
-                "5: [\"'hood\", \"hood\", \"hood'\", \"'hood'\"]",
+                "0: []",
+                "1: [\"hood\"]",
+                "2: [\"hood\"]",
+                "3: [\"hood\"]",
+                "4: [\"hood\"]",
+                "5: []",
             ]
         "#]];
         check_get_words(text, expected)
     }
 
     #[test]
-    fn get_words_all_punctuations() {
+    fn context_around_word() {
+        let content = "one two three\nfour five six seven\neight";
+        let context = get_context_from_content(content, Position::new(1, 6), 2);
+        let expected = expect![[r#"
+            [
+                "three",
+                "four",
+                "six",
+                "seven",
+            ]
+        "#]];
+        expected.assert_debug_eq(&context);
+    }
+
+    #[test]
+    fn complete_spaces() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
-        let words = wn
-            .all_words()
-            .into_iter()
-            .filter_map(|w| {
-                let mut non_alpha_chars =
-                    w.chars().filter(|c| !c.is_alphabetic()).collect::<Vec<_>>();
-                non_alpha_chars.sort();
-                non_alpha_chars.dedup();
-                if non_alpha_chars.is_empty() {
-                    None
-                } else {
-                    Some((non_alpha_chars, w))
-                }
-            })
-            .fold(BTreeMap::new(), |mut acc, (c, w)| {
-                acc.insert(c, w);
-                acc
-            })
-            .into_values()
-            .map(|word| {
-                let words = get_words_from_content(&word, 0, 0);
-                let found = words.contains(&word);
-                (word, words, found)
-            })
-            .collect::<Vec<_>>();
-        let expected = expect![[r#"
-            [
-                (
-                    "ta'ziyeh",
-                    [
-                        "ta'ziyeh",
-                    ],
-                    true,
-                ),
-                (
-                    "will-o'-the-wisp",
-                    [
-                        "will-o'-the-wisp",
-                    ],
-                    true,
-                ),
-                (
-                    "st.-bruno's-lily",
-                    [
-                        "st.-bruno's-lily",
-                    ],
-                    true,
-                ),
-                (
-                    "wine-maker's_yeast",
-                    [
-                        "wine-maker's_yeast",
-                    ],
-                    true,
-                ),
-                (
-                    "st._peter's_wreath",
-                    [
-                        "st._peter's_wreath",
-                    ],
-                    true,
-                ),
-                (
-                    "brodmann's_area_17",
-                    [
-                        "brodmann's_area_17",
-                    ],
-                    true,
-                ),
-                (
-                    "young's_modulus",
-                    [
-                        "young's_modulus",
-                    ],
-                    true,
-                ),
-                (
-                    "zig-zag",
-                    [
-                        "zig-zag",
-                    ],
-                    true,
-                ),
-                (
-                    ".22-calibre",
-                    [
-                        "22-calibre",
-                        ".22-calibre",
-                    ],
-                    true,
-                ),
-                (
-                    ".38-calibre",
-                    [
-                        "38-calibre",
-                        ".38-calibre",
-                    ],
-                    true,
-                ),
-                (
-                    ".45-calibre",
-                    [
-                        "45-calibre",
-                        ".45-calibre",
-                    ],
-                    true,
-                ),
-                (
-                    "wrangell-st._elias_national_park",
-                    [
-                        "wrangell-st._elias_national_park",
-                    ],
-                    true,
-                ),
-                (
-                    "10-membered",
-                    [
-                        "10-membered",
-                    ],
-                    true,
-                ),
-                (
-                    "401-k",
-                    [
-                        "401-k",
-                    ],
-                    true,
-                ),
-                (
-                    "401-k_plan",
-                    [
-                        "401-k_plan",
-                    ],
-                    true,
-                ),
-                (
-                    "k-dur_20",
-                    [
-                        "k-dur_20",
-                    ],
-                    true,
-                ),
-                (
-                    "v-1",
-                    [
-                        "v-1",
-                    ],
-                    true,
-                ),
-                (
-                    "iodine-125",
-                    [
-                        "iodine-125",
-                    ],
-                    true,
-                ),
-                (
-                    "12-tone_system",
-                    [
-                        "12-tone_system",
-                    ],
-                    true,
-                ),
-                (
-                    "iodine-131",
-                    [
-                        "iodine-131",
-                    ],
-                    true,
-                ),
-                (
-                    "carbon-14_dating",
-                    [
-                        "carbon-14_dating",
-                    ],
-                    true,
-                ),
-                (
-                    "18-karat_gold",
-                    [
-                        "18-karat_gold",
-                    ],
-                    true,
-                ),
-                (
-                    "9-11",
-                    [
-                        "9-11",
-                    ],
-                    true,
-                ),
-                (
-                    "m-1_rifle",
-                    [
-                        "m-1_rifle",
-                    ],
-                    true,
-                ),
-                (
-                    "r-2",
-                    [
-                        "r-2",
-                    ],
-                    true,
-                ),
-                (
-                    "24-karat_gold",
-                    [
-                        "24-karat_gold",
-                    ],
-                    true,
-                ),
-                (
-                    "b-52",
-                    [
-                        "b-52",
-                    ],
-                    true,
-                ),
-                (
-                    "thorium-228",
-                    [
-                        "thorium-228",
-                    ],
-                    true,
-                ),
-                (
-                    "guided_bomb_unit-28",
-                    [
-                        "guided_bomb_unit-28",
-                    ],
-                    true,
-                ),
-                (
-                    "cox-2_inhibitor",
-                    [
-                        "cox-2_inhibitor",
-                    ],
-                    true,
-                ),
-                (
-                    "omega-3",
-                    [
-                        "omega-3",
-                    ],
-                    true,
-                ),
-                (
-                    "5-hydroxy-3-methylglutaryl-coenzyme_a_reductase",
-                    [
-                        "5-hydroxy-3-methylglutaryl-coenzyme_a_reductase",
-                    ],
-                    true,
-                ),
-                (
-                    "omega-3_fatty_acid",
-                    [
-                        "omega-3_fatty_acid",
-                    ],
-                    true,
-                ),
-                (
-                    "4-membered",
-                    [
-                        "4-membered",
-                    ],
-                    true,
-                ),
-                (
-                    "5-membered",
-                    [
-                        "5-membered",
-                    ],
-                    true,
-                ),
-                (
-                    "omega-6",
-                    [
-                        "omega-6",
-                    ],
-                    true,
-                ),
-                (
-                    "omega-6_fatty_acid",
-                    [
-                        "omega-6_fatty_acid",
-                    ],
-                    true,
-                ),
-                (
-                    "7-membered",
-                    [
-                        "7-membered",
-                    ],
-                    true,
-                ),
-                (
-                    "8-membered",
-                    [
-                        "8-membered",
-                    ],
-                    true,
-                ),
-                (
-                    "v-8_juice",
-                    [
-                        "v-8_juice",
-                    ],
-                    true,
-                ),
-                (
-                    "9-membered",
-                    [
-                        "9-membered",
-                    ],
-                    true,
-                ),
-                (
-                    "zollinger-ellison_syndrome",
-                    [
-                        "zollinger-ellison_syndrome",
-                    ],
-                    true,
-                ),
-                (
-                    "w.m.d.",
-                    [
-                        "w.m.d",
-                        "w.m.d.",
-                    ],
-                    true,
-                ),
-                (
-                    "sept._11",
-                    [
-                        "sept._11",
-                    ],
-                    true,
-                ),
-                (
-                    ".22",
-                    [
-                        "22",
-                        ".22",
-                    ],
-                    true,
-                ),
-                (
-                    ".22_calibre",
-                    [
-                        "22_calibre",
-                        ".22_calibre",
-                    ],
-                    true,
-                ),
-                (
-                    ".38_calibre",
-                    [
-                        "38_calibre",
-                        ".38_calibre",
-                    ],
-                    true,
-                ),
-                (
-                    ".45_calibre",
-                    [
-                        "45_calibre",
-                        ".45_calibre",
-                    ],
-                    true,
-                ),
-                (
-                    "winston_s._churchill",
-                    [
-                        "winston_s._churchill",
-                    ],
-                    true,
-                ),
-                (
-                    "tcp/ip",
-                    [
-                        "tcp/ip",
-                    ],
-                    true,
-                ),
-                (
-                    "20/20",
-                    [
-                        "20/20",
-                    ],
-                    true,
-                ),
-                (
-                    "9/11",
-                    [
-                        "9/11",
-                    ],
-                    true,
-                ),
-                (
-                    "24/7",
-                    [
-                        "24/7",
-                    ],
-                    true,
-                ),
-                (
-                    "transmission_control_protocol/internet_protocol",
-                    [
-                        "transmission_control_protocol/internet_protocol",
-                    ],
-                    true,
-                ),
-                (
-                    "0",
-                    [
-                        "0",
-                    ],
-                    true,
-                ),
-                (
-                    "110th",
-                    [
-                        "110th",
-                    ],
-                    true,
-                ),
-                (
-                    "120th",
-                    [
-                        "120th",
-                    ],
-                    true,
-                ),
-                (
-                    "1820s",
-                    [
-                        "1820s",
-                    ],
-                    true,
-                ),
-                (
-                    "1920s",
-                    [
-                        "1920s",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_102",
-                    [
-                        "atomic_number_102",
-                    ],
-                    true,
-                ),
-                (
-                    "130th",
-                    [
-                        "130th",
-                    ],
-                    true,
-                ),
-                (
-                    "1530s",
-                    [
-                        "1530s",
-                    ],
-                    true,
-                ),
-                (
-                    "1830s",
-                    [
-                        "1830s",
-                    ],
-                    true,
-                ),
-                (
-                    "1930s",
-                    [
-                        "1930s",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_103",
-                    [
-                        "atomic_number_103",
-                    ],
-                    true,
-                ),
-                (
-                    "140th",
-                    [
-                        "140th",
-                    ],
-                    true,
-                ),
-                (
-                    "1840s",
-                    [
-                        "1840s",
-                    ],
-                    true,
-                ),
-                (
-                    "1940s",
-                    [
-                        "1940s",
-                    ],
-                    true,
-                ),
-                (
-                    "element_104",
-                    [
-                        "element_104",
-                    ],
-                    true,
-                ),
-                (
-                    "150th",
-                    [
-                        "150th",
-                    ],
-                    true,
-                ),
-                (
-                    "1750s",
-                    [
-                        "1750s",
-                    ],
-                    true,
-                ),
-                (
-                    "1850s",
-                    [
-                        "1850s",
-                    ],
-                    true,
-                ),
-                (
-                    "1950s",
-                    [
-                        "1950s",
-                    ],
-                    true,
-                ),
-                (
-                    "element_105",
-                    [
-                        "element_105",
-                    ],
-                    true,
-                ),
-                (
-                    "160th",
-                    [
-                        "160th",
-                    ],
-                    true,
-                ),
-                (
-                    "1760s",
-                    [
-                        "1760s",
-                    ],
-                    true,
-                ),
-                (
-                    "1860s",
-                    [
-                        "1860s",
-                    ],
-                    true,
-                ),
-                (
-                    "1960s",
-                    [
-                        "1960s",
-                    ],
-                    true,
-                ),
-                (
-                    "element_106",
-                    [
-                        "element_106",
-                    ],
-                    true,
-                ),
-                (
-                    "1770s",
-                    [
-                        "1770s",
-                    ],
-                    true,
-                ),
-                (
-                    "1870s",
-                    [
-                        "1870s",
-                    ],
-                    true,
-                ),
-                (
-                    "1970s",
-                    [
-                        "1970s",
-                    ],
-                    true,
-                ),
-                (
-                    "element_107",
-                    [
-                        "element_107",
-                    ],
-                    true,
-                ),
-                (
-                    "1880s",
-                    [
-                        "1880s",
-                    ],
-                    true,
-                ),
-                (
-                    "1980s",
-                    [
-                        "1980s",
-                    ],
-                    true,
-                ),
-                (
-                    "element_108",
-                    [
-                        "element_108",
-                    ],
-                    true,
-                ),
-                (
-                    "1990s",
-                    [
-                        "1990s",
-                    ],
-                    true,
-                ),
-                (
-                    "element_109",
-                    [
-                        "element_109",
-                    ],
-                    true,
-                ),
-                (
-                    "element_110",
-                    [
-                        "element_110",
-                    ],
-                    true,
-                ),
-                (
-                    "20th",
-                    [
-                        "20th",
-                    ],
-                    true,
-                ),
-                (
-                    "january_20",
-                    [
-                        "january_20",
-                    ],
-                    true,
-                ),
-                (
-                    "30th",
-                    [
-                        "30th",
-                    ],
-                    true,
-                ),
-                (
-                    "u308",
-                    [
-                        "u308",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_30",
-                    [
-                        "atomic_number_30",
-                    ],
-                    true,
-                ),
-                (
-                    "40th",
-                    [
-                        "40th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_40",
-                    [
-                        "atomic_number_40",
-                    ],
-                    true,
-                ),
-                (
-                    "50th",
-                    [
-                        "50th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_50",
-                    [
-                        "atomic_number_50",
-                    ],
-                    true,
-                ),
-                (
-                    "60th",
-                    [
-                        "60th",
-                    ],
-                    true,
-                ),
-                (
-                    "cobalt_60",
-                    [
-                        "cobalt_60",
-                    ],
-                    true,
-                ),
-                (
-                    "70th",
-                    [
-                        "70th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_70",
-                    [
-                        "atomic_number_70",
-                    ],
-                    true,
-                ),
-                (
-                    "80th",
-                    [
-                        "80th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_80",
-                    [
-                        "atomic_number_80",
-                    ],
-                    true,
-                ),
-                (
-                    "90th",
-                    [
-                        "90th",
-                    ],
-                    true,
-                ),
-                (
-                    "strontium_90",
-                    [
-                        "strontium_90",
-                    ],
-                    true,
-                ),
-                (
-                    "ut1",
-                    [
-                        "ut1",
-                    ],
-                    true,
-                ),
-                (
-                    "21st",
-                    [
-                        "21st",
-                    ],
-                    true,
-                ),
-                (
-                    "125th",
-                    [
-                        "125th",
-                    ],
-                    true,
-                ),
-                (
-                    "1728",
-                    [
-                        "1728",
-                    ],
-                    true,
-                ),
-                (
-                    "war_of_1812",
-                    [
-                        "war_of_1812",
-                    ],
-                    true,
-                ),
-                (
-                    "vitamin_b12",
-                    [
-                        "vitamin_b12",
-                    ],
-                    true,
-                ),
-                (
-                    "31st",
-                    [
-                        "31st",
-                    ],
-                    true,
-                ),
-                (
-                    "135th",
-                    [
-                        "135th",
-                    ],
-                    true,
-                ),
-                (
-                    "cesium_137",
-                    [
-                        "cesium_137",
-                    ],
-                    true,
-                ),
-                (
-                    "element_113",
-                    [
-                        "element_113",
-                    ],
-                    true,
-                ),
-                (
-                    "41st",
-                    [
-                        "41st",
-                    ],
-                    true,
-                ),
-                (
-                    "145th",
-                    [
-                        "145th",
-                    ],
-                    true,
-                ),
-                (
-                    "8_may_1945",
-                    [
-                        "8_may_1945",
-                    ],
-                    true,
-                ),
-                (
-                    "15_august_1945",
-                    [
-                        "15_august_1945",
-                    ],
-                    true,
-                ),
-                (
-                    "6_june_1944",
-                    [
-                        "6_june_1944",
-                    ],
-                    true,
-                ),
-                (
-                    "june_14",
-                    [
-                        "june_14",
-                    ],
-                    true,
-                ),
-                (
-                    "51",
-                    [
-                        "51",
-                    ],
-                    true,
-                ),
-                (
-                    "165th",
-                    [
-                        "165th",
-                    ],
-                    true,
-                ),
-                (
-                    "175th",
-                    [
-                        "175th",
-                    ],
-                    true,
-                ),
-                (
-                    "element_115",
-                    [
-                        "element_115",
-                    ],
-                    true,
-                ),
-                (
-                    "61",
-                    [
-                        "61",
-                    ],
-                    true,
-                ),
-                (
-                    "element_116",
-                    [
-                        "element_116",
-                    ],
-                    true,
-                ),
-                (
-                    "71",
-                    [
-                        "71",
-                    ],
-                    true,
-                ),
-                (
-                    "september_17",
-                    [
-                        "september_17",
-                    ],
-                    true,
-                ),
-                (
-                    "81",
-                    [
-                        "81",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_81",
-                    [
-                        "atomic_number_81",
-                    ],
-                    true,
-                ),
-                (
-                    "91",
-                    [
-                        "91",
-                    ],
-                    true,
-                ),
-                (
-                    "march_19",
-                    [
-                        "march_19",
-                    ],
-                    true,
-                ),
-                (
-                    "world_war_1",
-                    [
-                        "world_war_1",
-                    ],
-                    true,
-                ),
-                (
-                    "y2k",
-                    [
-                        "y2k",
-                    ],
-                    true,
-                ),
-                (
-                    "32nd",
-                    [
-                        "32nd",
-                    ],
-                    true,
-                ),
-                (
-                    "uranium_235",
-                    [
-                        "uranium_235",
-                    ],
-                    true,
-                ),
-                (
-                    "uranium_238",
-                    [
-                        "uranium_238",
-                    ],
-                    true,
-                ),
-                (
-                    "plutonium_239",
-                    [
-                        "plutonium_239",
-                    ],
-                    true,
-                ),
-                (
-                    "june_23",
-                    [
-                        "june_23",
-                    ],
-                    true,
-                ),
-                (
-                    "42nd",
-                    [
-                        "42nd",
-                    ],
-                    true,
-                ),
-                (
-                    "october_24",
-                    [
-                        "october_24",
-                    ],
-                    true,
-                ),
-                (
-                    "52",
-                    [
-                        "52",
-                    ],
-                    true,
-                ),
-                (
-                    "ponte_25_de_abril",
-                    [
-                        "ponte_25_de_abril",
-                    ],
-                    true,
-                ),
-                (
-                    "c2h6",
-                    [
-                        "c2h6",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_62",
-                    [
-                        "atomic_number_62",
-                    ],
-                    true,
-                ),
-                (
-                    "72",
-                    [
-                        "72",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_72",
-                    [
-                        "atomic_number_72",
-                    ],
-                    true,
-                ),
-                (
-                    "82",
-                    [
-                        "82",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_82",
-                    [
-                        "atomic_number_82",
-                    ],
-                    true,
-                ),
-                (
-                    "92",
-                    [
-                        "92",
-                    ],
-                    true,
-                ),
-                (
-                    "september_29",
-                    [
-                        "september_29",
-                    ],
-                    true,
-                ),
-                (
-                    "y2k_compliant",
-                    [
-                        "y2k_compliant",
-                    ],
-                    true,
-                ),
-                (
-                    "m3",
-                    [
-                        "m3",
-                    ],
-                    true,
-                ),
-                (
-                    "43rd",
-                    [
-                        "43rd",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_43",
-                    [
-                        "atomic_number_43",
-                    ],
-                    true,
-                ),
-                (
-                    "53",
-                    [
-                        "53",
-                    ],
-                    true,
-                ),
-                (
-                    "365_days",
-                    [
-                        "365_days",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_53",
-                    [
-                        "atomic_number_53",
-                    ],
-                    true,
-                ),
-                (
-                    "63",
-                    [
-                        "63",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_63",
-                    [
-                        "atomic_number_63",
-                    ],
-                    true,
-                ),
-                (
-                    "73",
-                    [
-                        "73",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_73",
-                    [
-                        "atomic_number_73",
-                    ],
-                    true,
-                ),
-                (
-                    "83",
-                    [
-                        "83",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_83",
-                    [
-                        "atomic_number_83",
-                    ],
-                    true,
-                ),
-                (
-                    "93",
-                    [
-                        "93",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_93",
-                    [
-                        "atomic_number_93",
-                    ],
-                    true,
-                ),
-                (
-                    "vitamin_k3",
-                    [
-                        "vitamin_k3",
-                    ],
-                    true,
-                ),
-                (
-                    "cd4",
-                    [
-                        "cd4",
-                    ],
-                    true,
-                ),
-                (
-                    "54",
-                    [
-                        "54",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_54",
-                    [
-                        "atomic_number_54",
-                    ],
-                    true,
-                ),
-                (
-                    "64th",
-                    [
-                        "64th",
-                    ],
-                    true,
-                ),
-                (
-                    "ru_486",
-                    [
-                        "ru_486",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_64",
-                    [
-                        "atomic_number_64",
-                    ],
-                    true,
-                ),
-                (
-                    "74",
-                    [
-                        "74",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_74",
-                    [
-                        "atomic_number_74",
-                    ],
-                    true,
-                ),
-                (
-                    "84",
-                    [
-                        "84",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_84",
-                    [
-                        "atomic_number_84",
-                    ],
-                    true,
-                ),
-                (
-                    "94",
-                    [
-                        "94",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_94",
-                    [
-                        "atomic_number_94",
-                    ],
-                    true,
-                ),
-                (
-                    "july_4",
-                    [
-                        "july_4",
-                    ],
-                    true,
-                ),
-                (
-                    "5th",
-                    [
-                        "5th",
-                    ],
-                    true,
-                ),
-                (
-                    "65th",
-                    [
-                        "65th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_65",
-                    [
-                        "atomic_number_65",
-                    ],
-                    true,
-                ),
-                (
-                    "75th",
-                    [
-                        "75th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_75",
-                    [
-                        "atomic_number_75",
-                    ],
-                    true,
-                ),
-                (
-                    "85th",
-                    [
-                        "85th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_85",
-                    [
-                        "atomic_number_85",
-                    ],
-                    true,
-                ),
-                (
-                    "95th",
-                    [
-                        "95th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_95",
-                    [
-                        "atomic_number_95",
-                    ],
-                    true,
-                ),
-                (
-                    "november_5",
-                    [
-                        "november_5",
-                    ],
-                    true,
-                ),
-                (
-                    "6th",
-                    [
-                        "6th",
-                    ],
-                    true,
-                ),
-                (
-                    "76",
-                    [
-                        "76",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_76",
-                    [
-                        "atomic_number_76",
-                    ],
-                    true,
-                ),
-                (
-                    "86",
-                    [
-                        "86",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_86",
-                    [
-                        "atomic_number_86",
-                    ],
-                    true,
-                ),
-                (
-                    "96",
-                    [
-                        "96",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_96",
-                    [
-                        "atomic_number_96",
-                    ],
-                    true,
-                ),
-                (
-                    "vitamin_b6",
-                    [
-                        "vitamin_b6",
-                    ],
-                    true,
-                ),
-                (
-                    "7th",
-                    [
-                        "7th",
-                    ],
-                    true,
-                ),
-                (
-                    "87",
-                    [
-                        "87",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_87",
-                    [
-                        "atomic_number_87",
-                    ],
-                    true,
-                ),
-                (
-                    "97",
-                    [
-                        "97",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_97",
-                    [
-                        "atomic_number_97",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_77",
-                    [
-                        "atomic_number_77",
-                    ],
-                    true,
-                ),
-                (
-                    "cd8",
-                    [
-                        "cd8",
-                    ],
-                    true,
-                ),
-                (
-                    "98",
-                    [
-                        "98",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_98",
-                    [
-                        "atomic_number_98",
-                    ],
-                    true,
-                ),
-                (
-                    "december_8",
-                    [
-                        "december_8",
-                    ],
-                    true,
-                ),
-                (
-                    "9th",
-                    [
-                        "9th",
-                    ],
-                    true,
-                ),
-                (
-                    "atomic_number_99",
-                    [
-                        "atomic_number_99",
-                    ],
-                    true,
-                ),
-                (
-                    "zygophyllum_fabago",
-                    [
-                        "zygophyllum_fabago",
-                    ],
-                    true,
-                ),
-            ]
-        "#]];
-        expected.assert_debug_eq(&words);
-    }
-
-    #[test]
-    fn complete_spaces() {
-        let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
-        let words = dict.complete(&"living".to_owned(), false, 10);
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
+        let words = dict.complete(&"living".to_owned(), false, 10);
         let expected = expect![[r#"
             [
                 CompletionItem {
@@ -3556,7 +3755,73 @@ mod tests {
                     sort_text: None,
                     filter_text: None,
                     insert_text: Some(
-                        "living death",
+                        "living death",
+                    ),
+                    insert_text_format: None,
+                    insert_text_mode: None,
+                    text_edit: None,
+                    additional_text_edits: None,
+                    command: None,
+                    commit_characters: None,
+                    data: None,
+                    tags: None,
+                },
+                CompletionItem {
+                    label: "living_granite",
+                    label_details: None,
+                    kind: None,
+                    detail: None,
+                    documentation: None,
+                    deprecated: None,
+                    preselect: None,
+                    sort_text: None,
+                    filter_text: None,
+                    insert_text: Some(
+                        "living granite",
+                    ),
+                    insert_text_format: None,
+                    insert_text_mode: None,
+                    text_edit: None,
+                    additional_text_edits: None,
+                    command: None,
+                    commit_characters: None,
+                    data: None,
+                    tags: None,
+                },
+                CompletionItem {
+                    label: "living_quarters",
+                    label_details: None,
+                    kind: None,
+                    detail: None,
+                    documentation: None,
+                    deprecated: None,
+                    preselect: None,
+                    sort_text: None,
+                    filter_text: None,
+                    insert_text: Some(
+                        "living quarters",
+                    ),
+                    insert_text_format: None,
+                    insert_text_mode: None,
+                    text_edit: None,
+                    additional_text_edits: None,
+                    command: None,
+                    commit_characters: None,
+                    data: None,
+                    tags: None,
+                },
+                CompletionItem {
+                    label: "living_rock",
+                    label_details: None,
+                    kind: None,
+                    detail: None,
+                    documentation: None,
+                    deprecated: None,
+                    preselect: None,
+                    sort_text: None,
+                    filter_text: None,
+                    insert_text: Some(
+                        "living rock",
                     ),
                     insert_text_format: None,
                     insert_text_mode: None,
@@ -3568,7 +3833,7 @@ mod tests {
                     tags: None,
                 },
                 CompletionItem {
-                    label: "living_granite",
+                    label: "living_room",
                     label_details: None,
                     kind: None,
                     detail: None,
@@ -3578,7 +3843,7 @@ mod tests {
                     sort_text: None,
                     filter_text: None,
                     insert_text: Some(
-                        "living granite",
+                        "living room",
                     ),
                     insert_text_format: None,
                     insert_text_mode: None,
@@ -3589,8 +3854,19 @@ mod tests {
                     data: None,
                     tags: None,
                 },
+            ]
+        "#]];
+        expected.assert_debug_eq(&words);
+    }
+
+    #[test]
+    fn complete_spaces_fixture() {
+        let (_dir, dict) = dict();
+        let words = dict.complete(&"living".to_owned(), false, 10);
+        let expected = expect![[r#"
+            [
                 CompletionItem {
-                    label: "living_quarters",
+                    label: "living",
                     label_details: None,
                     kind: None,
                     detail: None,
@@ -3599,9 +3875,7 @@ mod tests {
                     preselect: None,
                     sort_text: None,
                     filter_text: None,
-                    insert_text: Some(
-                        "living quarters",
-                    ),
+                    insert_text: None,
                     insert_text_format: None,
                     insert_text_mode: None,
                     text_edit: None,
@@ -3612,7 +3886,7 @@ mod tests {
                     tags: None,
                 },
                 CompletionItem {
-                    label: "living_rock",
+                    label: "living_room",
                     label_details: None,
                     kind: None,
                     detail: None,
@@ -3622,7 +3896,7 @@ mod tests {
                     sort_text: None,
                     filter_text: None,
                     insert_text: Some(
-                        "living rock",
+                        "living room",
                     ),
                     insert_text_format: None,
                     insert_text_mode: None,
@@ -3634,7 +3908,7 @@ mod tests {
                     tags: None,
                 },
                 CompletionItem {
-                    label: "living_room",
+                    label: "living_thing",
                     label_details: None,
                     kind: None,
                     detail: None,
@@ -3644,7 +3918,7 @@ mod tests {
                     sort_text: None,
                     filter_text: None,
                     insert_text: Some(
-                        "living room",
+                        "living thing",
                     ),
                     insert_text_format: None,
                     insert_text_mode: None,
@@ -3663,7 +3937,7 @@ mod tests {
     #[test]
     fn complete_uppercase() {
         let wndir = env::var("WNSEARCHDIR").unwrap();
-        let dict = Dict::new(&PathBuf::from(wndir));
+        let dict = Dict::new(WordNet::new(&PathBuf::from(wndir)).unwrap());
         let words = dict.complete(&"bost".to_owned(), true, 10);
         let expected = expect![[r#"
             [
@@ -3891,4 +4165,838 @@ mod tests {
         "#]];
         expected.assert_debug_eq(&words);
     }
+
+    #[test]
+    fn complete_uppercase_fixture() {
+        let (_dir, dict) = dict();
+        let words = dict.complete(&"bost".to_owned(), true, 10);
+        let expected = expect![[r#"
+            [
+                CompletionItem {
+                    label: "boston",
+                    label_details: None,
+                    kind: None,
+                    detail: None,
+                    documentation: None,
+                    deprecated: None,
+                    preselect: None,
+                    sort_text: None,
+                    filter_text: None,
+                    insert_text: Some(
+                        "Boston",
+                    ),
+                    insert_text_format: None,
+                    insert_text_mode: None,
+                    text_edit: None,
+                    additional_text_edits: None,
+                    command: None,
+                    commit_characters: None,
+                    data: None,
+                    tags: None,
+                },
+                CompletionItem {
+                    label: "bostonian",
+                    label_details: None,
+                    kind: None,
+                    detail: None,
+                    documentation: None,
+                    deprecated: None,
+                    preselect: None,
+                    sort_text: None,
+                    filter_text: None,
+                    insert_text: Some(
+                        "Bostonian",
+                    ),
+                    insert_text_format: None,
+                    insert_text_mode: None,
+                    text_edit: None,
+                    additional_text_edits: None,
+                    command: None,
+                    commit_characters: None,
+                    data: None,
+                    tags: None,
+                },
+            ]
+        "#]];
+        expected.assert_debug_eq(&words);
+    }
+
+    /// A database of a single word.
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = wordnet_ls::wordnet::WordNetBuilder::new();
+        builder.add_synset(PartOfSpeech::Noun, &["dog"], "a barking animal", &[]);
+        builder.write(dir.path()).unwrap();
+        dir
+    }
+
+    /// A server over an in-memory connection, with the fixture database.
+    struct TestServer {
+        client: Connection,
+        thread: std::thread::JoinHandle<Result<(), String>>,
+        next_id: i32,
+        /// Requests the server has sent.
+        requests: Vec<Request>,
+        notifications: Vec<Notification>,
+        _dir: tempfile::TempDir,
+    }
+
+    impl TestServer {
+        fn start(
+            mut init_options: serde_json::Value,
+            capabilities: lsp_types::ClientCapabilities,
+        ) -> Self {
+            let dir = fixture();
+            if init_options.get("wordnet").is_none() {
+                init_options["wordnet"] = serde_json::to_value(dir.path()).unwrap();
+            }
+            let params = InitializeParams {
+                capabilities,
+                initialization_options: Some(init_options),
+                ..Default::default()
+            };
+            let (server, client) = Connection::memory();
+            let thread = std::thread::spawn(move || {
+                Server::new(&server, params, Dicts::default()).serve(server)
+            });
+            Self {
+                client,
+                thread,
+                next_id: 0,
+                requests: Vec::new(),
+                notifications: Vec::new(),
+                _dir: dir,
+            }
+        }
+
+        fn notify(&self, method: &str, params: serde_json::Value) {
+            self.client
+                .sender
+                .send(Message::Notification(Notification::new(
+                    method.to_owned(),
+                    params,
+                )))
+                .unwrap();
+        }
+
+        fn request(&mut self, method: &str, params: serde_json::Value) -> Response {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            self.client
+                .sender
+                .send(Message::Request(Request::new(
+                    id.clone(),
+                    method.to_owned(),
+                    params,
+                )))
+                .unwrap();
+            loop {
+                match self.client.receiver.recv().unwrap() {
+                    Message::Response(r) if r.id == id => return r,
+                    Message::Request(r) => self.requests.push(r),
+                    Message::Notification(n) => self.notifications.push(n),
+                    Message::Response(_) => {}
+                }
+            }
+        }
+
+        fn respond(&self, id: RequestId, result: serde_json::Value) {
+            self.client
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .unwrap();
+        }
+
+        fn hover(&mut self) -> Option<serde_json::Value> {
+            self.hover_at(0, 1)
+        }
+
+        fn hover_at(&mut self, line: u32, character: u32) -> Option<serde_json::Value> {
+            let params = serde_json::json!({
+                "textDocument": { "uri": "file:///test.txt" },
+                "position": { "line": line, "character": character },
+            });
+            self.request(lsp_types::request::HoverRequest::METHOD, params)
+                .result
+                .filter(|result| !result.is_null())
+        }
+
+        /// Shut down the server, returning the methods and parameters of the requests it sent.
+        fn stop(mut self) -> Vec<(String, serde_json::Value)> {
+            self.request(
+                lsp_types::request::Shutdown::METHOD,
+                serde_json::Value::Null,
+            );
+            self.notify(
+                lsp_types::notification::Exit::METHOD,
+                serde_json::Value::Null,
+            );
+            self.thread.join().unwrap().unwrap();
+            self.requests
+                .into_iter()
+                .map(|r| (r.method, r.params))
+                .collect()
+        }
+    }
+
+    fn open_document(server: &TestServer) {
+        server.notify(
+            lsp_types::notification::DidOpenTextDocument::METHOD,
+            serde_json::json!({
+                "textDocument": {
+                    "uri": "file:///test.txt",
+                    "languageId": "plaintext",
+                    "version": 1,
+                    "text": "dog",
+                },
+            }),
+        );
+    }
+
+    #[test]
+    fn change_configuration_pushed() {
+        let mut server = TestServer::start(serde_json::json!({}), Default::default());
+        open_document(&server);
+        assert!(server.hover().is_some());
+
+        server.notify(
+            DidChangeConfiguration::METHOD,
+            serde_json::json!({ "settings": { "wordnet-ls": { "enable_hover": false } } }),
+        );
+        assert!(server.hover().is_none());
+        server.notify(
+            DidChangeConfiguration::METHOD,
+            serde_json::json!({ "settings": { "enable_hover": true } }),
+        );
+        assert!(server.hover().is_some());
+        assert!(server.stop().is_empty());
+    }
+
+    #[test]
+    fn change_configuration_pulled() {
+        let capabilities = serde_json::from_value(serde_json::json!({
+            "workspace": {
+                "configuration": true,
+                "didChangeConfiguration": { "dynamicRegistration": true },
+            },
+            "textDocument": { "hover": { "dynamicRegistration": true } },
+        }))
+        .unwrap();
+        let mut server = TestServer::start(serde_json::json!({}), capabilities);
+        open_document(&server);
+        assert!(server.hover().is_some());
+        // reply to the configuration request sent on startup
+        let configuration = server
+            .requests
+            .iter()
+            .find(|r| r.method == WorkspaceConfiguration::METHOD)
+            .unwrap();
+        server.respond(
+            configuration.id.clone(),
+            serde_json::json!([{ "enable_hover": false, "completion_limit": 1 }]),
+        );
+        assert!(server.hover().is_none());
+
+        let expected = expect![[r#"
+            [
+                (
+                    "client/registerCapability",
+                    Object {
+                        "registrations": Array [
+                            Object {
+                                "id": String("textDocument/hover"),
+                                "method": String("textDocument/hover"),
+                                "registerOptions": Object {
+                                    "documentSelector": Null,
+                                },
+                            },
+                        ],
+                    },
+                ),
+                (
+                    "client/registerCapability",
+                    Object {
+                        "registrations": Array [
+                            Object {
+                                "id": String("workspace/didChangeConfiguration"),
+                                "method": String("workspace/didChangeConfiguration"),
+                            },
+                        ],
+                    },
+                ),
+                (
+                    "workspace/configuration",
+                    Object {
+                        "items": Array [
+                            Object {
+                                "section": String("wordnet-ls"),
+                            },
+                        ],
+                    },
+                ),
+                (
+                    "client/unregisterCapability",
+                    Object {
+                        "unregisterations": Array [
+                            Object {
+                                "id": String("textDocument/hover"),
+                                "method": String("textDocument/hover"),
+                            },
+                        ],
+                    },
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&server.stop());
+    }
+
+    #[test]
+    fn responses_to_server_requests() {
+        let capabilities = serde_json::from_value(serde_json::json!({
+            "textDocument": { "hover": { "dynamicRegistration": true } },
+        }))
+        .unwrap();
+        let mut server = TestServer::start(serde_json::json!({}), capabilities);
+        open_document(&server);
+        server.hover();
+        server.notify(
+            DidChangeConfiguration::METHOD,
+            serde_json::json!({ "settings": { "enable_hover": false } }),
+        );
+        server.hover();
+        let id = |method: &str| {
+            server
+                .requests
+                .iter()
+                .find(|r| r.method == method)
+                .map(|r| r.id.clone())
+                .unwrap()
+        };
+        let (register, unregister) = (
+            id(RegisterCapability::METHOD),
+            id(UnregisterCapability::METHOD),
+        );
+        server
+            .client
+            .sender
+            .send(Message::Response(Response::new_err(
+                register,
+                ErrorCode::InvalidRequest as i32,
+                "not supported".to_owned(),
+            )))
+            .unwrap();
+        server.respond(unregister, serde_json::Value::Null);
+        server.respond(RequestId::from(1000), serde_json::Value::Null);
+        server.hover();
+
+        let expected = expect![[r#"
+            [
+                (
+                    "window/showMessage",
+                    Object {
+                        "message": String("The client failed to handle client/registerCapability: not supported"),
+                        "type": Number(2),
+                    },
+                ),
+                (
+                    "window/logMessage",
+                    String("Unmatched response received: 1000"),
+                ),
+            ]
+        "#]];
+        let messages = server
+            .notifications
+            .iter()
+            .filter(|n| n.method != PublishDiagnostics::METHOD)
+            .map(|n| (n.method.clone(), n.params.clone()))
+            .collect::<Vec<_>>();
+        expected.assert_debug_eq(&messages);
+        server.stop();
+    }
+
+    #[test]
+    fn panicking_request() {
+        let mut server = TestServer::start(Default::default(), Default::default());
+        open_document(&server);
+        let panicked = server.request("wordnet-ls/test/panic", serde_json::Value::Null);
+        let error = panicked.error.unwrap();
+        assert_eq!(error.code, ErrorCode::InternalError as i32);
+        assert_eq!(
+            error.message,
+            "Failed to handle wordnet-ls/test/panic: panicked on purpose"
+        );
+        // the server is still there to answer the next request
+        assert!(server.hover().is_some());
+        server.stop();
+    }
+
+    #[test]
+    fn sessions_share_dict() {
+        let dir = fixture();
+        let options = InitializationOptions {
+            wordnet: Some(dir.path().to_owned()),
+            ..Default::default()
+        };
+        let dicts = Dicts::default();
+        let load = |options| dicts.load(options, |_| {}).unwrap().0;
+        let first = load(&options);
+        assert!(Arc::ptr_eq(&first, &load(&options)));
+        let read = InitializationOptions {
+            mmap: Some(false),
+            ..options.clone()
+        };
+        assert!(!Arc::ptr_eq(&first, &load(&read)));
+    }
+
+    /// A database whose noun index doesn't end with a newline, with a lexicon whose hypernym is on
+    /// that last line.
+    fn unterminated_fixture() -> (tempfile::TempDir, PathBuf) {
+        let dir = fixture();
+        let index = dir.path().join("index.noun");
+        let content = std::fs::read_to_string(&index).unwrap();
+        std::fs::write(&index, content.trim_end()).unwrap();
+        let lexicon = dir.path().join("lexicon.toml");
+        std::fs::write(
+            &lexicon,
+            "[[entry]]\nword = \"puppy\"\npos = \"noun\"\ndefinition = \"a young dog\"\n\
+             hypernyms = [\"dog.n.01\"]\n",
+        )
+        .unwrap();
+        (dir, lexicon)
+    }
+
+    #[test]
+    fn unterminated_index() {
+        let (dir, lexicon) = unterminated_fixture();
+        let options = InitializationOptions {
+            wordnet: Some(dir.path().to_owned()),
+            lexicons: Some(vec![lexicon]),
+            ..Default::default()
+        };
+        let (dict, errors) = Dicts::default().load(&options, |_| {}).unwrap();
+        assert_eq!(errors, Vec::<String>::new());
+        let expected = expect![[r#"
+            # puppy
+
+            1. _noun_ a young dog.
+            - **hypernym**: dog"#]];
+        expected.assert_eq(&dict.all_info(&["puppy".to_owned()]).unwrap());
+    }
+
+    #[test]
+    fn unterminated_index_answers_requests() {
+        let (dir, lexicon) = unterminated_fixture();
+        let mut server = TestServer::start(
+            serde_json::json!({ "wordnet": dir.path(), "lexicons": [lexicon] }),
+            Default::default(),
+        );
+        open_document(&server);
+        let expected = expect![[r#"
+            Some(
+                Object {
+                    "contents": Object {
+                        "kind": String("markdown"),
+                        "value": String("**dog** _noun_\n1. a barking animal."),
+                    },
+                },
+            )
+        "#]];
+        expected.assert_debug_eq(&server.hover());
+        server.stop();
+    }
+
+    #[test]
+    fn session_over_tcp() {
+        let dir = fixture();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let c = transport::stream_connection(stream.try_clone().unwrap(), stream);
+            session(c, &Dicts::default())
+        });
+
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+        let mut send = |message: Message| message.write(&mut stream).unwrap();
+        send(Message::Request(Request::new(
+            RequestId::from(1),
+            lsp_types::request::Initialize::METHOD.to_owned(),
+            serde_json::json!({
+                "capabilities": {},
+                "initializationOptions": { "wordnet": dir.path() },
+            }),
+        )));
+        let Some(Message::Response(initialized)) = Message::read(&mut reader).unwrap() else {
+            panic!("expected the initialize response")
+        };
+        assert!(initialized.result.unwrap()["capabilities"]["hoverProvider"] == true);
+        send(Message::Notification(Notification::new(
+            lsp_types::notification::Initialized::METHOD.to_owned(),
+            serde_json::json!({}),
+        )));
+        send(Message::Request(Request::new(
+            RequestId::from(2),
+            lsp_types::request::Shutdown::METHOD.to_owned(),
+            serde_json::Value::Null,
+        )));
+        let Some(Message::Response(shutdown)) = Message::read(&mut reader).unwrap() else {
+            panic!("expected the shutdown response")
+        };
+        assert_eq!(shutdown.id, RequestId::from(2));
+        send(Message::Notification(Notification::new(
+            lsp_types::notification::Exit::METHOD.to_owned(),
+            serde_json::Value::Null,
+        )));
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn malformed_messages() {
+        let mut server = TestServer::start(serde_json::json!({}), Default::default());
+        open_document(&server);
+        let position = |uri: &str, line: u32, character: u32| {
+            serde_json::json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": line, "character": character },
+            })
+        };
+        let requests = [
+            (
+                lsp_types::request::HoverRequest::METHOD,
+                serde_json::json!({}),
+            ),
+            (
+                lsp_types::request::HoverRequest::METHOD,
+                serde_json::json!({ "textDocument": { "uri": "file:///test.txt" }, "position": "start" }),
+            ),
+            (
+                lsp_types::request::HoverRequest::METHOD,
+                position("untitled:Untitled-1", 0, 0),
+            ),
+            (
+                lsp_types::request::HoverRequest::METHOD,
+                position("file:///does/not/exist.txt", 0, 0),
+            ),
+            (
+                lsp_types::request::HoverRequest::METHOD,
+                position("file:///test.txt", 100, 100),
+            ),
+            (
+                lsp_types::request::Completion::METHOD,
+                position("file:///test.txt", 0, 0),
+            ),
+            (
+                lsp_types::request::GotoDefinition::METHOD,
+                serde_json::Value::Null,
+            ),
+            (
+                lsp_types::request::ExecuteCommand::METHOD,
+                serde_json::json!({ "command": "define", "arguments": [] }),
+            ),
+            (
+                lsp_types::request::ExecuteCommand::METHOD,
+                serde_json::json!({ "command": "define", "arguments": [{ "name": "dog" }] }),
+            ),
+            (
+                lsp_types::request::ExecuteCommand::METHOD,
+                serde_json::json!({ "command": "undefine", "arguments": [] }),
+            ),
+            ("wordnet/unknown", serde_json::json!({})),
+        ];
+        let responses = requests
+            .into_iter()
+            .map(|(method, params)| {
+                let response = server.request(method, params);
+                match response.error {
+                    Some(error) => format!("{method}: {} {}", error.code, error.message),
+                    None => format!("{method}: {:?}", response.result),
+                }
+            })
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                "textDocument/hover: -32602 Invalid params: missing field `textDocument`",
+                "textDocument/hover: -32602 Invalid params: invalid type: string \"start\", expected struct Position",
+                "textDocument/hover: -32602 untitled:Untitled-1 is not open or a file",
+                "textDocument/hover: -32603 Failed to read \"/does/not/exist.txt\": No such file or directory (os error 2)",
+                "textDocument/hover: Some(Null)",
+                "textDocument/completion: Some(Null)",
+                "textDocument/definition: -32602 Invalid params: invalid type: null, expected struct TextDocumentPositionParams",
+                "workspace/executeCommand: -32602 invalid arguments",
+                "workspace/executeCommand: -32602 invalid arguments",
+                "workspace/executeCommand: -32602 unknown command",
+                "wordnet/unknown: -32601 Unknown request wordnet/unknown",
+            ]
+        "#]];
+        expected.assert_debug_eq(&responses);
+
+        // notifications can't be answered, but mustn't stop the server
+        let change = |uri: &str, start: u32, end: u32| {
+            serde_json::json!({
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{
+                    "range": {
+                        "start": { "line": 0, "character": start },
+                        "end": { "line": 0, "character": end },
+                    },
+                    "text": "x",
+                }],
+            })
+        };
+        let notifications = [
+            (
+                lsp_types::notification::DidOpenTextDocument::METHOD,
+                serde_json::json!({ "textDocument": { "uri": "file:///other.txt" } }),
+            ),
+            (
+                lsp_types::notification::DidChangeTextDocument::METHOD,
+                change("file:///other.txt", 0, 1),
+            ),
+            (
+                lsp_types::notification::DidChangeTextDocument::METHOD,
+                change("file:///test.txt", 2, 1),
+            ),
+            (
+                lsp_types::notification::DidCloseTextDocument::METHOD,
+                serde_json::json!([]),
+            ),
+            (DidChangeConfiguration::METHOD, serde_json::json!({})),
+            (
+                DidChangeConfiguration::METHOD,
+                serde_json::json!({ "settings": { "completion_limit": "ten" } }),
+            ),
+        ];
+        for (method, params) in notifications {
+            server.notify(method, params);
+        }
+        assert!(server.hover().is_some());
+        server.stop();
+    }
+
+    #[test]
+    fn in_flight_cancel() {
+        let in_flight = InFlight::default();
+        let handled = |in_flight: &InFlight, id: i32, cancel: bool| {
+            let id = RequestId::from(id);
+            in_flight.start(id.clone());
+            if cancel {
+                in_flight.cancel(&id);
+            }
+            let mut called = false;
+            let result = in_flight.run(&id, || {
+                called = true;
+                // cancelled while being handled
+                in_flight.cancel(&RequestId::from(3));
+                Ok(serde_json::Value::Null)
+            });
+            (called, result.map_err(|e| e.code))
+        };
+        let expected = expect![[r#"
+            [
+                (
+                    true,
+                    Ok(
+                        Null,
+                    ),
+                ),
+                (
+                    false,
+                    Err(
+                        -32800,
+                    ),
+                ),
+                (
+                    true,
+                    Err(
+                        -32800,
+                    ),
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&[
+            handled(&in_flight, 1, false),
+            handled(&in_flight, 2, true),
+            handled(&in_flight, 3, false),
+        ]);
+        in_flight.cancel(&RequestId::from(4));
+        assert!(in_flight.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn concurrent_requests() {
+        let server = TestServer::start(serde_json::json!({}), Default::default());
+        open_document(&server);
+        let ids = (100..120).map(RequestId::from).collect::<BTreeSet<_>>();
+        for id in &ids {
+            let params = serde_json::json!({
+                "textDocument": { "uri": "file:///test.txt" },
+                "position": { "line": 0, "character": 1 },
+            });
+            server
+                .client
+                .sender
+                .send(Message::Request(Request::new(
+                    id.clone(),
+                    lsp_types::request::HoverRequest::METHOD.to_owned(),
+                    params,
+                )))
+                .unwrap();
+            server.notify(
+                lsp_types::notification::Cancel::METHOD,
+                serde_json::json!({ "id": 101 }),
+            );
+        }
+        let mut answered = BTreeSet::new();
+        while answered.len() < ids.len() {
+            if let Message::Response(r) = server.client.receiver.recv().unwrap() {
+                // each is either answered or cancelled, cancelling the others has no effect
+                match r.error {
+                    Some(error) => {
+                        assert_eq!(
+                            (r.id.clone(), error.code),
+                            (RequestId::from(101), ErrorCode::RequestCanceled as i32)
+                        );
+                    }
+                    None => assert!(r.result.is_some_and(|r| !r.is_null())),
+                }
+                answered.insert(r.id);
+            }
+        }
+        assert_eq!(answered, ids);
+        server.stop();
+    }
+
+    #[test]
+    fn loading_progress() {
+        let capabilities = serde_json::from_value(serde_json::json!({
+            "window": { "workDoneProgress": true },
+        }))
+        .unwrap();
+        let mut server = TestServer::start(serde_json::json!({}), capabilities);
+        open_document(&server);
+        // answered once loaded, if it arrives before
+        assert!(server.hover().is_some());
+        let progress = server
+            .notifications
+            .iter()
+            .filter(|n| n.method == lsp_types::notification::Progress::METHOD)
+            .map(|n| n.params.clone())
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            (
+                [
+                    Object {
+                        "token": String("wordnet-ls/loading/1"),
+                        "value": Object {
+                            "kind": String("begin"),
+                            "title": String("Loading WordNet"),
+                        },
+                    },
+                    Object {
+                        "token": String("wordnet-ls/loading/1"),
+                        "value": Object {
+                            "kind": String("report"),
+                            "message": String("Reading the database"),
+                        },
+                    },
+                    Object {
+                        "token": String("wordnet-ls/loading/1"),
+                        "value": Object {
+                            "kind": String("report"),
+                            "message": String("Indexing words"),
+                        },
+                    },
+                    Object {
+                        "token": String("wordnet-ls/loading/1"),
+                        "value": Object {
+                            "kind": String("end"),
+                        },
+                    },
+                ],
+                [
+                    (
+                        "window/workDoneProgress/create",
+                        Object {
+                            "token": String("wordnet-ls/loading/1"),
+                        },
+                    ),
+                ],
+            )
+        "#]];
+        expected.assert_debug_eq(&(progress, server.stop()));
+    }
+
+    #[test]
+    fn utf16_edits() {
+        let mut server = TestServer::start(serde_json::json!({}), Default::default());
+        server.notify(
+            lsp_types::notification::DidOpenTextDocument::METHOD,
+            serde_json::json!({
+                "textDocument": {
+                    "uri": "file:///test.txt",
+                    "languageId": "plaintext",
+                    "version": 1,
+                    "text": "😀 cat\r\n😀 cat",
+                },
+            }),
+        );
+        // the emoji is two UTF-16 code units
+        server.notify(
+            lsp_types::notification::DidChangeTextDocument::METHOD,
+            serde_json::json!({
+                "textDocument": { "uri": "file:///test.txt", "version": 2 },
+                "contentChanges": [{
+                    "range": {
+                        "start": { "line": 1, "character": 3 },
+                        "end": { "line": 1, "character": 6 },
+                    },
+                    "text": "dog",
+                }],
+            }),
+        );
+        assert!(server.hover_at(0, 4).is_none());
+        assert!(server.hover_at(1, 4).is_some());
+        assert!(server.stop().is_empty());
+    }
+
+    #[test]
+    fn source_code_prose() {
+        let mut server = TestServer::start(serde_json::json!({}), Default::default());
+        server.notify(
+            lsp_types::notification::DidOpenTextDocument::METHOD,
+            serde_json::json!({
+                "textDocument": {
+                    "uri": "file:///test.txt",
+                    "languageId": "rust",
+                    "version": 1,
+                    "text": "/* a\n   dog */\nlet dog = \"dog\";",
+                },
+            }),
+        );
+        assert!(server.hover_at(1, 4).is_some());
+        assert!(server.hover_at(2, 5).is_none());
+        assert!(server.hover_at(2, 12).is_some());
+        assert!(server.stop().is_empty());
+    }
+
+    #[test]
+    fn markdown_prose() {
+        let mut server = TestServer::start(serde_json::json!({}), Default::default());
+        server.notify(
+            lsp_types::notification::DidOpenTextDocument::METHOD,
+            serde_json::json!({
+                "textDocument": {
+                    "uri": "file:///test.txt",
+                    "languageId": "markdown",
+                    "version": 1,
+                    "text": "`dog` **dog**\n```\ndog\n```",
+                },
+            }),
+        );
+        assert!(server.hover_at(0, 2).is_none());
+        assert!(server.hover_at(0, 9).is_some());
+        assert!(server.hover_at(2, 1).is_none());
+        assert!(server.stop().is_empty());
+    }
 }
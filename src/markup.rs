@@ -0,0 +1,723 @@
+use std::ops::Range;
+
+/// Environments whose content is not prose.
+const LATEX_ENVIRONMENTS: [&str; 14] = [
+    "equation",
+    "align",
+    "alignat",
+    "flalign",
+    "gather",
+    "multline",
+    "eqnarray",
+    "math",
+    "displaymath",
+    "verbatim",
+    "lstlisting",
+    "minted",
+    "tikzpicture",
+    "comment",
+];
+
+/// Directives whose arguments and content are not prose.
+const RST_LITERAL_DIRECTIVES: [&str; 8] = [
+    "code",
+    "code-block",
+    "sourcecode",
+    "highlight",
+    "literalinclude",
+    "math",
+    "raw",
+    "toctree",
+];
+
+/// Directives whose arguments are paths, with prose content.
+const RST_PATH_DIRECTIVES: [&str; 3] = ["image", "figure", "include"];
+
+/// The chars of a document, marking those that are not prose.
+struct Masker {
+    chars: Vec<char>,
+    prose: Vec<bool>,
+}
+
+impl Masker {
+    fn new(text: &str) -> Self {
+        let chars = text.chars().collect::<Vec<_>>();
+        let prose = vec![true; chars.len()];
+        Self { chars, prose }
+    }
+
+    fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    fn mask(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        self.prose[start..end].fill(false);
+    }
+
+    fn text(&self, range: Range<usize>) -> String {
+        self.chars[range].iter().collect()
+    }
+
+    fn starts_with(&self, i: usize, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(j, c)| self.chars.get(i + j) == Some(&c))
+    }
+
+    /// The index of the first occurrence of the string within the range.
+    fn find(&self, range: Range<usize>, s: &str) -> Option<usize> {
+        let len = s.chars().count();
+        range
+            .clone()
+            .find(|&i| i + len <= range.end && self.starts_with(i, s))
+    }
+
+    /// The length of the run of the char starting at the index, up to the end.
+    fn run(&self, i: usize, c: char, end: usize) -> usize {
+        self.chars[i..end].iter().take_while(|&&d| d == c).count()
+    }
+
+    fn is_alphanumeric(&self, i: usize) -> bool {
+        self.chars.get(i).is_some_and(|c| c.is_alphanumeric())
+    }
+
+    /// The lines, without their line endings.
+    fn lines(&self) -> Vec<Range<usize>> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (i, &c) in self.chars.iter().enumerate() {
+            if c == '\n' {
+                let end = if i > start && self.chars[i - 1] == '\r' {
+                    i - 1
+                } else {
+                    i
+                };
+                lines.push(start..end);
+                start = i + 1;
+            }
+        }
+        lines.push(start..self.len());
+        lines
+    }
+
+    fn indent(&self, line: &Range<usize>) -> usize {
+        self.chars[line.clone()]
+            .iter()
+            .take_while(|c| **c == ' ' || **c == '\t')
+            .count()
+    }
+
+    /// The index after the group opening at the index, skipping nested groups and escaped chars.
+    fn group_end(&self, start: usize, open: char, close: char, end: usize) -> Option<usize> {
+        let mut depth = 0;
+        let mut i = start;
+        while i < end {
+            match self.chars[i] {
+                '\\' => i += 1,
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i + 1);
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Mask a URL starting at the index, returning where it ends.
+    fn url(&mut self, i: usize, end: usize) -> Option<usize> {
+        if i > 0 && self.is_alphanumeric(i - 1) {
+            return None;
+        }
+        ["https://", "http://", "ftp://", "mailto:", "www."]
+            .iter()
+            .any(|scheme| self.starts_with(i, scheme))
+            .then(|| {
+                let url_end = (i..end)
+                    .find(|&j| self.chars[j].is_whitespace() || "<>\"`".contains(self.chars[j]))
+                    .unwrap_or(end);
+                self.mask(i..url_end);
+                url_end
+            })
+    }
+
+    /// Mask a run of emphasis delimiters starting at the index, unless it is within a word such
+    /// as `snake_case`, returning where it ends.
+    fn emphasis(&mut self, i: usize, line: &Range<usize>) -> usize {
+        let run_end = i + self.run(i, self.chars[i], line.end);
+        let within_word =
+            i > line.start && self.is_alphanumeric(i - 1) && self.is_alphanumeric(run_end);
+        if !within_word {
+            self.mask(i..run_end);
+        }
+        run_end
+    }
+
+    /// The text with the chars that are not prose blanked out, keeping line breaks.
+    fn finish(self) -> String {
+        self.chars
+            .into_iter()
+            .zip(self.prose)
+            .map(|(c, prose)| match c {
+                _ if prose => c,
+                '\n' | '\r' => c,
+                _ => ' ',
+            })
+            .collect()
+    }
+}
+
+/// Blank out the code blocks, inline code, URLs, link targets, HTML tags and emphasis markers of a
+/// Markdown document.
+pub fn markdown(text: &str) -> String {
+    let mut m = Masker::new(text);
+    let lines = m.lines();
+    let mut front_matter = lines
+        .first()
+        .is_some_and(|line| m.text(line.clone()) == "---");
+    // the fence char and length of the code block we are in
+    let mut fence: Option<(char, usize)> = None;
+    for (n, line) in lines.into_iter().enumerate() {
+        if front_matter {
+            m.mask(line.clone());
+            front_matter = n == 0 || !matches!(m.text(line).as_str(), "---" | "...");
+            continue;
+        }
+        let indent = m.indent(&line);
+        let start = line.start + indent;
+        if let Some((c, len)) = fence {
+            m.mask(line.clone());
+            let run = m.run(start, c, line.end);
+            if indent < 4 && run >= len && m.text(start + run..line.end).trim().is_empty() {
+                fence = None;
+            }
+            continue;
+        }
+        if indent < 4 {
+            fence = ['`', '~']
+                .into_iter()
+                .map(|c| (c, m.run(start, c, line.end)))
+                .find(|(_, run)| *run >= 3);
+            if fence.is_some() {
+                m.mask(line);
+                continue;
+            }
+            let heading = m.run(start, '#', line.end);
+            if heading > 0
+                && m.chars
+                    .get(start + heading)
+                    .is_none_or(|c| c.is_whitespace())
+            {
+                m.mask(start..start + heading);
+            }
+        }
+        markdown_inline(&mut m, start..line.end);
+    }
+    m.finish()
+}
+
+fn markdown_inline(m: &mut Masker, line: Range<usize>) {
+    let mut i = line.start;
+    while i < line.end {
+        if let Some(end) = m.url(i, line.end) {
+            i = end;
+            continue;
+        }
+        match m.chars[i] {
+            '`' => {
+                // inline code ends with a run of backticks of the same length
+                let run = m.run(i, '`', line.end);
+                let mut j = i + run;
+                let mut end = i + run;
+                while j < line.end {
+                    let close = m.run(j, '`', line.end);
+                    if close == run {
+                        end = j + close;
+                        break;
+                    }
+                    j += close.max(1);
+                }
+                m.mask(i..end);
+                i = end;
+            }
+            '\\' => {
+                m.mask(i..(i + 2).min(line.end));
+                i += 2;
+            }
+            '<' => {
+                // autolinks and HTML tags
+                let tag = m
+                    .chars
+                    .get(i + 1)
+                    .is_some_and(|c| c.is_alphabetic() || *c == '/' || *c == '!');
+                match m.find(i..line.end, ">").filter(|_| tag) {
+                    Some(close) => {
+                        m.mask(i..close + 1);
+                        i = close + 1;
+                    }
+                    None => i += 1,
+                }
+            }
+            '[' => {
+                m.mask(i..i + 1);
+                i += 1;
+            }
+            '!' if m.chars.get(i + 1) == Some(&'[') => {
+                m.mask(i..i + 1);
+                i += 1;
+            }
+            ']' => {
+                // the link text is prose, but not its target
+                let target = match m.chars.get(i + 1) {
+                    Some('(') => m.group_end(i + 1, '(', ')', line.end),
+                    Some('[') => m.group_end(i + 1, '[', ']', line.end),
+                    Some(':') if m.chars[line.start] == '[' => {
+                        // a link reference definition
+                        m.mask(line.clone());
+                        return;
+                    }
+                    _ => None,
+                };
+                let end = target.unwrap_or(i + 1);
+                m.mask(i..end);
+                i = end;
+            }
+            '*' | '_' | '~' => i = m.emphasis(i, &line),
+            _ => i += 1,
+        }
+    }
+}
+
+/// Blank out the commands, comments, math, verbatim environments and non-prose arguments (e.g.
+/// labels and citations) of a LaTeX document.
+pub fn latex(text: &str) -> String {
+    let mut m = Masker::new(text);
+    let len = m.len();
+    let mut i = 0;
+    while i < len {
+        match m.chars[i] {
+            '%' => {
+                let end = m.find(i..len, "\n").unwrap_or(len);
+                m.mask(i..end);
+                i = end;
+            }
+            '\\' => i = latex_command(&mut m, i),
+            '$' => {
+                let delimiter = if m.starts_with(i, "$$") { "$$" } else { "$" };
+                let start = i + delimiter.len();
+                let mut end = start;
+                while end < len && !m.starts_with(end, delimiter) {
+                    end += if m.chars[end] == '\\' { 2 } else { 1 };
+                }
+                let end = (end + delimiter.len()).min(len);
+                m.mask(i..end);
+                i = end;
+            }
+            '{' | '}' | '~' => {
+                m.mask(i..i + 1);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    m.finish()
+}
+
+/// The number of leading arguments of the command that are not prose.
+fn latex_arguments(command: &str) -> usize {
+    match command {
+        "label" | "ref" | "eqref" | "pageref" | "autoref" | "cref" | "Cref" | "cite" | "citep"
+        | "citet" | "parencite" | "textcite" | "autocite" | "nocite" | "url" | "href" | "input"
+        | "include" | "includeonly" | "includegraphics" | "usepackage" | "RequirePackage"
+        | "documentclass" | "bibliography" | "bibliographystyle" | "addbibresource" | "bibitem"
+        | "graphicspath" | "pagestyle" | "thispagestyle" | "pagenumbering" | "vspace"
+        | "hspace" | "color" | "textcolor" | "hypersetup" | "geometry" => 1,
+        "setlength" | "addtolength" | "setcounter" | "addtocounter" => 2,
+        "newcommand" | "renewcommand" | "providecommand" | "newenvironment"
+        | "renewenvironment" | "def" => usize::MAX,
+        _ => 0,
+    }
+}
+
+/// Mask the command starting at the index, returning where it ends.
+fn latex_command(m: &mut Masker, i: usize) -> usize {
+    let len = m.len();
+    let name_end = i
+        + 1
+        + m.chars[i + 1..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+    if name_end == i + 1 {
+        let end = match m.chars.get(i + 1) {
+            Some('(') => m.find(i..len, "\\)").map_or(len, |end| end + 2),
+            Some('[') => m.find(i..len, "\\]").map_or(len, |end| end + 2),
+            // an escaped char, or a line break
+            _ => (i + 2).min(len),
+        };
+        m.mask(i..end);
+        return end;
+    }
+    let name = m.text(i + 1..name_end);
+    let mut j = name_end + usize::from(m.chars.get(name_end) == Some(&'*'));
+    m.mask(i..j);
+    if name == "begin" || name == "end" {
+        let Some(group_end) = m
+            .group_end(j, '{', '}', len)
+            .filter(|_| m.chars.get(j) == Some(&'{'))
+        else {
+            return j;
+        };
+        let environment = m.text(j + 1..group_end - 1);
+        m.mask(j..group_end);
+        if name == "begin" && LATEX_ENVIRONMENTS.contains(&environment.trim_end_matches('*')) {
+            let end = format!("\\end{{{environment}}}");
+            let end = m
+                .find(group_end..len, &end)
+                .map_or(len, |k| k + end.chars().count());
+            m.mask(group_end..end);
+            return end;
+        }
+        return group_end;
+    }
+    // options are never prose
+    let mut arguments = latex_arguments(&name);
+    loop {
+        let group = match m.chars.get(j) {
+            Some('[') => m.group_end(j, '[', ']', len),
+            Some('{') if arguments > 0 => {
+                arguments -= 1;
+                m.group_end(j, '{', '}', len)
+            }
+            _ => None,
+        };
+        let Some(end) = group else {
+            return j;
+        };
+        m.mask(j..end);
+        j = end;
+    }
+}
+
+/// Blank out the literal blocks, code and other non-prose directives, comments, inline literals,
+/// roles, link targets and emphasis markers of a reStructuredText document.
+pub fn restructured_text(text: &str) -> String {
+    let mut m = Masker::new(text);
+    // the indentation of the block being masked, which continues while lines are indented more
+    let mut block: Option<usize> = None;
+    // the indentation of the directive whose content is being read
+    let mut directive: Option<usize> = None;
+    for line in m.lines() {
+        let indent = m.indent(&line);
+        let start = line.start + indent;
+        if start == line.end {
+            continue;
+        }
+        if let Some(block_indent) = block {
+            if indent > block_indent {
+                m.mask(line);
+                continue;
+            }
+            block = None;
+        }
+        if let Some(directive_indent) = directive {
+            if indent > directive_indent {
+                if m.chars[start] == ':' {
+                    // an option of the directive
+                    m.mask(line);
+                } else {
+                    rst_inline(&mut m, start..line.end);
+                }
+                continue;
+            }
+            directive = None;
+        }
+        if m.starts_with(start, "..") && (start + 2 == line.end || m.chars[start + 2] == ' ') {
+            let rest = (start + 3).min(line.end);
+            let mut name_end = rest;
+            while name_end < line.end
+                && !m.chars[name_end].is_whitespace()
+                && !m.starts_with(name_end, "::")
+            {
+                name_end += 1;
+            }
+            if name_end > rest && m.starts_with(name_end, "::") {
+                let name = m.text(rest..name_end);
+                m.mask(start..name_end + 2);
+                if RST_LITERAL_DIRECTIVES.contains(&name.as_str()) {
+                    m.mask(name_end + 2..line.end);
+                    block = Some(indent);
+                } else {
+                    if RST_PATH_DIRECTIVES.contains(&name.as_str()) {
+                        m.mask(name_end + 2..line.end);
+                    } else {
+                        rst_inline(&mut m, name_end + 2..line.end);
+                    }
+                    directive = Some(indent);
+                }
+            } else if let Some(close) = m
+                .find(rest..line.end, "]")
+                .filter(|_| m.starts_with(rest, "["))
+            {
+                // a footnote or citation
+                m.mask(start..close + 1);
+                rst_inline(&mut m, close + 1..line.end);
+                directive = Some(indent);
+            } else {
+                // a comment, hyperlink target or substitution definition
+                m.mask(line);
+                block = Some(indent);
+            }
+            continue;
+        }
+        rst_inline(&mut m, start..line.end);
+        let text = m.text(start..line.end);
+        if text.trim_end().ends_with("::") {
+            // the paragraph introduces a literal block
+            let end = start + text.trim_end().chars().count();
+            m.mask(end - 2..end);
+            block = Some(indent);
+        }
+    }
+    m.finish()
+}
+
+fn rst_inline(m: &mut Masker, line: Range<usize>) {
+    let mut i = line.start;
+    while i < line.end {
+        if let Some(end) = m.url(i, line.end) {
+            i = end;
+            continue;
+        }
+        match m.chars[i] {
+            '`' if m.starts_with(i, "``") => {
+                let end = m.find(i + 2..line.end, "``").map_or(i + 2, |end| end + 2);
+                m.mask(i..end);
+                i = end;
+            }
+            '`' => {
+                let Some(close) = m.find(i + 1..line.end, "`") else {
+                    m.mask(i..i + 1);
+                    i += 1;
+                    continue;
+                };
+                m.mask(i..i + 1);
+                // the text of a link is prose, but not its target
+                if m.chars[close - 1] == '>' {
+                    if let Some(target) = (i + 1..close).rev().find(|&j| m.chars[j] == '<') {
+                        m.mask(target..close);
+                    }
+                }
+                let end = close + 1 + m.run(close + 1, '_', line.end);
+                m.mask(close..end);
+                i = end;
+            }
+            ':' => {
+                // a role, whose text is usually code or a reference
+                let name_end = i
+                    + 1
+                    + m.chars[i + 1..line.end]
+                        .iter()
+                        .take_while(|c| c.is_alphanumeric() || "-_.+".contains(**c))
+                        .count();
+                let role = name_end > i + 1 && m.starts_with(name_end, ":`");
+                match m.find(name_end + 2..line.end, "`").filter(|_| role) {
+                    Some(close) => {
+                        m.mask(i..close + 1);
+                        i = close + 1;
+                    }
+                    None => i += 1,
+                }
+            }
+            '|' => {
+                // a substitution reference
+                let end = m
+                    .find(i + 1..line.end, "|")
+                    .filter(|_| m.chars.get(i + 1).is_some_and(|c| !c.is_whitespace()));
+                match end {
+                    Some(close) => {
+                        let end = close + 1 + m.run(close + 1, '_', line.end);
+                        m.mask(i..end);
+                        i = end;
+                    }
+                    None => i += 1,
+                }
+            }
+            '[' => {
+                // a footnote or citation reference
+                let end = m
+                    .find(i + 1..line.end, "]_")
+                    .filter(|&close| !m.chars[i + 1..close].iter().any(|c| c.is_whitespace()));
+                match end {
+                    Some(close) => {
+                        m.mask(i..close + 2);
+                        i = close + 2;
+                    }
+                    None => i += 1,
+                }
+            }
+            '\\' => {
+                m.mask(i..(i + 2).min(line.end));
+                i += 2;
+            }
+            '*' | '_' => i = m.emphasis(i, &line),
+            _ => i += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+    use proptest::prelude::*;
+
+    /// The lines of prose, checking that they line up with the text.
+    fn lines(prose: String, text: &str) -> Vec<String> {
+        assert_eq!(prose.lines().count(), text.lines().count());
+        for (prose, text) in prose.lines().zip(text.lines()) {
+            assert_eq!(prose.chars().count(), text.chars().count());
+        }
+        prose
+            .lines()
+            .map(|line| line.trim_end().to_owned())
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn lines_up_with_any_text(text in r"[a \\\n\r`*_~#<>\[\]()!:|{}$%.]{0,40}") {
+            for extract in [markdown, latex, restructured_text] {
+                let prose = extract(&text);
+                prop_assert_eq!(prose.lines().count(), text.lines().count());
+                for (prose, text) in prose.lines().zip(text.lines()) {
+                    prop_assert_eq!(prose.chars().count(), text.chars().count());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn markdown_prose() {
+        let text = r#"---
+title: Dogs
+---
+# Dogs and `cats`
+
+A **dog** is a _barking_ animal, see [the docs](https://example.com/dogs) or <https://dogs.org>.
+Use `snake_case` or visit www.example.com, ![a dog](dog.png) and [ref][1] <b>bold</b>.
+
+```rust
+let dog = "dog";
+```
+
+[1]: https://example.com "Example"
+"#;
+        let expected = expect![[r#"
+            [
+                "",
+                "",
+                "",
+                "  Dogs and",
+                "",
+                "A   dog   is a  barking  animal, see  the docs                            or                   .",
+                "Use              or visit                    a dog           and  ref        bold    .",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "",
+            ]
+        "#]];
+        expected.assert_debug_eq(&lines(markdown(text), text));
+    }
+
+    #[test]
+    fn latex_prose() {
+        let text = r#"\documentclass[a4paper]{article}
+\usepackage{amsmath}
+\begin{document}
+\section{Dogs and cats} % a comment
+A dog~\cite{dogs} is $x^2$ a \textbf{barking} animal, see Figure~\ref{fig:dog}.
+\begin{equation*}
+  E = mc^2
+\end{equation*}
+Costs 5\% more \(a + b\) than \href{https://example.com}{the docs}.
+\end{document}
+"#;
+        let expected = expect![[r#"
+            [
+                "",
+                "",
+                "",
+                "         Dogs and cats",
+                "A dog             is       a         barking  animal, see Figure              .",
+                "",
+                "",
+                "",
+                "Costs 5   more           than                            the docs .",
+                "",
+            ]
+        "#]];
+        expected.assert_debug_eq(&lines(latex(text), text));
+    }
+
+    #[test]
+    fn restructured_text_prose() {
+        let text = r#"Dogs
+====
+
+A **dog** is a *barking* animal, see `the docs <https://example.com>`_ and :func:`bark`.
+Use ``snake_case`` or |logo| and [1]_ a reference_ here::
+
+    let dog = "dog";
+
+.. code-block:: python
+
+    dog = "dog"
+
+.. note:: Dogs bark.
+   :class: aside
+
+   They are loyal.
+
+.. image:: dog.png
+.. _reference: https://example.com
+.. a comment
+   continued
+"#;
+        let expected = expect![[r#"
+            [
+                "Dogs",
+                "====",
+                "",
+                "A   dog   is a  barking  animal, see  the docs                         and             .",
+                "Use                or        and      a reference  here",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "          Dogs bark.",
+                "",
+                "",
+                "   They are loyal.",
+                "",
+                "",
+                "",
+                "",
+                "",
+            ]
+        "#]];
+        expected.assert_debug_eq(&lines(restructured_text(text), text));
+    }
+}
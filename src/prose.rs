@@ -0,0 +1,496 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::markup;
+
+/// How to find the prose in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Language<'a> {
+    /// Source code, whose comments and strings are prose.
+    Code(Cow<'a, Syntax>),
+    Markdown,
+    Latex,
+    RestructuredText,
+}
+
+/// Where the prose is in the source code of a language: its comments and string literals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Syntax {
+    /// Markers starting comments that run to the end of the line.
+    pub line_comments: Vec<String>,
+    /// Markers starting and ending block comments.
+    pub block_comments: Vec<[String; 2]>,
+    /// Delimiters starting and ending string literals, including docstrings.
+    pub strings: Vec<[String; 2]>,
+    /// The character escaping the next one in string literals.
+    pub escape: Option<char>,
+    /// Whether block comments nest, as in Rust.
+    pub nested_comments: bool,
+    /// Prefixes of raw strings, which close with a quote followed by as many `#` as followed the
+    /// prefix and have no escapes, e.g. `r` for Rust's `r#"…"#`.
+    pub raw_strings: Vec<String>,
+    /// Whether a single char (or escape sequence) in single quotes is a char literal, which is
+    /// code, with other single quotes being code too, e.g. Rust's lifetimes.
+    pub char_literals: bool,
+    /// Whether `/` where a value is expected starts a regular expression literal, which is code,
+    /// as in JavaScript.
+    pub regex_literals: bool,
+}
+
+impl Syntax {
+    fn new(line_comments: &[&str], block_comments: &[[&str; 2]], strings: &[[&str; 2]]) -> Self {
+        Self {
+            line_comments: line_comments.iter().map(|m| m.to_string()).collect(),
+            block_comments: block_comments
+                .iter()
+                .map(|d| d.map(str::to_owned))
+                .collect(),
+            strings: strings.iter().map(|d| d.map(str::to_owned)).collect(),
+            escape: Some('\\'),
+            ..Self::default()
+        }
+    }
+
+    /// The length of the literal starting the text that is code rather than prose, such as a char
+    /// or regular expression literal, given the code before it.
+    fn code_literal(&self, text: &str, before: &str) -> Option<usize> {
+        if self.char_literals && text.starts_with('\'') {
+            return Some(self.char_literal(text).unwrap_or(1));
+        }
+        if self.regex_literals && text.starts_with('/') && expects_value(before) {
+            return regex_literal(text);
+        }
+        None
+    }
+
+    /// The length of the char literal starting the text, if it is one rather than a lifetime.
+    fn char_literal(&self, text: &str) -> Option<usize> {
+        let mut chars = text.char_indices().skip(1);
+        let (_, c) = chars.next()?;
+        if c == '\'' || c == '\n' {
+            return None;
+        }
+        let end = if Some(c) == self.escape {
+            // skip the escaped char, so that `'\''` closes at the last quote
+            let (i, escaped) = chars.next()?;
+            let start = i + escaped.len_utf8();
+            // the longest escape is a unicode one, e.g. `'\u{10FFFF}'`
+            text[start..]
+                .find('\'')
+                .map(|j| start + j)
+                .filter(|end| *end <= 11)?
+        } else {
+            let (i, quote) = chars.next()?;
+            (quote == '\'').then_some(i)?
+        };
+        Some(end + 1)
+    }
+
+    /// The raw string starting the text, as the length of its opening delimiter and its closing
+    /// one.
+    fn raw_string(&self, text: &str, before: &str) -> Option<(usize, String)> {
+        if before.chars().next_back().is_some_and(is_identifier) {
+            return None;
+        }
+        self.raw_strings.iter().find_map(|prefix| {
+            let rest = text.strip_prefix(prefix.as_str())?;
+            let hashes = rest.len() - rest.trim_start_matches('#').len();
+            rest[hashes..].starts_with('"').then(|| {
+                (
+                    prefix.len() + hashes + 1,
+                    format!("\"{}", "#".repeat(hashes)),
+                )
+            })
+        })
+    }
+
+    /// The region starting the text, as its opening and closing delimiters and whether it is a
+    /// string, preferring the longest opening delimiter.
+    fn region<'a>(&'a self, text: &str) -> Option<(&'a str, Option<&'a str>, bool)> {
+        let line_comments = self.line_comments.iter().map(|m| (m.as_str(), None, false));
+        let block_comments = self
+            .block_comments
+            .iter()
+            .map(|[open, close]| (open.as_str(), Some(close.as_str()), false));
+        let strings = self
+            .strings
+            .iter()
+            .map(|[open, close]| (open.as_str(), Some(close.as_str()), true));
+        line_comments
+            .chain(block_comments)
+            .chain(strings)
+            .filter(|(open, _, _)| !open.is_empty() && text.starts_with(open))
+            .reduce(|longest, region| {
+                if region.0.len() > longest.0.len() {
+                    region
+                } else {
+                    longest
+                }
+            })
+    }
+}
+
+/// The syntax of the languages, by `languageId`, that we know without being configured.
+pub fn builtin(language_id: &str) -> Option<Syntax> {
+    let c_comments = [["/*", "*/"]];
+    let quotes = [["\"", "\""], ["'", "'"]];
+    let syntax = match language_id {
+        "rust" => Syntax {
+            nested_comments: true,
+            raw_strings: vec!["r".to_owned(), "br".to_owned(), "cr".to_owned()],
+            char_literals: true,
+            ..Syntax::new(&["//"], &c_comments, &[["\"", "\""]])
+        },
+        "c" | "cpp" | "csharp" | "java" | "kotlin" | "scala" | "swift" | "dart" | "objective-c"
+        | "objective-cpp" => Syntax::new(&["//"], &c_comments, &quotes),
+        "javascript" | "javascriptreact" | "typescript" | "typescriptreact" => Syntax {
+            regex_literals: true,
+            ..Syntax::new(
+                &["//"],
+                &c_comments,
+                &[["\"", "\""], ["'", "'"], ["`", "`"]],
+            )
+        },
+        "go" => Syntax::new(&["//"], &c_comments, &[["\"", "\""], ["`", "`"]]),
+        "python" => Syntax::new(
+            &["#"],
+            &[],
+            &[
+                ["\"\"\"", "\"\"\""],
+                ["'''", "'''"],
+                ["\"", "\""],
+                ["'", "'"],
+            ],
+        ),
+        "shellscript" | "ruby" | "perl" | "r" | "toml" => Syntax::new(&["#"], &[], &quotes),
+        "lua" => Syntax::new(&["--"], &[["--[[", "]]"]], &quotes),
+        "haskell" => Syntax::new(&["--"], &[["{-", "-}"]], &[["\"", "\""]]),
+        "sql" => Syntax::new(&["--"], &c_comments, &[["'", "'"]]),
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// The `languageId` an editor would give the file, from its extension, or else the extension
+/// itself so that configured languages can be named after it.
+pub fn language_id(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?;
+    let language_id = match extension.to_lowercase().as_str() {
+        "md" | "markdown" => "markdown",
+        "tex" | "latex" => "latex",
+        "rst" => "restructuredtext",
+        "rs" => "rust",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => "cpp",
+        "cs" => "csharp",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "scala" => "scala",
+        "swift" => "swift",
+        "dart" => "dart",
+        "m" => "objective-c",
+        "mm" => "objective-cpp",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "go" => "go",
+        "py" | "pyi" => "python",
+        "sh" | "bash" | "zsh" => "shellscript",
+        "rb" => "ruby",
+        "pl" | "pm" => "perl",
+        "r" => "r",
+        "toml" => "toml",
+        "lua" => "lua",
+        "hs" => "haskell",
+        "sql" => "sql",
+        _ => extension,
+    };
+    Some(language_id.to_owned())
+}
+
+/// The language of the document, from the configured languages or else the built in ones, if
+/// only part of it is prose.
+pub fn language<'a>(
+    languages: &'a BTreeMap<String, Syntax>,
+    language_id: &str,
+) -> Option<Language<'a>> {
+    if let Some(syntax) = languages.get(language_id) {
+        return Some(Language::Code(Cow::Borrowed(syntax)));
+    }
+    match language_id {
+        "markdown" => Some(Language::Markdown),
+        "latex" | "tex" => Some(Language::Latex),
+        "restructuredtext" => Some(Language::RestructuredText),
+        _ => builtin(language_id).map(|syntax| Language::Code(Cow::Owned(syntax))),
+    }
+}
+
+/// Blank out everything but the prose of the text, keeping line breaks so that positions in the
+/// prose are the same as in the text.
+pub fn extract(text: &str, language: &Language) -> String {
+    match language {
+        Language::Code(syntax) => extract_code(text, syntax),
+        Language::Markdown => markup::markdown(text),
+        Language::Latex => markup::latex(text),
+        Language::RestructuredText => markup::restructured_text(text),
+    }
+}
+
+/// Blank out everything but the comments and strings of the code.
+fn extract_code(text: &str, syntax: &Syntax) -> String {
+    let mut prose = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let before = &text[..text.len() - rest.len()];
+        if let Some(len) = syntax.code_literal(rest, before) {
+            blank(&mut prose, &rest[..len]);
+            rest = &rest[len..];
+        } else if let Some((open, close)) = syntax.raw_string(rest, before) {
+            blank(&mut prose, &rest[..open]);
+            rest = extract_region(&mut prose, &rest[open..], Some(&close), None, None);
+        } else if let Some((open, close, string)) = syntax.region(rest) {
+            blank(&mut prose, open);
+            let escape = syntax.escape.filter(|_| string);
+            let nested = (syntax.nested_comments && !string && close.is_some()).then_some(open);
+            rest = extract_region(&mut prose, &rest[open.len()..], close, escape, nested);
+        } else {
+            blank(&mut prose, &rest[..c.len_utf8()]);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    prose
+}
+
+/// Copy the prose of a comment or string up to and including its closing delimiter, or the end
+/// of the line without one, returning the text after it. `nested` is the opening delimiter of
+/// comments that nest.
+fn extract_region<'a>(
+    prose: &mut String,
+    mut rest: &'a str,
+    close: Option<&str>,
+    escape: Option<char>,
+    nested: Option<&str>,
+) -> &'a str {
+    let mut depth = 0;
+    while let Some(c) = rest.chars().next() {
+        match close {
+            Some(close) if rest.starts_with(close) => {
+                blank(prose, close);
+                rest = &rest[close.len()..];
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                continue;
+            }
+            // line comments end with the line
+            None if c == '\n' => break,
+            _ => {}
+        }
+        if let Some(open) = nested.filter(|open| rest.starts_with(open)) {
+            blank(prose, open);
+            rest = &rest[open.len()..];
+            depth += 1;
+        } else if Some(c) == escape {
+            // blank out the escape sequence so it doesn't join onto the words around it
+            let len = rest[c.len_utf8()..]
+                .chars()
+                .next()
+                .map_or(0, |escaped| escaped.len_utf8());
+            blank(prose, &rest[..c.len_utf8() + len]);
+            rest = &rest[c.len_utf8() + len..];
+        } else {
+            prose.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    rest
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Whether a value rather than an operator is expected after the code, so that `/` starts a
+/// regular expression rather than dividing.
+fn expects_value(before: &str) -> bool {
+    let before = before.trim_end();
+    match before.chars().next_back() {
+        None => true,
+        Some(c) if is_identifier(c) => {
+            let word = &before[before.trim_end_matches(is_identifier).len()..];
+            matches!(
+                word,
+                "return"
+                    | "typeof"
+                    | "instanceof"
+                    | "in"
+                    | "of"
+                    | "new"
+                    | "delete"
+                    | "void"
+                    | "throw"
+                    | "case"
+                    | "do"
+                    | "else"
+                    | "yield"
+                    | "await"
+            )
+        }
+        Some(c) => !matches!(c, ')' | ']' | '}' | '"' | '\'' | '`'),
+    }
+}
+
+/// The length of the regular expression literal starting the text, if it ends on the same line.
+fn regex_literal(text: &str) -> Option<usize> {
+    if text.starts_with("//") || text.starts_with("/*") {
+        return None;
+    }
+    let mut class = false;
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return None,
+            '[' => class = true,
+            ']' => class = false,
+            '/' if !class => {
+                // and any flags
+                let flags = text[i + 1..]
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(text.len() - i - 1);
+                return Some(i + 1 + flags);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Push a space for each char of the text, other than line breaks.
+fn blank(prose: &mut String, text: &str) {
+    prose.extend(text.chars().map(|c| match c {
+        '\n' | '\r' => c,
+        _ => ' ',
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    /// The lines of prose, checking that they line up with the text.
+    fn extract_builtin(language_id: &str, text: &str) -> Vec<String> {
+        let prose = extract_code(text, &builtin(language_id).unwrap());
+        assert_eq!(prose.lines().count(), text.lines().count());
+        for (prose, text) in prose.lines().zip(text.lines()) {
+            assert_eq!(prose.chars().count(), text.chars().count());
+        }
+        prose
+            .lines()
+            .map(|line| line.trim_end().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn rust() {
+        let text = r###"/// Greet the world.
+fn main() {
+    /* a block
+       comment */
+    println!("hello, \"world\"\n"); // done
+    let _ = 'a';
+    let q = '"'; // after a quote
+    let e = ['\'', '\u{1F600}']; // after escapes
+    fn f<'a>(x: &'a str) {} // after lifetimes
+    let r = r#"a "b" c"#; // after a raw string
+    let b = (b"bytes", br"raw", cr##"c "#"##); // after byte strings
+    /* outer /* inner */ still */ let x = 1; // after nesting
+}"###;
+        let expected = expect![[r##"
+            [
+                "  / Greet the world.",
+                "",
+                "       a block",
+                "       comment",
+                "              hello,   world           done",
+                "",
+                "                    after a quote",
+                "                                    after escapes",
+                "                               after lifetimes",
+                "               a \"b\" c       after a raw string",
+                "               bytes      raw        c \"#         after byte strings",
+                "       outer    inner    still                  after nesting",
+                "",
+            ]
+        "##]];
+        expected.assert_debug_eq(&extract_builtin("rust", text));
+    }
+
+    #[test]
+    fn javascript() {
+        let text = r#"let r = /"[/"]/g; // a regex
+let half = x / 2; // a "division" / 2
+return /it's/.test(s) ? 'yes' : "no";"#;
+        let expected = expect![[r#"
+            [
+                "                     a regex",
+                "                     a \"division\" / 2",
+                "                         yes     no",
+            ]
+        "#]];
+        expected.assert_debug_eq(&extract_builtin("javascript", text));
+    }
+
+    #[test]
+    fn python() {
+        let text = r#"def greet(name):
+    """Greet someone by name.
+
+    It's polite."""
+    print(f'hello {name}')  # say hello
+"#;
+        let expected = expect![[r#"
+            [
+                "",
+                "       Greet someone by name.",
+                "",
+                "    It's polite.",
+                "            hello {name}      say hello",
+            ]
+        "#]];
+        expected.assert_debug_eq(&extract_builtin("python", text));
+    }
+
+    #[test]
+    fn unterminated() {
+        assert_eq!(extract_builtin("c", "x = \"abc\\"), ["     abc"]);
+        assert_eq!(
+            extract_builtin("lua", "--[[ a ]] b -- c"),
+            ["     a         c"]
+        );
+    }
+
+    #[test]
+    fn configured() {
+        let languages: BTreeMap<String, Syntax> = serde_json::from_value(serde_json::json!({
+            "elixir": { "line_comments": ["#"], "strings": [["\"", "\""]], "escape": "\\" },
+            "rust": { "line_comments": ["//"] },
+        }))
+        .unwrap();
+        let elixir = language(&languages, "elixir").unwrap();
+        assert_eq!(extract("x = \"dog\" # cat", &elixir), "     dog    cat");
+        let rust = language(&languages, "rust").unwrap();
+        assert_eq!(extract("\"dog\" // cat", &rust), "         cat");
+        assert!(language(&languages, "plaintext").is_none());
+        assert_eq!(language(&languages, "markdown"), Some(Language::Markdown));
+    }
+}
@@ -0,0 +1,487 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use wordnet_ls::wordnet::LexicalRelation;
+use wordnet_ls::wordnet::PartOfSpeech;
+use wordnet_ls::wordnet::SemanticRelation;
+use wordnet_ls::wordnet::SynSet;
+
+use crate::Dict;
+use crate::InitializationOptions;
+use crate::COMPLETION_LIMIT;
+
+/// Where the commands run without an editor find the database.
+#[derive(Debug, Clone, clap::Args)]
+pub struct DatabaseArgs {
+    /// Location of the database, found as the server finds it when not given.
+    #[clap(long)]
+    wordnet: Option<PathBuf>,
+    /// User lexicon file to add to the database, can be given more than once.
+    #[clap(long = "lexicon")]
+    lexicons: Vec<PathBuf>,
+}
+
+impl DatabaseArgs {
+    /// Load the database, with the arguments taking precedence over the options.
+    ///
+    /// Failing to load the database or a lexicon is an error.
+    pub fn load(self, options: &InitializationOptions) -> Result<Dict, String> {
+        let mut lexicons = options.lexicons.clone().unwrap_or_default();
+        lexicons.extend(self.lexicons);
+        let (wordnet, errors) = crate::load_wordnet(&InitializationOptions {
+            wordnet: self.wordnet.or_else(|| options.wordnet.clone()),
+            lexicons: Some(lexicons),
+            mmap: options.mmap,
+            ..Default::default()
+        });
+        // unlike the server, scripts are better off failing than running without the database
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        Ok(Dict::new(wordnet))
+    }
+}
+
+/// Options for the commands that query the database.
+#[derive(Debug, Clone, clap::Args)]
+pub struct QueryArgs {
+    #[clap(flatten)]
+    database: DatabaseArgs,
+    /// Print JSON for scripts instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+/// A question about a word.
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// The senses of the word, or everything about it with `all`.
+    Define {
+        all: bool,
+    },
+    Synonyms,
+    Antonyms,
+    Hypernyms,
+    Lemmatize,
+    /// Words starting with the prefix.
+    Complete {
+        limit: Option<usize>,
+    },
+}
+
+/// A sense of a word, as printed by `define`.
+#[derive(Debug, Serialize)]
+struct Sense {
+    lemma: String,
+    part_of_speech: PartOfSpeech,
+    definition: String,
+    examples: Vec<String>,
+    synonyms: Vec<String>,
+    antonyms: Vec<String>,
+    /// The words related to the sense, and to the lemma in it, by each relationship other than
+    /// antonymy, with `define --all`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    relationships: Option<BTreeMap<String, BTreeSet<String>>>,
+}
+
+/// Answer the query about the word, returning whether it was found.
+pub fn run(query: Query, word: &str, args: QueryArgs) -> Result<bool, String> {
+    let dict = args.database.load(&InitializationOptions::default())?;
+    match answer(&dict, &query, word, args.json)? {
+        Some(answer) => {
+            println!("{answer}");
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// The answer to the query as text or JSON, `None` if the word wasn't found.
+fn answer(dict: &Dict, query: &Query, word: &str, json: bool) -> Result<Option<String>, String> {
+    let answer = match query {
+        Query::Define { all } => {
+            let senses = senses(dict, word, *all);
+            if senses.is_empty() {
+                return Ok(None);
+            }
+            if !json {
+                return Ok(Some(definitions(&senses)));
+            }
+            Some(serde_json::to_value(senses))
+        }
+        Query::Synonyms => related(dict, word, |synset, _| synset.synonyms()),
+        Query::Antonyms => related(dict, word, |synset, lemma| {
+            synset
+                .lemmas
+                .iter()
+                .filter(|l| l.word == lemma)
+                .flat_map(|l| l.antonyms(&dict.wordnet))
+                .collect()
+        }),
+        Query::Hypernyms => related(dict, word, |synset, _| {
+            synset
+                .relationships
+                .iter()
+                .filter(|r| {
+                    matches!(
+                        r.relation,
+                        SemanticRelation::Hypernym | SemanticRelation::InstanceHypernym
+                    )
+                })
+                .filter_map(|r| dict.wordnet.resolve(r.part_of_speech, r.synset_offset))
+                .flat_map(|hypernym| hypernym.synonyms())
+                .collect()
+        }),
+        Query::Lemmatize => {
+            let mut lemmas = BTreeMap::new();
+            dict.wordnet.lemmatize(word).for_each(|pos, words| {
+                if !words.is_empty() {
+                    lemmas.insert(pos, words);
+                }
+            });
+            (!lemmas.is_empty()).then(|| serde_json::to_value(lemmas))
+        }
+        Query::Complete { limit } => {
+            let completions = dict
+                .complete(&word.to_owned(), false, limit.unwrap_or(COMPLETION_LIMIT))
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<_>>();
+            (!completions.is_empty()).then(|| serde_json::to_value(completions))
+        }
+    };
+    let Some(answer) = answer.transpose().map_err(|err| err.to_string())? else {
+        return Ok(None);
+    };
+    if json {
+        return serde_json::to_string_pretty(&answer)
+            .map(Some)
+            .map_err(|err| err.to_string());
+    }
+    Ok(Some(lines(query, &answer)))
+}
+
+/// The words of the answer as lines of text, with the part of speech after lemmas.
+fn lines(query: &Query, answer: &serde_json::Value) -> String {
+    let words = |value: &serde_json::Value| {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|word| word.as_str())
+            .map(|word| word.replace('_', " "))
+            .collect::<Vec<_>>()
+    };
+    match (query, answer) {
+        (Query::Lemmatize, serde_json::Value::Object(lemmas)) => lemmas
+            .iter()
+            .flat_map(|(pos, lemmas)| {
+                words(lemmas)
+                    .into_iter()
+                    .map(move |l| format!("{l}\t{pos}"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        (_, serde_json::Value::Object(related)) => related
+            .values()
+            .flat_map(words)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => words(answer).join("\n"),
+    }
+}
+
+/// The senses as text, numbered under each lemma and part of speech.
+fn definitions(senses: &[Sense]) -> String {
+    let words = |words: &mut dyn Iterator<Item = &String>| {
+        words
+            .map(|w| w.replace('_', " "))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut blocks = Vec::<String>::new();
+    let mut heading = None;
+    let mut number = 0;
+    for sense in senses {
+        let lemma = Some((&sense.lemma, sense.part_of_speech));
+        if heading != lemma {
+            heading = lemma;
+            number = 0;
+            blocks.push(format!("**{}** _{}_", sense.lemma, sense.part_of_speech));
+        }
+        number += 1;
+        let block = blocks.last_mut().unwrap();
+        write!(block, "\n{number}. {}.", sense.definition).unwrap();
+        if !sense.examples.is_empty() {
+            write!(block, " e.g. {}.", sense.examples.join("; ")).unwrap();
+        }
+        let mut related = vec![
+            ("synonyms", words(&mut sense.synonyms.iter())),
+            ("antonyms", words(&mut sense.antonyms.iter())),
+        ];
+        for (relation, related_words) in sense.relationships.iter().flatten() {
+            related.push((relation, words(&mut related_words.iter())));
+        }
+        for (relation, related_words) in related {
+            if !related_words.is_empty() {
+                write!(block, "\n   - **{relation}**: {related_words}").unwrap();
+            }
+        }
+    }
+    blocks.join("\n\n")
+}
+
+/// The senses of each lemma of the word, with their relationships if `all`.
+fn senses(dict: &Dict, word: &str, all: bool) -> Vec<Sense> {
+    let mut senses = Vec::new();
+    dict.wordnet.lemmatize(word).for_each(|pos, lemmas| {
+        for lemma in lemmas {
+            for synset in dict.wordnet.synsets_for(&lemma, pos) {
+                let mut antonyms = synset
+                    .lemmas
+                    .iter()
+                    .filter(|l| l.word == lemma)
+                    .flat_map(|l| l.antonyms(&dict.wordnet))
+                    .collect::<Vec<_>>();
+                antonyms.sort();
+                antonyms.dedup();
+                let relationships = all.then(|| {
+                    let mut relationships = dict
+                        .semantic_relations(&synset)
+                        .into_iter()
+                        .map(|(r, words)| (r.to_string(), words))
+                        .collect::<BTreeMap<_, _>>();
+                    let lexical = synset
+                        .lemmas
+                        .iter()
+                        .filter(|l| l.word == lemma)
+                        .flat_map(|l| &l.relationships)
+                        .filter(|r| r.relation != LexicalRelation::Antonym);
+                    for r in lexical {
+                        if let Some(word) = dict.resolve_lemma(r) {
+                            relationships
+                                .entry(r.relation.to_string())
+                                .or_default()
+                                .insert(word);
+                        }
+                    }
+                    relationships
+                });
+                senses.push(Sense {
+                    lemma: lemma.clone(),
+                    part_of_speech: pos,
+                    synonyms: synset
+                        .synonyms()
+                        .into_iter()
+                        .filter(|w| *w != lemma)
+                        .collect(),
+                    antonyms,
+                    relationships,
+                    definition: synset.definition,
+                    examples: synset.examples,
+                });
+            }
+        }
+    });
+    senses
+}
+
+/// The words related to the senses of each lemma of the word, by part of speech, `None` if the
+/// word wasn't found.
+fn related(
+    dict: &Dict,
+    word: &str,
+    relate: impl Fn(&SynSet, &str) -> Vec<String>,
+) -> Option<serde_json::Result<serde_json::Value>> {
+    let mut found = false;
+    let mut related = BTreeMap::<PartOfSpeech, BTreeSet<String>>::new();
+    dict.wordnet.lemmatize(word).for_each(|pos, lemmas| {
+        for lemma in lemmas {
+            for synset in dict.wordnet.synsets_for(&lemma, pos) {
+                found = true;
+                let words = relate(&synset, &lemma).into_iter().filter(|w| *w != lemma);
+                related.entry(pos).or_default().extend(words);
+            }
+        }
+    });
+    related.retain(|_, words| !words.is_empty());
+    found.then(|| serde_json::to_value(related))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    /// The answers to each kind of query, separated by blank lines.
+    fn answers(json: bool) -> String {
+        let (_dir, dict) = crate::fixture::dogs();
+        [
+            (Query::Define { all: false }, "dogs"),
+            (Query::Define { all: true }, "dog"),
+            (Query::Synonyms, "dog"),
+            (Query::Antonyms, "dog"),
+            (Query::Hypernyms, "dog"),
+            (Query::Lemmatize, "dogs"),
+            (Query::Complete { limit: Some(1) }, "do"),
+            (Query::Synonyms, "wolf"),
+        ]
+        .iter()
+        .map(|(query, word)| {
+            answer(&dict, query, word, json)
+                .unwrap()
+                .unwrap_or_else(|| "not found".to_owned())
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+    }
+
+    #[test]
+    fn text() {
+        let expected = expect![[r#"
+            **dog** _noun_
+            1. a member of the genus Canis that has been domesticated by man since prehistoric times. e.g. the dog barked all night.
+               - **synonyms**: domestic dog
+               - **antonyms**: cat
+
+            **dog** _verb_
+            1. follow closely.
+
+            **dog** _noun_
+            1. a member of the genus Canis that has been domesticated by man since prehistoric times. e.g. the dog barked all night.
+               - **synonyms**: domestic dog
+               - **antonyms**: cat
+               - **derivationally related form**: dog
+               - **hypernym**: canid, canine
+               - **part meronym**: paw
+
+            **dog** _verb_
+            1. follow closely.
+               - **derivationally related form**: dog
+
+            domestic dog
+
+            cat
+
+            canid
+            canine
+
+            dog	noun
+            dog	verb
+
+            dog
+
+            not found"#]];
+        expected.assert_eq(&answers(false));
+    }
+
+    #[test]
+    fn json() {
+        let expected = expect![[r#"
+            [
+              {
+                "antonyms": [
+                  "cat"
+                ],
+                "definition": "a member of the genus Canis that has been domesticated by man since prehistoric times",
+                "examples": [
+                  "the dog barked all night"
+                ],
+                "lemma": "dog",
+                "part_of_speech": "noun",
+                "synonyms": [
+                  "domestic_dog"
+                ]
+              },
+              {
+                "antonyms": [],
+                "definition": "follow closely",
+                "examples": [],
+                "lemma": "dog",
+                "part_of_speech": "verb",
+                "synonyms": []
+              }
+            ]
+
+            [
+              {
+                "antonyms": [
+                  "cat"
+                ],
+                "definition": "a member of the genus Canis that has been domesticated by man since prehistoric times",
+                "examples": [
+                  "the dog barked all night"
+                ],
+                "lemma": "dog",
+                "part_of_speech": "noun",
+                "relationships": {
+                  "derivationally related form": [
+                    "dog"
+                  ],
+                  "hypernym": [
+                    "canid",
+                    "canine"
+                  ],
+                  "part meronym": [
+                    "paw"
+                  ]
+                },
+                "synonyms": [
+                  "domestic_dog"
+                ]
+              },
+              {
+                "antonyms": [],
+                "definition": "follow closely",
+                "examples": [],
+                "lemma": "dog",
+                "part_of_speech": "verb",
+                "relationships": {
+                  "derivationally related form": [
+                    "dog"
+                  ]
+                },
+                "synonyms": []
+              }
+            ]
+
+            {
+              "noun": [
+                "domestic_dog"
+              ]
+            }
+
+            {
+              "noun": [
+                "cat"
+              ]
+            }
+
+            {
+              "noun": [
+                "canid",
+                "canine"
+              ]
+            }
+
+            {
+              "noun": [
+                "dog"
+              ],
+              "verb": [
+                "dog"
+              ]
+            }
+
+            [
+              "dog"
+            ]
+
+            not found"#]];
+        expected.assert_eq(&answers(true));
+    }
+}
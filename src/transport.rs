@@ -0,0 +1,111 @@
+use lsp_server::Connection;
+use lsp_server::Message;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// A connection exchanging messages over a stream, such as a socket.
+///
+/// The threads reading and writing the stream stop once the client disconnects or the connection
+/// is dropped.
+pub fn stream_connection(
+    reader: impl Read + Send + 'static,
+    mut writer: impl Write + Send + 'static,
+) -> Connection {
+    let (server, client) = Connection::memory();
+    let Connection { sender, receiver } = client;
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        while let Ok(Some(message)) = Message::read(&mut reader) {
+            let exit = matches!(
+                &message,
+                Message::Notification(n) if n.method == "exit"
+            );
+            if sender.send(message).is_err() || exit {
+                break;
+            }
+        }
+    });
+    thread::spawn(move || {
+        for message in receiver {
+            if message.write(&mut writer).is_err() {
+                break;
+            }
+        }
+    });
+    server
+}
+
+/// Accept clients on the TCP address, running a session for each one on its own thread.
+pub fn listen_tcp(
+    addr: &str,
+    session: impl Fn(Connection) + Clone + Send + 'static,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let connection = match stream.and_then(|s| Ok(stream_connection(s.try_clone()?, s))) {
+            Ok(connection) => connection,
+            Err(err) => {
+                failed_accept(err);
+                continue;
+            }
+        };
+        let session = session.clone();
+        thread::spawn(move || session(connection));
+    }
+    Ok(())
+}
+
+/// Accept clients on the Unix domain socket, running a session for each one on its own thread.
+///
+/// A socket left behind by a previous server at the path is replaced.
+#[cfg(unix)]
+pub fn listen_unix(
+    path: &Path,
+    session: impl Fn(Connection) + Clone + Send + 'static,
+) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt as _;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    eprintln!("Listening on {}", path.display());
+    for stream in listener.incoming() {
+        let connection = match stream.and_then(|s| Ok(stream_connection(s.try_clone()?, s))) {
+            Ok(connection) => connection,
+            Err(err) => {
+                failed_accept(err);
+                continue;
+            }
+        };
+        let session = session.clone();
+        thread::spawn(move || session(connection));
+    }
+    Ok(())
+}
+
+/// A client that couldn't be accepted (e.g. when out of file descriptors) doesn't stop the server
+/// or its other sessions, but pause briefly so that a persistent error doesn't spin.
+fn failed_accept(err: std::io::Error) {
+    eprintln!("Failed to accept a client: {err}");
+    thread::sleep(Duration::from_millis(100));
+}
+
+#[cfg(not(unix))]
+pub fn listen_unix(
+    _path: &Path,
+    _session: impl Fn(Connection) + Clone + Send + 'static,
+) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Unix domain sockets are not supported on this platform",
+    ))
+}
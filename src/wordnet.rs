@@ -1,23 +1,56 @@
+use buffer::Buffer;
+pub use buffer::Loading;
+pub use builder::SynSetId;
+pub use builder::WordNetBuilder;
 use data::Data;
 use index::Index;
+use lexicon::Lexicon;
+pub use lint::Problem;
+pub use lint::Relation;
+pub use mapping::Change;
+pub use mapping::Diff;
+pub use mapping::Mapped;
+pub use mapping::Mapping;
+pub use mapping::Method;
 pub use pos::PartOfSpeech;
+#[cfg(not(target_arch = "wasm32"))]
 use rayon::prelude::*;
 pub use relation::LexicalRelation;
 pub use relation::SemanticRelation;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
+pub use synset::LexicalRelationship;
+pub use synset::Location;
 pub use synset::SynSet;
+pub use tokenize::word_ranges;
+pub use tokenize::Token;
+pub use wsd::Lesk;
+pub use wsd::Signatures;
 
 use self::lemmatize::Lemmatizer;
 use self::pos::PartsOfSpeech;
 
+mod archive;
+mod buffer;
+mod builder;
+#[cfg(feature = "bundled")]
+mod bundled;
 mod data;
 mod index;
 mod lemmatize;
+mod lexicon;
+mod lint;
+mod mapping;
 mod pos;
 mod relation;
 mod synset;
+mod tagger;
+mod tokenize;
 mod utils;
+mod wsd;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -26,6 +59,8 @@ pub enum Error {
         path: PathBuf,
         error: std::io::Error,
     },
+    #[error("Failed to load lexicon {path:?}: {message}")]
+    Lexicon { path: PathBuf, message: String },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -35,30 +70,105 @@ pub struct WordNet {
     index: Index,
     data: Data,
     lemmatizer: Lemmatizer,
+    lexicon: Lexicon,
 }
 
 impl WordNet {
     pub fn new(dir: &Path) -> Result<Self> {
-        Ok(Self {
-            index: Index::new(dir).map_err(|e| Error::IO {
-                path: dir.to_owned(),
-                error: e,
-            })?,
-            data: Data::new(dir).map_err(|e| Error::IO {
-                path: dir.to_owned(),
-                error: e,
-            })?,
-            lemmatizer: Lemmatizer::new(dir).map_err(|e| Error::IO {
-                path: dir.to_owned(),
+        Self::with_loading(dir, Loading::default())
+    }
+
+    /// Load the database, choosing whether to map the files into memory or read them.
+    ///
+    /// If `dir` is an archive file, the database is read from inside it instead.
+    pub fn with_loading(dir: &Path, loading: Loading) -> Result<Self> {
+        if dir.is_file() {
+            return Self::from_archive(dir);
+        }
+        Self::from_buffers(|name| {
+            File::open(dir.join(name))
+                .and_then(|file| Buffer::open(file, loading))
+                .map_err(|e| Error::IO {
+                    path: dir.to_owned(),
+                    error: e,
+                })
+        })
+    }
+
+    /// Load the database from readers for each of its files, `open` is given the name of each
+    /// file in [`WordNet::file_names`].
+    pub fn from_reader<R: Read>(mut open: impl FnMut(&str) -> std::io::Result<R>) -> Result<Self> {
+        Self::from_buffers(|name| {
+            open(name).and_then(Buffer::read).map_err(|e| Error::IO {
+                path: PathBuf::from(name),
                 error: e,
-            })?,
+            })
+        })
+    }
+
+    /// Load the database from the contents of each of its files, keyed by the names in
+    /// [`WordNet::file_names`].
+    pub fn from_bytes<'a>(files: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> Result<Self> {
+        let files = files.into_iter().collect::<BTreeMap<_, _>>();
+        Self::from_reader(|name| {
+            files.get(name).copied().ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, format!("missing {name}"))
+            })
         })
     }
 
+    /// A database without any words, for running without the files.
+    pub fn empty() -> Self {
+        Self::from_buffers(|_| Ok(Buffer::Owned(Box::default()))).unwrap()
+    }
+
+    /// Names of the files that make up the database.
+    pub fn file_names() -> Vec<String> {
+        let names: [fn(PartOfSpeech) -> String; 3] =
+            [Data::file_name, Index::file_name, Lemmatizer::file_name];
+        names
+            .into_iter()
+            .flat_map(|name| PartOfSpeech::iter().map(name))
+            .collect()
+    }
+
+    fn from_buffers(mut open: impl FnMut(&str) -> Result<Buffer>) -> Result<Self> {
+        let mut load =
+            |name: fn(PartOfSpeech) -> String| PartsOfSpeech::try_with(|pos| open(&name(pos)));
+        Ok(Self {
+            index: Index::new(load(Index::file_name)?),
+            data: Data::new(load(Data::file_name)?),
+            lemmatizer: Lemmatizer::new(load(Lemmatizer::file_name)?),
+            lexicon: Lexicon::default(),
+        })
+    }
+
+    /// Merge the entries of a user lexicon file (TOML or JSON) into the results of queries.
+    pub fn add_lexicon(&mut self, path: &Path) -> Result<()> {
+        let error = |message| Error::Lexicon {
+            path: path.to_owned(),
+            message,
+        };
+        let entries = lexicon::read_entries(path).map_err(error)?;
+        let index = &self.index;
+        self.lexicon
+            .extend(entries, &|word, pos, sense| {
+                let item = index.load(word);
+                item.get(pos)
+                    .as_ref()
+                    .and_then(|item| item.syn_offsets.get(sense - 1).copied())
+            })
+            .map_err(error)
+    }
+
     pub fn contains(&self, word: &str) -> bool {
         PartOfSpeech::variants()
             .into_iter()
-            .any(|pos| self.index.contains(word, pos))
+            .any(|pos| self.is_lemma(word, pos))
+    }
+
+    fn is_lemma(&self, word: &str, pos: PartOfSpeech) -> bool {
+        self.index.contains(word, pos) || self.lexicon.contains(word, pos)
     }
 
     /// Directly resolve a reference, this should only be used with part_of_speech, offset pairs
@@ -69,19 +179,27 @@ impl WordNet {
 
     pub fn all_words(&self) -> Vec<String> {
         let mut result = Vec::new();
+        // there are no threads to run on in the browser
+        #[cfg(not(target_arch = "wasm32"))]
         result.par_extend(
             PartOfSpeech::variants()
                 .into_par_iter()
                 .flat_map(|pos| self.index.words_for(pos)),
         );
+        #[cfg(target_arch = "wasm32")]
+        result.extend(PartOfSpeech::iter().flat_map(|pos| self.index.words_for(pos)));
+        result.extend(self.lexicon.words().cloned());
+        #[cfg(not(target_arch = "wasm32"))]
         result.par_sort_unstable();
+        #[cfg(target_arch = "wasm32")]
+        result.sort_unstable();
         result.dedup();
         result
     }
 
     pub fn lemmatize(&self, word: &str) -> PartsOfSpeech<Vec<String>> {
         PartsOfSpeech::with(|pos| {
-            let mut lemmas = self.lemmatizer.lemmatize(word, pos, &self.index);
+            let mut lemmas = self.lemmatize_for(word, pos);
             lemmas.sort_unstable();
             lemmas.dedup();
             lemmas
@@ -89,22 +207,25 @@ impl WordNet {
     }
 
     pub fn lemmatize_for(&self, word: &str, pos: PartOfSpeech) -> Vec<String> {
-        self.lemmatizer.lemmatize(word, pos, &self.index)
+        self.lemmatizer
+            .lemmatize(word, pos, &|w| self.is_lemma(w, pos))
     }
 
     pub fn synsets(&self, word: &str) -> PartsOfSpeech<Vec<SynSet>> {
         let word = word.to_lowercase();
         let items = self.index.load(&word);
 
-        items.map(|_pos, items| {
-            items
+        items.map(|pos, items| {
+            let mut synsets: Vec<_> = items
                 .map(|item| {
                     item.syn_offsets
                         .into_iter()
                         .filter_map(|offset| self.data.load(offset, item.pos))
                         .collect()
                 })
-                .unwrap_or_default()
+                .unwrap_or_default();
+            synsets.extend(self.lexicon.synsets_for(&word, pos));
+            synsets
         })
     }
 
@@ -121,6 +242,7 @@ impl WordNet {
                 }
             }
         }
+        synsets.extend(self.lexicon.synsets_for(&word, pos));
 
         synsets
     }
@@ -154813,4 +154935,79 @@ mod tests {
         let expected = expect![];
         expected.assert_debug_eq(&wn);
     }
+
+    #[test]
+    fn from_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = WordNetBuilder::new();
+        builder.add_synset(PartOfSpeech::Noun, &["goose"], "a web-footed bird", &[]);
+        builder.add_exception(PartOfSpeech::Noun, "geese", "goose");
+        builder.write(dir.path()).unwrap();
+
+        let files = WordNet::file_names()
+            .into_iter()
+            .map(|name| (name.clone(), std::fs::read(dir.path().join(name)).unwrap()))
+            .collect::<Vec<_>>();
+        let wn =
+            WordNet::from_bytes(files.iter().map(|(n, b)| (n.as_str(), b.as_slice()))).unwrap();
+        let missing =
+            WordNet::from_bytes(files[1..].iter().map(|(n, b)| (n.as_str(), b.as_slice())));
+        let expected = expect![[r#"
+            (
+                [
+                    "goose",
+                ],
+                "a web-footed bird",
+                Err(
+                    IO {
+                        path: "data.noun",
+                        error: Custom {
+                            kind: NotFound,
+                            error: "missing data.noun",
+                        },
+                    },
+                ),
+            )
+        "#]];
+        expected.assert_debug_eq(&(
+            wn.lemmatize_for("geese", PartOfSpeech::Noun),
+            wn.synsets_for("goose", PartOfSpeech::Noun)[0]
+                .definition
+                .clone(),
+            missing.map(|_| ()),
+        ));
+    }
+
+    #[test]
+    fn empty() {
+        let wn = WordNet::empty();
+        let expected = expect![[r#"
+            (
+                PartsOfSpeech {
+                    noun: [],
+                    verb: [],
+                    adjective: [],
+                    adverb: [],
+                },
+                PartsOfSpeech {
+                    noun: [],
+                    verb: [],
+                    adjective: [],
+                    adverb: [],
+                },
+                0,
+                [
+                    None,
+                    None,
+                    None,
+                ],
+            )
+        "#]];
+        expected.assert_debug_eq(&(
+            wn.synsets("dog"),
+            wn.lemmatize("dogs"),
+            wn.all_words().len(),
+            wn.tag(&wn.tokenize("the dogs ran")),
+        ));
+    }
 }
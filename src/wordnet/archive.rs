@@ -0,0 +1,169 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+
+use super::{Error, Result, WordNet};
+
+impl WordNet {
+    /// Load the database from a `.tar.gz` (or `.tgz`) or `.zip` archive of the distribution,
+    /// reading the `dict` files inside it into memory.
+    pub fn from_archive(path: &Path) -> Result<Self> {
+        let error = |error| Error::IO {
+            path: path.to_owned(),
+            error,
+        };
+        let name = path.to_string_lossy();
+        let file = File::open(path).map_err(error)?;
+        let files = if name.ends_with(".zip") {
+            read_zip(file)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            read_tar(GzDecoder::new(file))
+        } else if name.ends_with(".tar") {
+            read_tar(file)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected a .tar.gz, .tgz, .tar or .zip archive",
+            ))
+        }
+        .map_err(error)?;
+        Self::from_bytes(files.iter().map(|(n, b)| (n.as_str(), b.as_slice())))
+    }
+}
+
+/// Whether the archive entry is one of the database files, returning its name if so.
+///
+/// Archives have the files under a `dict` directory, possibly inside another directory (e.g.
+/// `WordNet-3.0/dict/data.noun`), or at the top level.
+fn database_file(entry: &str) -> Option<String> {
+    let (dir, name) = entry.rsplit_once('/').unwrap_or(("", entry));
+    let in_dict = dir.is_empty() || dir == "dict" || dir.ends_with("/dict");
+    (in_dict && WordNet::file_names().iter().any(|n| n == name)).then(|| name.to_owned())
+}
+
+fn read_tar(reader: impl Read) -> io::Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut files = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        if let Some(name) = database_file(&path) {
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            files.insert(name, content);
+        }
+    }
+    Ok(files)
+}
+
+fn read_zip(file: File) -> io::Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut files = BTreeMap::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if let Some(name) = database_file(entry.name()) {
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            files.insert(name, content);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use expect_test::expect;
+
+    use super::*;
+    use crate::wordnet::{PartOfSpeech, WordNetBuilder};
+
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = WordNetBuilder::new();
+        builder.add_synset(PartOfSpeech::Noun, &["goose"], "a web-footed bird", &[]);
+        builder.write(&dir.path().join("dict")).unwrap();
+        dir
+    }
+
+    fn definition(wn: &WordNet) -> String {
+        wn.synsets_for("goose", PartOfSpeech::Noun)[0]
+            .definition
+            .clone()
+    }
+
+    #[test]
+    fn tar_gz() {
+        let dir = fixture();
+        let path = dir.path().join("WordNet-3.0.tar.gz");
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        ));
+        builder
+            .append_dir_all("WordNet-3.0/dict", dir.path().join("dict"))
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let wn = WordNet::new(&path).unwrap();
+        expect!["a web-footed bird"].assert_eq(&definition(&wn));
+    }
+
+    #[test]
+    fn zip() {
+        let dir = fixture();
+        let path = dir.path().join("wn3.1.dict.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        for name in WordNet::file_names() {
+            zip.start_file(
+                format!("dict/{name}"),
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(&std::fs::read(dir.path().join("dict").join(&name)).unwrap())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let wn = WordNet::new(&path).unwrap();
+        expect!["a web-footed bird"].assert_eq(&definition(&wn));
+    }
+
+    #[test]
+    fn entries_in_dict() {
+        let names = [
+            "dict/data.noun",
+            "WordNet-3.0/dict/index.verb",
+            "WordNet-3.0/dict/adj.exc",
+            "WordNet-3.0/src/data.noun",
+            "dict/sentidx.vrb",
+            "data.noun",
+        ]
+        .map(database_file);
+        let expected = expect![[r#"
+            [
+                Some(
+                    "data.noun",
+                ),
+                Some(
+                    "index.verb",
+                ),
+                Some(
+                    "adj.exc",
+                ),
+                None,
+                None,
+                Some(
+                    "data.noun",
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&names);
+    }
+}
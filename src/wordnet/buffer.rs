@@ -0,0 +1,112 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    ops::Deref,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use memmap::Mmap;
+
+/// How the database files are loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Loading {
+    /// Map the files into memory, this is fastest to load but the files must not change while
+    /// they are in use. Without memory mapping (e.g. on wasm32) the files are read instead.
+    #[default]
+    Mmap,
+    /// Read the files into owned buffers, safe for files that may change underneath (e.g. on a
+    /// network filesystem).
+    Read,
+}
+
+/// The contents of a database file.
+#[derive(Debug)]
+pub enum Buffer {
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(Mmap),
+    Owned(Box<[u8]>),
+    /// A file embedded in the binary, decompressed when first read.
+    #[cfg(feature = "bundled")]
+    Bundled(super::bundled::Bundled),
+}
+
+impl Buffer {
+    pub fn open(file: File, loading: Loading) -> io::Result<Self> {
+        match loading {
+            // SAFETY: the caller chose to map the file, accepting that it must not be modified
+            // while the database is loaded.
+            #[cfg(not(target_arch = "wasm32"))]
+            Loading::Mmap => Ok(Self::Mapped(unsafe { Mmap::map(&file)? })),
+            _ => Self::read(file),
+        }
+    }
+
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        Ok(Self::Owned(buf.into_boxed_slice()))
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Mapped(map) => map,
+            Self::Owned(buf) => buf,
+            #[cfg(feature = "bundled")]
+            Self::Bundled(bundled) => bundled.bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use crate::wordnet::{PartOfSpeech, WordNet, WordNetBuilder};
+
+    use super::*;
+
+    fn write_fixture(dir: &std::path::Path, definition: &str) {
+        let mut builder = WordNetBuilder::new();
+        builder.add_synset(PartOfSpeech::Noun, &["dog"], definition, &[]);
+        builder.write(dir).unwrap();
+    }
+
+    #[test]
+    fn read_is_unaffected_by_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fixture(dir.path(), "a barking animal");
+        let mapped = WordNet::with_loading(dir.path(), Loading::Mmap).unwrap();
+        let read = WordNet::with_loading(dir.path(), Loading::Read).unwrap();
+        let definition = |wn: &WordNet| {
+            wn.synsets_for("dog", PartOfSpeech::Noun)[0]
+                .definition
+                .clone()
+        };
+        assert_eq!(definition(&mapped), definition(&read));
+
+        // replace the files rather than writing into them, so the mapped database stays valid
+        let replacement = tempfile::tempdir().unwrap();
+        write_fixture(replacement.path(), "a loyal barking animal");
+        for entry in std::fs::read_dir(replacement.path()).unwrap() {
+            let entry = entry.unwrap();
+            std::fs::rename(entry.path(), dir.path().join(entry.file_name())).unwrap();
+        }
+        let expected = expect![[r#"
+            (
+                "a barking animal",
+                "a loyal barking animal",
+            )
+        "#]];
+        expected.assert_debug_eq(&(
+            definition(&read),
+            WordNet::with_loading(dir.path(), Loading::Read)
+                .map(|wn| definition(&wn))
+                .unwrap(),
+        ));
+    }
+}
@@ -0,0 +1,441 @@
+// https://wordnet.princeton.edu/documentation/wndb5wn
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+use super::{
+    pos::PartsOfSpeech,
+    relation::{LexicalRelation, SemanticRelation},
+    PartOfSpeech,
+};
+
+/// Written at the start of every file, in place of the WordNet license, so that none are empty.
+const HEADER: &str = "  1 This file was written by wordnet-ls\n";
+
+/// A synset added to a [`WordNetBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SynSetId {
+    part_of_speech: PartOfSpeech,
+    index: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    lemmas: Vec<String>,
+    definition: String,
+    examples: Vec<String>,
+    pointers: Vec<Pointer>,
+}
+
+#[derive(Debug)]
+struct Pointer {
+    symbol: &'static str,
+    target: SynSetId,
+    /// Lemma numbers (starting from 1) of the source and target, 0 for semantic relations.
+    source_lemma: usize,
+    target_lemma: usize,
+}
+
+/// Build a WordNet database in memory and write it out in the WNDB format that
+/// [`WordNet::new`](super::WordNet::new) reads.
+#[derive(Debug, Default)]
+pub struct WordNetBuilder {
+    synsets: PartsOfSpeech<Vec<Entry>>,
+    /// Base forms of irregular inflections.
+    exceptions: PartsOfSpeech<BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl WordNetBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a synset, lemmas with spaces are stored joined by underscores.
+    ///
+    /// The order synsets are added in is the order of the senses of their lemmas.
+    pub fn add_synset<S: AsRef<str>>(
+        &mut self,
+        part_of_speech: PartOfSpeech,
+        lemmas: &[S],
+        definition: &str,
+        examples: &[S],
+    ) -> SynSetId {
+        let synsets = self.synsets.get_mut(part_of_speech);
+        synsets.push(Entry {
+            lemmas: lemmas
+                .iter()
+                .map(|l| l.as_ref().split_whitespace().collect::<Vec<_>>().join("_"))
+                .collect(),
+            definition: definition.to_owned(),
+            examples: examples.iter().map(|e| e.as_ref().to_owned()).collect(),
+            pointers: Vec::new(),
+        });
+        SynSetId {
+            part_of_speech,
+            index: synsets.len() - 1,
+        }
+    }
+
+    /// Relate one synset to another.
+    pub fn add_relation(&mut self, from: SynSetId, relation: SemanticRelation, to: SynSetId) {
+        self.entry(from).pointers.push(Pointer {
+            symbol: relation.as_str(),
+            target: to,
+            source_lemma: 0,
+            target_lemma: 0,
+        });
+    }
+
+    /// Relate a lemma of one synset to a lemma of another, lemmas are indices into those given
+    /// when adding the synsets.
+    ///
+    /// # Panics
+    ///
+    /// If either lemma is not in its synset.
+    pub fn add_lexical_relation(
+        &mut self,
+        from: SynSetId,
+        from_lemma: usize,
+        relation: LexicalRelation,
+        to: SynSetId,
+        to_lemma: usize,
+    ) {
+        assert!(
+            to_lemma < self.entry(to).lemmas.len(),
+            "lemma {to_lemma} is not in the target synset"
+        );
+        let entry = self.entry(from);
+        assert!(
+            from_lemma < entry.lemmas.len(),
+            "lemma {from_lemma} is not in the source synset"
+        );
+        entry.pointers.push(Pointer {
+            symbol: relation.as_str(),
+            target: to,
+            source_lemma: from_lemma + 1,
+            target_lemma: to_lemma + 1,
+        });
+    }
+
+    /// Add a base form for an irregular inflection, e.g. `geese` to `goose`.
+    pub fn add_exception(&mut self, part_of_speech: PartOfSpeech, inflected: &str, base: &str) {
+        self.exceptions
+            .get_mut(part_of_speech)
+            .entry(inflected.to_lowercase())
+            .or_default()
+            .insert(base.to_lowercase());
+    }
+
+    /// Write the `data.*`, `index.*` and `*.exc` files into the directory, creating it if needed.
+    pub fn write(&self, dir: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let offsets = self.offsets();
+        for pos in PartOfSpeech::iter() {
+            let data = self.data_lines(pos, &offsets).concat();
+            fs::write(dir.join("data").with_extension(pos.as_suffix()), data)?;
+            fs::write(
+                dir.join("index").with_extension(pos.as_suffix()),
+                self.index(pos, &offsets),
+            )?;
+            fs::write(
+                dir.join(pos.as_suffix()).with_extension("exc"),
+                self.exceptions(pos),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, id: SynSetId) -> &mut Entry {
+        &mut self.synsets.get_mut(id.part_of_speech)[id.index]
+    }
+
+    /// Byte offsets of each synset in its data file.
+    ///
+    /// Offsets are written with a fixed width so the length of each line doesn't depend on them.
+    fn offsets(&self) -> PartsOfSpeech<Vec<u64>> {
+        let placeholder = PartsOfSpeech::with(|pos| vec![0; self.synsets.get(pos).len()]);
+        PartsOfSpeech::with(|pos| {
+            let mut offset = HEADER.len() as u64;
+            self.data_lines(pos, &placeholder)
+                .into_iter()
+                .skip(1)
+                .map(|line| {
+                    let start = offset;
+                    offset += line.len() as u64;
+                    start
+                })
+                .collect()
+        })
+    }
+
+    fn data_lines(&self, pos: PartOfSpeech, offsets: &PartsOfSpeech<Vec<u64>>) -> Vec<String> {
+        let mut lines = vec![HEADER.to_owned()];
+        for (i, entry) in self.synsets.get(pos).iter().enumerate() {
+            let mut line = format!(
+                "{:08} {:02} {} {:02x}",
+                offsets.get(pos)[i],
+                lex_filenum(pos),
+                pos.as_str(),
+                entry.lemmas.len()
+            );
+            for lemma in &entry.lemmas {
+                write!(line, " {lemma} {:x}", self.lex_id(pos, i, lemma)).unwrap();
+            }
+            write!(line, " {:03}", entry.pointers.len()).unwrap();
+            for pointer in &entry.pointers {
+                write!(
+                    line,
+                    " {} {:08} {} {:02x}{:02x}",
+                    pointer.symbol,
+                    offsets.get(pointer.target.part_of_speech)[pointer.target.index],
+                    pointer.target.part_of_speech.as_str(),
+                    pointer.source_lemma,
+                    pointer.target_lemma
+                )
+                .unwrap();
+            }
+            if pos == PartOfSpeech::Verb {
+                line.push_str(" 00");
+            }
+            write!(line, " | {}", entry.definition).unwrap();
+            for example in &entry.examples {
+                write!(line, "; \"{example}\"").unwrap();
+            }
+            line.push_str("  \n");
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Distinguishes the senses of a lemma within the lexicographer file, by the number of earlier
+    /// synsets containing it.
+    fn lex_id(&self, pos: PartOfSpeech, index: usize, lemma: &str) -> usize {
+        let earlier = self.synsets.get(pos)[..index]
+            .iter()
+            .filter(|e| e.lemmas.iter().any(|l| l.eq_ignore_ascii_case(lemma)))
+            .count();
+        earlier % 16
+    }
+
+    fn index(&self, pos: PartOfSpeech, offsets: &PartsOfSpeech<Vec<u64>>) -> String {
+        // synsets containing each lemma, in sense order
+        let mut lemmas = BTreeMap::<String, Vec<usize>>::new();
+        for (i, entry) in self.synsets.get(pos).iter().enumerate() {
+            for lemma in &entry.lemmas {
+                let senses = lemmas.entry(lemma.to_lowercase()).or_default();
+                if !senses.contains(&i) {
+                    senses.push(i);
+                }
+            }
+        }
+
+        let mut index = HEADER.to_owned();
+        for (lemma, senses) in lemmas {
+            let symbols = senses
+                .iter()
+                .flat_map(|i| &self.synsets.get(pos)[*i].pointers)
+                .map(|p| p.symbol)
+                .collect::<BTreeSet<_>>();
+            write!(
+                index,
+                "{lemma} {} {} {}",
+                pos.as_str(),
+                senses.len(),
+                symbols.len()
+            )
+            .unwrap();
+            for symbol in symbols {
+                write!(index, " {symbol}").unwrap();
+            }
+            write!(index, " {} 0", senses.len()).unwrap();
+            for i in senses {
+                write!(index, " {:08}", offsets.get(pos)[i]).unwrap();
+            }
+            index.push_str("  \n");
+        }
+        index
+    }
+
+    fn exceptions(&self, pos: PartOfSpeech) -> String {
+        let mut exc = HEADER.to_owned();
+        for (inflected, bases) in self.exceptions.get(pos) {
+            exc.push_str(inflected);
+            for base in bases {
+                write!(exc, " {base}").unwrap();
+            }
+            exc.push('\n');
+        }
+        exc
+    }
+}
+
+/// The lexicographer file synsets are written as being from, the generic one for each part of
+/// speech.
+fn lex_filenum(pos: PartOfSpeech) -> u8 {
+    match pos {
+        PartOfSpeech::Adjective => 0,
+        PartOfSpeech::Adverb => 2,
+        PartOfSpeech::Noun => 3,
+        PartOfSpeech::Verb => 29,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+    use crate::wordnet::WordNet;
+
+    fn fixture() -> WordNetBuilder {
+        let mut builder = WordNetBuilder::new();
+        let animal = builder.add_synset(PartOfSpeech::Noun, &["animal"], "a living organism", &[]);
+        let goose = builder.add_synset(
+            PartOfSpeech::Noun,
+            &["goose"],
+            "web-footed long-necked bird",
+            &["the geese flew south"],
+        );
+        let run = builder.add_synset(
+            PartOfSpeech::Verb,
+            &["run", "go quickly"],
+            "move fast by using your legs",
+            &[],
+        );
+        let fast = builder.add_synset(PartOfSpeech::Adjective, &["fast"], "quick", &[]);
+        let slow = builder.add_synset(PartOfSpeech::Adjective, &["slow"], "not quick", &[]);
+        builder.add_relation(goose, SemanticRelation::Hypernym, animal);
+        builder.add_relation(animal, SemanticRelation::Hyponym, goose);
+        builder.add_lexical_relation(fast, 0, LexicalRelation::Antonym, slow, 0);
+        builder.add_lexical_relation(slow, 0, LexicalRelation::Antonym, fast, 0);
+        builder.add_lexical_relation(run, 0, LexicalRelation::VerbGroup, run, 1);
+        builder.add_exception(PartOfSpeech::Noun, "geese", "goose");
+        builder.add_exception(PartOfSpeech::Verb, "ran", "run");
+        builder
+    }
+
+    #[test]
+    fn written_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fixture().write(dir.path()).unwrap();
+        let files = [
+            "data.noun",
+            "index.noun",
+            "data.verb",
+            "index.adj",
+            "noun.exc",
+        ]
+        .map(|f| format!("{f}:\n{}", fs::read_to_string(dir.path().join(f)).unwrap()))
+        .join("\n");
+        let expected = expect![[r#"
+            data.noun:
+              1 This file was written by wordnet-ls
+            00000040 03 n 01 animal 0 001 ~ 00000110 n 0000 | a living organism  
+            00000110 03 n 01 goose 0 001 @ 00000040 n 0000 | web-footed long-necked bird; "the geese flew south"  
+
+            index.noun:
+              1 This file was written by wordnet-ls
+            animal n 1 1 ~ 1 0 00000040  
+            goose n 1 1 @ 1 0 00000110  
+
+            data.verb:
+              1 This file was written by wordnet-ls
+            00000040 29 v 02 run 0 go_quickly 0 001 $ 00000040 v 0102 00 | move fast by using your legs  
+
+            index.adj:
+              1 This file was written by wordnet-ls
+            fast a 1 1 ! 1 0 00000040  
+            slow a 1 1 ! 1 0 00000096  
+
+            noun.exc:
+              1 This file was written by wordnet-ls
+            geese goose
+        "#]];
+        expected.assert_eq(&files);
+    }
+
+    #[test]
+    fn read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        fixture().write(dir.path()).unwrap();
+        let wn = WordNet::new(dir.path()).unwrap();
+
+        let geese = wn.lemmatize("geese");
+        let goose = wn.synsets_for("goose", PartOfSpeech::Noun).remove(0);
+        let hypernym = &goose.with_relationship(SemanticRelation::Hypernym)[0];
+        let animal = wn.resolve(hypernym.part_of_speech, hypernym.synset_offset);
+        let antonyms = wn.synsets_for("fast", PartOfSpeech::Adjective)[0].lemmas[0].antonyms(&wn);
+        let expected = expect![[r#"
+            (
+                [
+                    "animal",
+                    "fast",
+                    "go_quickly",
+                    "goose",
+                    "run",
+                    "slow",
+                ],
+                PartsOfSpeech {
+                    noun: [
+                        "goose",
+                    ],
+                    verb: [],
+                    adjective: [],
+                    adverb: [],
+                },
+                [
+                    "run",
+                ],
+                [
+                    "the geese flew south",
+                ],
+                Some(
+                    "a living organism",
+                ),
+                [
+                    "slow",
+                ],
+                [
+                    SynSet {
+                        lemmas: [
+                            Lemma {
+                                word: "run",
+                                part_of_speech: Verb,
+                                relationships: [
+                                    LexicalRelationship {
+                                        relation: VerbGroup,
+                                        synset_offset: 40,
+                                        part_of_speech: Verb,
+                                        target: 1,
+                                    },
+                                ],
+                            },
+                            Lemma {
+                                word: "go_quickly",
+                                part_of_speech: Verb,
+                                relationships: [],
+                            },
+                        ],
+                        definition: "move fast by using your legs",
+                        examples: [],
+                        part_of_speech: Verb,
+                        relationships: [],
+                    },
+                ],
+            )
+        "#]];
+        expected.assert_debug_eq(&(
+            wn.all_words(),
+            geese,
+            wn.lemmatize("ran").verb,
+            goose.examples,
+            animal.map(|a| a.definition),
+            antonyms,
+            wn.synsets_for("go_quickly", PartOfSpeech::Verb),
+        ));
+    }
+}
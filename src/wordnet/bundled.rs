@@ -0,0 +1,67 @@
+use std::{io::Read, path::PathBuf, sync::OnceLock};
+
+use flate2::read::GzDecoder;
+
+use super::{buffer::Buffer, Error, Result, WordNet};
+
+/// Gzipped database files embedded by the build script, keyed by their names.
+const FILES: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/bundled.rs"));
+
+/// The decompressed files, in the same order as [`FILES`].
+static DECOMPRESSED: [OnceLock<Box<[u8]>>; FILES.len()] = [const { OnceLock::new() }; FILES.len()];
+
+/// An embedded file, decompressed when it is first read and kept for the rest of the process.
+#[derive(Debug, Clone, Copy)]
+pub struct Bundled(usize);
+
+impl Bundled {
+    pub fn bytes(self) -> &'static [u8] {
+        DECOMPRESSED[self.0].get_or_init(|| {
+            let mut buf = Vec::new();
+            GzDecoder::new(FILES[self.0].1)
+                .read_to_end(&mut buf)
+                .expect("the build script embeds valid gzip files");
+            buf.into_boxed_slice()
+        })
+    }
+}
+
+impl WordNet {
+    /// Load the copy of WordNet embedded in the binary, each file is decompressed once, when it is
+    /// first needed.
+    pub fn bundled() -> Result<Self> {
+        Self::from_buffers(|name| {
+            FILES
+                .iter()
+                .position(|(n, _)| *n == name)
+                .map(|i| Buffer::Bundled(Bundled(i)))
+                .ok_or_else(|| Error::IO {
+                    path: PathBuf::from(name),
+                    error: std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("missing {name}"),
+                    ),
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wordnet::PartOfSpeech;
+
+    #[test]
+    fn bundles_every_file() {
+        let names = FILES.iter().map(|(n, _)| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, WordNet::file_names());
+        let wordnet = WordNet::bundled().unwrap();
+        let decompressed = |name: &str| {
+            let i = FILES.iter().position(|(n, _)| *n == name).unwrap();
+            DECOMPRESSED[i].get().is_some()
+        };
+        wordnet.synsets_for("dog", PartOfSpeech::Noun);
+        assert!(decompressed("index.noun"));
+        assert!(!decompressed("data.verb"));
+    }
+}
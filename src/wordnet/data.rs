@@ -1,3 +1,4 @@
+use super::buffer::Buffer;
 use super::pos::PartOfSpeech;
 use super::pos::PartsOfSpeech;
 use super::relation::LexicalRelation;
@@ -6,34 +7,53 @@ use super::synset::Lemma;
 use super::synset::LexicalRelationship;
 use super::synset::SemanticRelationship;
 use super::synset::SynSet;
-use memmap::Mmap;
-use std::fs::File;
 use std::io::BufRead as _;
-use std::path::Path;
 
 #[derive(Debug)]
 pub struct Data {
-    maps: PartsOfSpeech<Mmap>,
+    maps: PartsOfSpeech<Buffer>,
 }
 
 impl Data {
-    pub fn new(dir: &Path) -> std::io::Result<Self> {
-        let maps = PartsOfSpeech::try_with(|pos| unsafe { Mmap::map(&Self::get_file(dir, pos)?) })?;
-        Ok(Self { maps })
+    pub fn new(maps: PartsOfSpeech<Buffer>) -> Self {
+        Self { maps }
+    }
+
+    /// Name of the file for the part of speech in the database directory.
+    pub fn file_name(pos: PartOfSpeech) -> String {
+        format!("data.{}", pos.as_suffix())
     }
 
     /// Load a synset from the given offset in a particular part of speech file.
     pub(super) fn load(&self, offset: u64, pos: PartOfSpeech) -> Option<SynSet> {
+        SynSet::from_parts(self.line(offset, pos)?.split_whitespace())
+    }
+
+    /// The raw line for the synset at the offset.
+    pub(super) fn line(&self, offset: u64, pos: PartOfSpeech) -> Option<String> {
         let map = self.maps.get(pos);
         let mut line = String::new();
-        (&map[offset as usize..]).read_line(&mut line).ok()?;
+        map.get(offset as usize..)?.read_line(&mut line).ok()?;
 
-        SynSet::from_parts(line.split_whitespace())
+        // the offset must be the start of the synset's line
+        if line.split_whitespace().next()?.parse::<u64>().ok()? != offset {
+            return None;
+        }
+        Some(line)
     }
 
-    fn get_file(dir: &Path, pos: PartOfSpeech) -> std::io::Result<File> {
-        let p = dir.join("data").with_extension(pos.as_suffix());
-        File::open(p)
+    /// Offsets of every synset line in the file for the part of speech.
+    pub(super) fn offsets(&self, pos: PartOfSpeech) -> Vec<u64> {
+        let map = self.maps.get(pos);
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        for line in map.split(|b| *b == b'\n') {
+            if !line.is_empty() && !line.starts_with(b"  ") {
+                offsets.push(offset);
+            }
+            offset += line.len() as u64 + 1;
+        }
+        offsets
     }
 }
 
@@ -83,12 +103,15 @@ impl SynSet {
                 let (source, target) = source_target.split_at(2);
                 let source = usize::from_str_radix(source, 16).ok()?;
                 let target = usize::from_str_radix(target, 16).ok()?;
-                lemmas[source - 1].relationships.push(LexicalRelationship {
-                    relation: pointer_type,
-                    synset_offset,
-                    part_of_speech,
-                    target: target - 1,
-                })
+                lemmas
+                    .get_mut(source.checked_sub(1)?)?
+                    .relationships
+                    .push(LexicalRelationship {
+                        relation: pointer_type,
+                        synset_offset,
+                        part_of_speech,
+                        target: target.checked_sub(1)?,
+                    })
             };
         }
 
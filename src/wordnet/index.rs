@@ -1,14 +1,11 @@
-use memmap::Mmap;
-
+use super::buffer::Buffer;
 use super::pos::{PartOfSpeech, PartsOfSpeech};
 use super::utils;
-use std::fs::File;
 use std::io::BufRead;
-use std::path::Path;
 
 #[derive(Debug)]
 pub struct Index {
-    maps: PartsOfSpeech<Mmap>,
+    maps: PartsOfSpeech<Buffer>,
 }
 
 #[derive(Debug)]
@@ -18,9 +15,13 @@ pub struct IndexItem {
 }
 
 impl Index {
-    pub fn new(dir: &Path) -> std::io::Result<Self> {
-        let maps = PartsOfSpeech::try_with(|pos| unsafe { Mmap::map(&Self::get_file(dir, pos)?) })?;
-        Ok(Index { maps })
+    pub fn new(maps: PartsOfSpeech<Buffer>) -> Self {
+        Self { maps }
+    }
+
+    /// Name of the file for the part of speech in the database directory.
+    pub fn file_name(pos: PartOfSpeech) -> String {
+        format!("index.{}", pos.as_suffix())
     }
 
     pub fn load(&self, word: &str) -> PartsOfSpeech<Option<IndexItem>> {
@@ -31,9 +32,9 @@ impl Index {
         self.search(pos, word).is_some()
     }
 
-    fn get_file(dir: &Path, pos: PartOfSpeech) -> std::io::Result<File> {
-        let p = dir.join("index").with_extension(pos.as_suffix());
-        File::open(p)
+    /// Whether any word for the part of speech starts with the prefix.
+    pub fn contains_prefix(&self, prefix: &str, pos: PartOfSpeech) -> bool {
+        utils::binary_search_file_prefix(self.maps.get(pos), prefix)
     }
 
     fn search(&self, pos: PartOfSpeech, word: &str) -> Option<IndexItem> {
@@ -42,6 +43,15 @@ impl Index {
         IndexItem::try_from_parts(line.split_whitespace())
     }
 
+    /// Every entry line in the file for the part of speech.
+    pub fn lines_for(&self, pos: PartOfSpeech) -> impl Iterator<Item = String> + '_ {
+        self.maps
+            .get(pos)
+            .lines()
+            .map_while(|l| l.ok())
+            .filter(|l| !l.starts_with("  ") && !l.is_empty())
+    }
+
     pub fn words_for(&self, pos: PartOfSpeech) -> Vec<String> {
         let map = self.maps.get(pos);
         let mut results = Vec::new();
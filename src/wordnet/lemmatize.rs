@@ -1,35 +1,35 @@
 // https://wordnet.princeton.edu/documentation/morphy7wn
 
-use std::{fs::File, path::Path};
-
-use memmap::Mmap;
-
-use super::{index::Index, pos::PartsOfSpeech, utils, PartOfSpeech};
+use super::{buffer::Buffer, pos::PartsOfSpeech, utils, PartOfSpeech};
 
 #[derive(Debug)]
 pub struct Lemmatizer {
-    maps: PartsOfSpeech<Mmap>,
+    maps: PartsOfSpeech<Buffer>,
 }
 
 impl Lemmatizer {
-    pub fn new(dir: &Path) -> std::io::Result<Self> {
-        let maps = PartsOfSpeech::try_with(|pos| unsafe { Mmap::map(&Self::get_file(dir, pos)?) })?;
-        Ok(Self { maps })
+    pub fn new(maps: PartsOfSpeech<Buffer>) -> Self {
+        Self { maps }
     }
 
-    fn get_file(dir: &Path, pos: PartOfSpeech) -> std::io::Result<File> {
-        let p = dir.join(pos.as_suffix()).with_extension("exc");
-        File::open(p)
+    /// Name of the file for the part of speech in the database directory.
+    pub fn file_name(pos: PartOfSpeech) -> String {
+        format!("{}.exc", pos.as_suffix())
     }
 
-    fn exceptions_for(&self, index: &Index, word: &str, pos: PartOfSpeech) -> Vec<String> {
+    fn exceptions_for(
+        &self,
+        word: &str,
+        pos: PartOfSpeech,
+        is_lemma: &dyn Fn(&str) -> bool,
+    ) -> Vec<String> {
         let map = self.maps.get(pos);
         let mut results = Vec::new();
         if let Some(line) = utils::binary_search_file(map, word) {
             let base_forms = line.split_whitespace().skip(1);
             for base_form in base_forms {
                 // not all base forms exist in word net so don't include them
-                if index.contains(base_form, pos) {
+                if is_lemma(base_form) {
                     results.push(base_form.to_owned());
                 }
             }
@@ -41,19 +41,57 @@ impl Lemmatizer {
         &self,
         word: &str,
         part_of_speech: PartOfSpeech,
-        index: &Index,
+        is_lemma: &dyn Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let lemmas = self.lemmatize_with(word, part_of_speech, is_lemma);
+        if lemmas.is_empty() && word.contains('_') {
+            self.lemmatize_collocation(word, part_of_speech, is_lemma)
+        } else {
+            lemmas
+        }
+    }
+
+    /// Lemmatize each word of a collocation in turn, e.g. `looked_up` to `look_up`.
+    fn lemmatize_collocation(
+        &self,
+        word: &str,
+        part_of_speech: PartOfSpeech,
+        is_lemma: &dyn Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let parts = word.split('_').collect::<Vec<_>>();
+        let mut results = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let join = |base: &str| {
+                let mut parts = parts.clone();
+                parts[i] = base;
+                parts.join("_")
+            };
+            let bases = self.lemmatize_with(part, part_of_speech, &|base| is_lemma(&join(base)));
+            results.extend(bases.iter().map(|base| join(base)));
+        }
+        results.sort_unstable();
+        results.dedup();
+        results
+    }
+
+    /// Apply the rules for the part of speech, keeping the base forms that are lemmas.
+    fn lemmatize_with(
+        &self,
+        word: &str,
+        part_of_speech: PartOfSpeech,
+        is_lemma: &dyn Fn(&str) -> bool,
     ) -> Vec<String> {
         match part_of_speech {
-            PartOfSpeech::Noun => self.lemmatize_noun(word, index),
-            PartOfSpeech::Verb => self.lemmatize_verb(word, index),
-            PartOfSpeech::Adjective => self.lemmatize_adjective(word, index),
-            PartOfSpeech::Adverb => self.lemmatize_adverb(word, index),
+            PartOfSpeech::Noun => self.lemmatize_noun(word, is_lemma),
+            PartOfSpeech::Verb => self.lemmatize_verb(word, is_lemma),
+            PartOfSpeech::Adjective => self.lemmatize_adjective(word, is_lemma),
+            PartOfSpeech::Adverb => self.lemmatize_adverb(word, is_lemma),
         }
     }
 
-    fn lemmatize_noun(&self, word: &str, index: &Index) -> Vec<String> {
-        let mut results = self.exceptions_for(index, word, PartOfSpeech::Noun);
-        if index.contains(word, PartOfSpeech::Noun) {
+    fn lemmatize_noun(&self, word: &str, is_lemma: &dyn Fn(&str) -> bool) -> Vec<String> {
+        let mut results = self.exceptions_for(word, PartOfSpeech::Noun, is_lemma);
+        if is_lemma(word) {
             results.push(word.to_owned());
         }
         let mut search_term = word;
@@ -70,7 +108,7 @@ impl Lemmatizer {
                     if ful_suffix {
                         detached.push_str("ful");
                     }
-                    if index.contains(&detached, PartOfSpeech::Noun) {
+                    if is_lemma(&detached) {
                         results.push(detached);
                     }
                 }
@@ -89,9 +127,9 @@ impl Lemmatizer {
         results
     }
 
-    fn lemmatize_verb(&self, word: &str, index: &Index) -> Vec<String> {
-        let mut results = self.exceptions_for(index, word, PartOfSpeech::Verb);
-        if index.contains(word, PartOfSpeech::Verb) {
+    fn lemmatize_verb(&self, word: &str, is_lemma: &dyn Fn(&str) -> bool) -> Vec<String> {
+        let mut results = self.exceptions_for(word, PartOfSpeech::Verb, is_lemma);
+        if is_lemma(word) {
             results.push(word.to_owned());
         }
         macro_rules! strip_add_search {
@@ -99,7 +137,7 @@ impl Lemmatizer {
                 if let Some(detached) = word.strip_suffix($suffix) {
                     let mut detached = detached.to_owned();
                     detached.push_str($ending);
-                    if index.contains(&detached, PartOfSpeech::Verb) {
+                    if is_lemma(&detached) {
                         results.push(detached);
                     }
                 }
@@ -118,9 +156,9 @@ impl Lemmatizer {
         results
     }
 
-    fn lemmatize_adjective(&self, word: &str, index: &Index) -> Vec<String> {
-        let mut results = self.exceptions_for(index, word, PartOfSpeech::Adjective);
-        if index.contains(word, PartOfSpeech::Adjective) {
+    fn lemmatize_adjective(&self, word: &str, is_lemma: &dyn Fn(&str) -> bool) -> Vec<String> {
+        let mut results = self.exceptions_for(word, PartOfSpeech::Adjective, is_lemma);
+        if is_lemma(word) {
             results.push(word.to_owned());
         }
         macro_rules! strip_add_search {
@@ -128,7 +166,7 @@ impl Lemmatizer {
                 if let Some(detached) = word.strip_suffix($suffix) {
                     let mut detached = detached.to_owned();
                     detached.push_str($ending);
-                    if index.contains(&detached, PartOfSpeech::Adjective) {
+                    if is_lemma(&detached) {
                         results.push(detached);
                     }
                 }
@@ -143,9 +181,9 @@ impl Lemmatizer {
         results
     }
 
-    fn lemmatize_adverb(&self, word: &str, index: &Index) -> Vec<String> {
-        let mut results = self.exceptions_for(index, word, PartOfSpeech::Adverb);
-        if index.contains(word, PartOfSpeech::Adverb) {
+    fn lemmatize_adverb(&self, word: &str, is_lemma: &dyn Fn(&str) -> bool) -> Vec<String> {
+        let mut results = self.exceptions_for(word, PartOfSpeech::Adverb, is_lemma);
+        if is_lemma(word) {
             results.push(word.to_owned());
         }
         results.sort_unstable();
@@ -161,12 +199,12 @@ mod tests {
     use expect_test::{expect, Expect};
 
     use super::*;
+    use crate::wordnet::WordNet;
 
     fn check(word: &str, pos: PartOfSpeech, expected: Expect) {
         let wndir = PathBuf::from(env::var("WNSEARCHDIR").unwrap());
-        let index = Index::new(&wndir).unwrap();
-        let lemmatizer = Lemmatizer::new(&wndir).unwrap();
-        let shortened = lemmatizer.lemmatize(word, pos, &index);
+        let wn = WordNet::new(&wndir).unwrap();
+        let shortened = wn.lemmatize_for(word, pos);
         expected.assert_debug_eq(&shortened);
     }
 
@@ -248,6 +286,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verb_collocation() {
+        check(
+            "looked_up",
+            PartOfSpeech::Verb,
+            expect![[r#"
+                [
+                    "look_up",
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn noun_collocation() {
+        check(
+            "ice_creams",
+            PartOfSpeech::Noun,
+            expect![[r#"
+                [
+                    "ice_cream",
+                ]
+            "#]],
+        );
+    }
+
     #[test]
     fn ful_noun() {
         check(
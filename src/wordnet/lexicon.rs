@@ -0,0 +1,313 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Deserialize;
+
+use super::{
+    pos::PartsOfSpeech,
+    relation::SemanticRelation,
+    synset::{Lemma, SemanticRelationship},
+    PartOfSpeech, SynSet,
+};
+
+/// An entry in a user lexicon file.
+///
+/// ```toml
+/// [[entry]]
+/// word = "angioplasty"
+/// pos = "noun"
+/// definition = "surgical repair of a blood vessel"
+/// examples = ["she had an angioplasty last year"]
+/// synonyms = ["balloon angioplasty"]
+/// hypernyms = ["operation.n.05"]
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Entry {
+    pub word: String,
+    /// Part of speech, either the name (`noun`) or the WordNet letter (`n`).
+    pub pos: String,
+    pub definition: String,
+    #[serde(default)]
+    pub examples: Vec<String>,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    /// WordNet synsets this entry is a kind of, as `word.pos.sense` (e.g. `operation.n.05`).
+    #[serde(default)]
+    pub hypernyms: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LexiconFile {
+    #[serde(default)]
+    entry: Vec<Entry>,
+}
+
+/// Read the entries from a TOML or JSON lexicon file, chosen by its extension.
+pub fn read_entries(path: &Path) -> Result<Vec<Entry>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: LexiconFile = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| e.to_string())?,
+        Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string())?,
+        _ => return Err("expected a .toml or .json file".to_owned()),
+    };
+    Ok(file.entry)
+}
+
+/// Custom synsets supplementing WordNet.
+#[derive(Debug, Default)]
+pub struct Lexicon {
+    synsets: Vec<SynSet>,
+    /// Indices into the synsets for each lemma.
+    words: PartsOfSpeech<BTreeMap<String, Vec<usize>>>,
+}
+
+impl Lexicon {
+    /// Add the entries, `resolve` maps a word, part of speech and sense number (starting from 1) to
+    /// the offset of the synset in WordNet.
+    pub fn extend(
+        &mut self,
+        entries: Vec<Entry>,
+        resolve: &dyn Fn(&str, PartOfSpeech, usize) -> Option<u64>,
+    ) -> Result<(), String> {
+        // check every entry before adding any, so an invalid one leaves the lexicon unchanged
+        let synsets = entries
+            .into_iter()
+            .map(|entry| to_synset(entry, resolve))
+            .collect::<Result<Vec<_>, String>>()?;
+        for synset in synsets {
+            let index = self.synsets.len();
+            for lemma in &synset.lemmas {
+                self.words
+                    .get_mut(synset.part_of_speech)
+                    .entry(lemma.word.to_lowercase())
+                    .or_default()
+                    .push(index);
+            }
+            self.synsets.push(synset);
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, word: &str, pos: PartOfSpeech) -> bool {
+        self.words.get(pos).contains_key(word)
+    }
+
+    /// Whether any word for the part of speech starts with the prefix.
+    pub fn contains_prefix(&self, prefix: &str, pos: PartOfSpeech) -> bool {
+        self.words
+            .get(pos)
+            .range(prefix.to_owned()..)
+            .next()
+            .is_some_and(|(w, _)| w.starts_with(prefix))
+    }
+
+    pub fn synsets_for(&self, word: &str, pos: PartOfSpeech) -> Vec<SynSet> {
+        self.words
+            .get(pos)
+            .get(word)
+            .map(|indices| indices.iter().map(|i| self.synsets[*i].clone()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn sense_count(&self, word: &str, pos: PartOfSpeech) -> usize {
+        self.words
+            .get(pos)
+            .get(word)
+            .map_or(0, |indices| indices.len())
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        PartOfSpeech::iter().flat_map(|pos| self.words.get(pos).keys())
+    }
+}
+
+/// The synset for an entry, checking its part of speech and hypernyms.
+fn to_synset(
+    entry: Entry,
+    resolve: &dyn Fn(&str, PartOfSpeech, usize) -> Option<u64>,
+) -> Result<SynSet, String> {
+    let part_of_speech = parse_part_of_speech(&entry.pos)
+        .ok_or_else(|| format!("unknown part of speech {:?}", entry.pos))?;
+    let relationships = entry
+        .hypernyms
+        .iter()
+        .map(|reference| {
+            let (word, pos, sense) = parse_reference(reference)
+                .ok_or_else(|| format!("invalid hypernym {reference:?}"))?;
+            let offset = resolve(&word, pos, sense)
+                .ok_or_else(|| format!("unknown WordNet synset {reference:?}"))?;
+            Ok(SemanticRelationship {
+                relation: SemanticRelation::Hypernym,
+                synset_offset: offset,
+                part_of_speech: pos,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let lemmas = std::iter::once(&entry.word)
+        .chain(&entry.synonyms)
+        .map(|w| Lemma {
+            word: w.split_whitespace().collect::<Vec<_>>().join("_"),
+            part_of_speech,
+            relationships: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    Ok(SynSet {
+        lemmas,
+        definition: entry.definition,
+        examples: entry.examples,
+        part_of_speech,
+        relationships,
+    })
+}
+
+fn parse_part_of_speech(s: &str) -> Option<PartOfSpeech> {
+    match s {
+        "noun" => Some(PartOfSpeech::Noun),
+        "verb" => Some(PartOfSpeech::Verb),
+        "adjective" | "adj" => Some(PartOfSpeech::Adjective),
+        "adverb" | "adv" => Some(PartOfSpeech::Adverb),
+        s => PartOfSpeech::try_from_str(s),
+    }
+}
+
+/// Parse a synset reference like `operation.n.05`.
+fn parse_reference(reference: &str) -> Option<(String, PartOfSpeech, usize)> {
+    let mut parts = reference.rsplitn(3, '.');
+    let sense = parts.next()?.parse().ok().filter(|s| *s > 0)?;
+    let pos = parse_part_of_speech(parts.next()?)?;
+    let word = parts.next()?.to_lowercase();
+    Some((word, pos, sense))
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn toml_and_json_entries() {
+        let toml: LexiconFile = toml::from_str(
+            r#"
+            [[entry]]
+            word = "angioplasty"
+            pos = "noun"
+            definition = "surgical repair of a blood vessel"
+            synonyms = ["balloon angioplasty"]
+            hypernyms = ["operation.n.05"]
+            "#,
+        )
+        .unwrap();
+        let json: LexiconFile = serde_json::from_str(
+            r#"{"entry": [{"word": "dogfood", "pos": "v", "definition": "use your own product"}]}"#,
+        )
+        .unwrap();
+
+        let mut lexicon = Lexicon::default();
+        lexicon
+            .extend(toml.entry, &|word, pos, sense| {
+                (word == "operation" && pos == PartOfSpeech::Noun && sense == 5).then_some(42)
+            })
+            .unwrap();
+        lexicon.extend(json.entry, &|_, _, _| None).unwrap();
+
+        let words = lexicon.words().collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                "angioplasty",
+                "balloon_angioplasty",
+                "dogfood",
+            ]
+        "#]];
+        expected.assert_debug_eq(&words);
+
+        let expected = expect![[r#"
+            [
+                SynSet {
+                    lemmas: [
+                        Lemma {
+                            word: "angioplasty",
+                            part_of_speech: Noun,
+                            relationships: [],
+                        },
+                        Lemma {
+                            word: "balloon_angioplasty",
+                            part_of_speech: Noun,
+                            relationships: [],
+                        },
+                    ],
+                    definition: "surgical repair of a blood vessel",
+                    examples: [],
+                    part_of_speech: Noun,
+                    relationships: [
+                        SemanticRelationship {
+                            relation: Hypernym,
+                            synset_offset: 42,
+                            part_of_speech: Noun,
+                        },
+                    ],
+                },
+            ]
+        "#]];
+        expected.assert_debug_eq(&lexicon.synsets_for("balloon_angioplasty", PartOfSpeech::Noun));
+        assert!(lexicon.contains_prefix("balloon_", PartOfSpeech::Noun));
+        assert!(!lexicon.contains("dogfood", PartOfSpeech::Noun));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let entry = |pos: &str, hypernym: &str| Entry {
+            word: "word".to_owned(),
+            pos: pos.to_owned(),
+            definition: String::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            hypernyms: vec![hypernym.to_owned()],
+        };
+        let errors = [
+            entry("pronoun", "thing.n.01"),
+            entry("noun", "thing"),
+            entry("noun", "thing.n.0"),
+            entry("noun", "thing.n.01"),
+        ]
+        .into_iter()
+        .map(|e| Lexicon::default().extend(vec![e], &|_, _, _| None))
+        .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                Err(
+                    "unknown part of speech \"pronoun\"",
+                ),
+                Err(
+                    "invalid hypernym \"thing\"",
+                ),
+                Err(
+                    "invalid hypernym \"thing.n.0\"",
+                ),
+                Err(
+                    "unknown WordNet synset \"thing.n.01\"",
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&errors);
+    }
+
+    #[test]
+    fn invalid_entry_adds_nothing() {
+        let entry = |word: &str, pos: &str| Entry {
+            word: word.to_owned(),
+            pos: pos.to_owned(),
+            definition: String::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            hypernyms: Vec::new(),
+        };
+        let mut lexicon = Lexicon::default();
+        let result = lexicon.extend(
+            vec![entry("angioplasty", "noun"), entry("dogfood", "pronoun")],
+            &|_, _, _| None,
+        );
+        assert!(result.is_err());
+        assert_eq!(lexicon.words().count(), 0);
+        assert!(lexicon.synsets.is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use serde::Serialize;
+
+use super::{
+    index::IndexItem, LexicalRelation, Location, PartOfSpeech, SemanticRelation, SynSet, WordNet,
+};
+
+/// Pairs of relations that should always point back at each other.
+const RECIPROCAL_RELATIONS: [(SemanticRelation, SemanticRelation); 5] = [
+    (SemanticRelation::Hypernym, SemanticRelation::Hyponym),
+    (
+        SemanticRelation::InstanceHypernym,
+        SemanticRelation::InstanceHyponym,
+    ),
+    (
+        SemanticRelation::MemberHolonym,
+        SemanticRelation::MemberMeronym,
+    ),
+    (
+        SemanticRelation::SubstanceHolonym,
+        SemanticRelation::SubstanceMeronym,
+    ),
+    (SemanticRelation::PartHolonym, SemanticRelation::PartMeronym),
+];
+
+/// A pointer in a synset or from one of its lemmas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Relation {
+    Semantic(SemanticRelation),
+    Lexical(LexicalRelation),
+}
+
+/// A problem found in a database by [`WordNet::lint`].
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Problem {
+    /// A line in a data file that could not be parsed as a synset.
+    InvalidSynset { synset: Location },
+    /// A pointer to a synset that does not resolve.
+    DanglingPointer {
+        synset: Location,
+        relation: Relation,
+        target: Location,
+    },
+    /// A pointer whose target does not point back with the reciprocal relation.
+    MissingReciprocal {
+        synset: Location,
+        relation: SemanticRelation,
+        target: Location,
+        expected: SemanticRelation,
+    },
+    /// An antonym whose target lemma does not have the source as an antonym.
+    AsymmetricAntonym {
+        synset: Location,
+        lemma: String,
+        target: Location,
+        target_lemma: String,
+    },
+    /// A line in an index file that could not be parsed.
+    InvalidIndexEntry {
+        part_of_speech: PartOfSpeech,
+        line: String,
+    },
+    /// An index entry with an offset that does not resolve to a synset.
+    DanglingIndexOffset {
+        part_of_speech: PartOfSpeech,
+        lemma: String,
+        offset: u64,
+    },
+}
+
+impl WordNet {
+    /// Check every synset and index entry in the database for problems.
+    pub fn lint(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        for pos in PartOfSpeech::iter() {
+            for offset in self.data.offsets(pos) {
+                let synset = Location {
+                    part_of_speech: pos,
+                    offset,
+                };
+                match self.resolve(pos, offset) {
+                    Some(ss) => problems.extend(self.lint_synset(synset, &ss)),
+                    None => problems.push(Problem::InvalidSynset { synset }),
+                }
+            }
+            for line in self.index.lines_for(pos) {
+                problems.extend(self.lint_index_entry(pos, line));
+            }
+        }
+        problems
+    }
+
+    fn lint_synset(&self, synset: Location, ss: &SynSet) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        for r in &ss.relationships {
+            let target = Location {
+                part_of_speech: r.part_of_speech,
+                offset: r.synset_offset,
+            };
+            let Some(target_ss) = self.resolve(r.part_of_speech, r.synset_offset) else {
+                problems.push(Problem::DanglingPointer {
+                    synset,
+                    relation: Relation::Semantic(r.relation),
+                    target,
+                });
+                continue;
+            };
+            let Some(expected) = reciprocal(r.relation) else {
+                continue;
+            };
+            let points_back = target_ss.with_relationship(expected).iter().any(|back| {
+                back.part_of_speech == synset.part_of_speech && back.synset_offset == synset.offset
+            });
+            if !points_back {
+                problems.push(Problem::MissingReciprocal {
+                    synset,
+                    relation: r.relation,
+                    target,
+                    expected,
+                });
+            }
+        }
+
+        for (i, lemma) in ss.lemmas.iter().enumerate() {
+            for r in &lemma.relationships {
+                let target = Location {
+                    part_of_speech: r.part_of_speech,
+                    offset: r.synset_offset,
+                };
+                let target_lemma =
+                    self.resolve(r.part_of_speech, r.synset_offset)
+                        .and_then(|mut ss| {
+                            (r.target < ss.lemmas.len()).then(|| ss.lemmas.remove(r.target))
+                        });
+                let Some(target_lemma) = target_lemma else {
+                    problems.push(Problem::DanglingPointer {
+                        synset,
+                        relation: Relation::Lexical(r.relation),
+                        target,
+                    });
+                    continue;
+                };
+                if r.relation != LexicalRelation::Antonym {
+                    continue;
+                }
+                let symmetric = target_lemma
+                    .with_relationship(LexicalRelation::Antonym)
+                    .iter()
+                    .any(|back| {
+                        back.part_of_speech == synset.part_of_speech
+                            && back.synset_offset == synset.offset
+                            && back.target == i
+                    });
+                if !symmetric {
+                    problems.push(Problem::AsymmetricAntonym {
+                        synset,
+                        lemma: lemma.word.clone(),
+                        target,
+                        target_lemma: target_lemma.word,
+                    });
+                }
+            }
+        }
+        problems
+    }
+
+    fn lint_index_entry(&self, pos: PartOfSpeech, line: String) -> Vec<Problem> {
+        let Some(item) = IndexItem::try_from_parts(line.split_whitespace()) else {
+            return vec![Problem::InvalidIndexEntry {
+                part_of_speech: pos,
+                line,
+            }];
+        };
+        let lemma = line.split_whitespace().next().unwrap_or_default();
+        item.syn_offsets
+            .into_iter()
+            .filter(|offset| self.resolve(item.pos, *offset).is_none())
+            .map(|offset| Problem::DanglingIndexOffset {
+                part_of_speech: pos,
+                lemma: lemma.to_owned(),
+                offset,
+            })
+            .collect()
+    }
+}
+
+fn reciprocal(relation: SemanticRelation) -> Option<SemanticRelation> {
+    RECIPROCAL_RELATIONS.iter().find_map(|(a, b)| {
+        if *a == relation {
+            Some(*b)
+        } else if *b == relation {
+            Some(*a)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write as _;
+
+    use expect_test::expect;
+
+    use super::*;
+    use crate::wordnet::WordNetBuilder;
+
+    #[test]
+    fn lint_problems() {
+        let mut builder = WordNetBuilder::new();
+        let animal = builder.add_synset(PartOfSpeech::Noun, &["animal"], "a living thing", &[]);
+        let dog = builder.add_synset(PartOfSpeech::Noun, &["dog"], "a barking animal", &[]);
+        let cat = builder.add_synset(PartOfSpeech::Noun, &["cat"], "a purring animal", &[]);
+        builder.add_relation(dog, SemanticRelation::Hypernym, animal);
+        builder.add_relation(animal, SemanticRelation::Hyponym, dog);
+        builder.add_relation(cat, SemanticRelation::Hypernym, animal);
+        let hot = builder.add_synset(PartOfSpeech::Adjective, &["hot"], "high temperature", &[]);
+        let cold = builder.add_synset(PartOfSpeech::Adjective, &["cold"], "low temperature", &[]);
+        builder.add_lexical_relation(hot, 0, LexicalRelation::Antonym, cold, 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        builder.write(dir.path()).unwrap();
+        let append = |file: &str, line: &str| {
+            let mut f = OpenOptions::new()
+                .append(true)
+                .open(dir.path().join(file))
+                .unwrap();
+            writeln!(f, "{line}").unwrap();
+        };
+        append(
+            "data.verb",
+            "00000040 29 v 01 bark 0 001 @ 99999999 v 0000 00 | make a barking sound",
+        );
+        append("data.verb", "garbage");
+        append("index.verb", "bark v 1 1 @ 1 0 00000040 00000123");
+        append("index.verb", "zzz v one");
+
+        let wn = WordNet::new(dir.path()).unwrap();
+        let problems = wn
+            .lint()
+            .iter()
+            .map(|p| serde_json::to_string(p).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let expected = expect![[r#"
+            {"kind":"missing_reciprocal","synset":{"part_of_speech":"noun","offset":173},"relation":"hypernym","target":{"part_of_speech":"noun","offset":40},"expected":"hyponym"}
+            {"kind":"dangling_pointer","synset":{"part_of_speech":"verb","offset":40},"relation":"hypernym","target":{"part_of_speech":"verb","offset":99999999}}
+            {"kind":"invalid_synset","synset":{"part_of_speech":"verb","offset":112}}
+            {"kind":"dangling_index_offset","part_of_speech":"verb","lemma":"bark","offset":123}
+            {"kind":"invalid_index_entry","part_of_speech":"verb","line":"zzz v one"}
+            {"kind":"asymmetric_antonym","synset":{"part_of_speech":"adjective","offset":40},"lemma":"hot","target":{"part_of_speech":"adjective","offset":106},"target_lemma":"cold"}"#]];
+        expected.assert_eq(&problems);
+    }
+}
@@ -0,0 +1,463 @@
+// https://wordnet.princeton.edu/documentation/senseidx5wn
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use super::{wsd, Location, PartOfSpeech, SemanticRelation, SynSet, WordNet};
+
+/// Lowest score for a synset to be mapped by its lemmas and gloss.
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// How a synset was mapped to the other database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    /// The synsets share sense keys.
+    SenseKey,
+    /// The synsets have similar lemmas and glosses.
+    Similarity,
+}
+
+/// Where a synset is in the other database.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Mapped {
+    pub from: Location,
+    pub to: Location,
+    /// From 0 to 1, how sure the mapping is.
+    pub confidence: f32,
+    pub method: Method,
+}
+
+/// A synset that is in both databases but has changed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub from: Location,
+    pub to: Location,
+    pub added_lemmas: Vec<String>,
+    pub removed_lemmas: Vec<String>,
+    /// The new definition, if it changed.
+    pub definition: Option<String>,
+}
+
+/// The synsets that differ between two databases.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Diff {
+    pub added: Vec<Location>,
+    pub removed: Vec<Location>,
+    pub changed: Vec<Change>,
+}
+
+/// Map the synsets of one database onto another, such as between releases of WordNet.
+#[derive(Debug, Default)]
+pub struct Mapping {
+    mapped: BTreeMap<Location, Mapped>,
+}
+
+/// A synset with what is used to align it.
+struct Aligned {
+    location: Location,
+    synset: SynSet,
+    sense_keys: BTreeSet<String>,
+}
+
+impl Mapping {
+    /// Align the synsets of `from` with those in `to`, first by sense keys and then by the
+    /// similarity of their lemmas and glosses, where each synset in `to` is matched at most once.
+    pub fn align(from: &WordNet, to: &WordNet) -> Self {
+        let mut mapped = BTreeMap::new();
+        for pos in PartOfSpeech::iter() {
+            let to_synsets = to.aligned(pos);
+            let mut by_key = HashMap::<&str, Vec<usize>>::new();
+            let mut by_definition = HashMap::<&str, Vec<usize>>::new();
+            let mut by_offset = HashMap::new();
+            for (i, aligned) in to_synsets.iter().enumerate() {
+                for key in &aligned.sense_keys {
+                    by_key.entry(key).or_default().push(i);
+                }
+                by_definition
+                    .entry(aligned.synset.definition.as_str())
+                    .or_default()
+                    .push(i);
+                by_offset.insert(aligned.location.offset, i);
+            }
+
+            // synsets without a shared sense key are matched one to one by similarity, the most
+            // similar pairs first, so several old synsets aren't mapped to the same new one
+            let mut taken = BTreeSet::new();
+            let mut candidates = Vec::new();
+            for aligned in from.aligned(pos) {
+                let by_sense_key = aligned
+                    .sense_keys
+                    .iter()
+                    .flat_map(|k| by_key.get(k.as_str()).into_iter().flatten())
+                    .map(|i| (jaccard(&aligned.sense_keys, &to_synsets[*i].sense_keys), *i))
+                    .max_by(|a, b| a.0.total_cmp(&b.0));
+                if let Some((confidence, i)) = by_sense_key {
+                    taken.insert(i);
+                    mapped.insert(
+                        aligned.location,
+                        Mapped {
+                            from: aligned.location,
+                            to: to_synsets[i].location,
+                            confidence,
+                            method: Method::SenseKey,
+                        },
+                    );
+                    continue;
+                }
+                let by_lemma = aligned.synset.lemmas.iter().flat_map(|l| {
+                    let items = to.index.load(&l.word.to_lowercase());
+                    let offsets = items
+                        .get(pos)
+                        .as_ref()
+                        .map(|item| item.syn_offsets.clone())
+                        .unwrap_or_default();
+                    offsets
+                        .into_iter()
+                        .filter_map(|o| by_offset.get(&o).copied())
+                });
+                let by_gloss = by_definition
+                    .get(aligned.synset.definition.as_str())
+                    .into_iter()
+                    .flatten()
+                    .copied();
+                candidates.extend(
+                    by_lemma
+                        .chain(by_gloss)
+                        .map(|i| (similarity(&aligned.synset, &to_synsets[i].synset), i))
+                        .filter(|(score, _)| *score >= MIN_SIMILARITY)
+                        .map(|(score, i)| (score, aligned.location, i)),
+                );
+            }
+
+            candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+            for (confidence, from, i) in candidates {
+                if mapped.contains_key(&from) || !taken.insert(i) {
+                    continue;
+                }
+                mapped.insert(
+                    from,
+                    Mapped {
+                        from,
+                        to: to_synsets[i].location,
+                        confidence,
+                        method: Method::Similarity,
+                    },
+                );
+            }
+        }
+        Self { mapped }
+    }
+
+    pub fn get(&self, location: Location) -> Option<&Mapped> {
+        self.mapped.get(&location)
+    }
+
+    /// Where a stored reference to a synset is in the other database.
+    pub fn rekey(&self, location: Location) -> Option<Location> {
+        self.get(location).map(|m| m.to)
+    }
+
+    /// Every mapped synset, ordered by its location in the first database.
+    pub fn iter(&self) -> impl Iterator<Item = &Mapped> {
+        self.mapped.values()
+    }
+
+    /// The synsets added, removed and changed between the databases the mapping was aligned
+    /// with.
+    pub fn diff(&self, from: &WordNet, to: &WordNet) -> Diff {
+        let mut diff = Diff::default();
+        let targets = self.mapped.values().map(|m| m.to).collect::<BTreeSet<_>>();
+        for pos in PartOfSpeech::iter() {
+            for offset in from.data.offsets(pos) {
+                let location = Location {
+                    part_of_speech: pos,
+                    offset,
+                };
+                let Some(mapped) = self.get(location) else {
+                    diff.removed.push(location);
+                    continue;
+                };
+                let (Some(old), Some(new)) = (
+                    from.resolve(pos, offset),
+                    to.resolve(mapped.to.part_of_speech, mapped.to.offset),
+                ) else {
+                    continue;
+                };
+                let old_lemmas = old.synonyms().into_iter().collect::<BTreeSet<_>>();
+                let new_lemmas = new.synonyms().into_iter().collect::<BTreeSet<_>>();
+                let definition = (old.definition != new.definition).then_some(new.definition);
+                if old_lemmas != new_lemmas || definition.is_some() {
+                    diff.changed.push(Change {
+                        from: location,
+                        to: mapped.to,
+                        added_lemmas: new_lemmas.difference(&old_lemmas).cloned().collect(),
+                        removed_lemmas: old_lemmas.difference(&new_lemmas).cloned().collect(),
+                        definition,
+                    });
+                }
+            }
+            diff.added.extend(
+                to.data
+                    .offsets(pos)
+                    .into_iter()
+                    .map(|offset| Location {
+                        part_of_speech: pos,
+                        offset,
+                    })
+                    .filter(|l| !targets.contains(l)),
+            );
+        }
+        diff
+    }
+}
+
+impl WordNet {
+    /// Every synset for the part of speech with its sense keys.
+    fn aligned(&self, pos: PartOfSpeech) -> Vec<Aligned> {
+        self.data
+            .offsets(pos)
+            .into_iter()
+            .filter_map(|offset| {
+                let synset = self.resolve(pos, offset)?;
+                Some(Aligned {
+                    location: Location {
+                        part_of_speech: pos,
+                        offset,
+                    },
+                    sense_keys: self.sense_keys(pos, offset),
+                    synset,
+                })
+            })
+            .collect()
+    }
+
+    /// The sense keys of each lemma in the synset, e.g. `dog%1:05:00::`.
+    fn sense_keys(&self, pos: PartOfSpeech, offset: u64) -> BTreeSet<String> {
+        let Some(line) = self.data.line(offset, pos) else {
+            return BTreeSet::new();
+        };
+        let lemmas = lex_ids(&line);
+        let mut parts = line.split_whitespace().skip(1);
+        let (Some(lex_filenum), Some(ss_type)) = (parts.next(), parts.next()) else {
+            return BTreeSet::new();
+        };
+        // satellites are keyed by the head of their adjective cluster
+        let head = if ss_type == "s" {
+            self.resolve(pos, offset)
+                .into_iter()
+                .flat_map(|ss| {
+                    ss.with_relationship(SemanticRelation::SimilarTo)
+                        .into_iter()
+                        .next()
+                        .map(|r| (r.part_of_speech, r.synset_offset))
+                })
+                .filter_map(|(pos, offset)| self.data.line(offset, pos))
+                .find_map(|line| lex_ids(&line).into_iter().next())
+                .map(|(word, lex_id)| format!("{word}:{lex_id:02}"))
+        } else {
+            None
+        };
+        let ss_type = match ss_type {
+            "n" => 1,
+            "v" => 2,
+            "a" => 3,
+            "r" => 4,
+            _ => 5,
+        };
+        lemmas
+            .into_iter()
+            .map(|(word, lex_id)| {
+                format!(
+                    "{word}%{ss_type}:{lex_filenum}:{lex_id:02}:{}",
+                    head.as_deref().unwrap_or(":")
+                )
+            })
+            .collect()
+    }
+}
+
+/// The lowercase lemmas of a synset line with their lex ids, without adjective markers.
+fn lex_ids(line: &str) -> Vec<(String, u8)> {
+    let mut parts = line.split_whitespace().skip(3);
+    let Some(count) = parts.next().and_then(|c| usize::from_str_radix(c, 16).ok()) else {
+        return Vec::new();
+    };
+    let mut lemmas = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (Some(word), Some(lex_id)) = (parts.next(), parts.next()) else {
+            break;
+        };
+        let word = word.split('(').next().unwrap_or(word).to_lowercase();
+        lemmas.push((word, u8::from_str_radix(lex_id, 16).unwrap_or_default()));
+    }
+    lemmas
+}
+
+/// How alike two synsets are, by the average overlap of their lemmas and gloss words.
+fn similarity(a: &SynSet, b: &SynSet) -> f32 {
+    let lemmas = |ss: &SynSet| {
+        ss.lemmas
+            .iter()
+            .map(|l| l.word.to_lowercase())
+            .collect::<BTreeSet<_>>()
+    };
+    let gloss = |ss: &SynSet| {
+        wsd::tokens(&ss.definition)
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+    };
+    (jaccard(&lemmas(a), &lemmas(b)) + jaccard(&gloss(a), &gloss(b))) / 2.0
+}
+
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+    use crate::wordnet::WordNetBuilder;
+
+    #[test]
+    fn align_versions() {
+        let mut old = WordNetBuilder::new();
+        old.add_synset(
+            PartOfSpeech::Noun,
+            &["dog", "domestic dog"],
+            "a barking animal",
+            &[],
+        );
+        old.add_synset(PartOfSpeech::Noun, &["cat"], "a purring animal", &[]);
+        old.add_synset(
+            PartOfSpeech::Noun,
+            &["colour"],
+            "a visual attribute of things",
+            &[],
+        );
+        old.add_synset(PartOfSpeech::Noun, &["wireless"], "a radio", &[]);
+
+        let mut new = WordNetBuilder::new();
+        new.add_synset(PartOfSpeech::Noun, &["aardvark"], "an anteater", &[]);
+        new.add_synset(
+            PartOfSpeech::Noun,
+            &["dog", "Canis familiaris"],
+            "a domesticated barking animal",
+            &[],
+        );
+        new.add_synset(PartOfSpeech::Noun, &["cat"], "a purring animal", &[]);
+        new.add_synset(
+            PartOfSpeech::Noun,
+            &["color"],
+            "a visual attribute of things",
+            &[],
+        );
+
+        let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        old.write(dirs[0].path()).unwrap();
+        new.write(dirs[1].path()).unwrap();
+        let old = WordNet::new(dirs[0].path()).unwrap();
+        let new = WordNet::new(dirs[1].path()).unwrap();
+
+        let mapping = Mapping::align(&old, &new);
+        let mapped = mapping
+            .iter()
+            .map(|m| serde_json::to_string(m).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let expected = expect![[r#"
+            {"from":{"part_of_speech":"noun","offset":40},"to":{"part_of_speech":"noun","offset":88},"confidence":0.33333334,"method":"sense_key"}
+            {"from":{"part_of_speech":"noun","offset":103},"to":{"part_of_speech":"noun","offset":168},"confidence":1.0,"method":"sense_key"}
+            {"from":{"part_of_speech":"noun","offset":151},"to":{"part_of_speech":"noun","offset":216},"confidence":0.5,"method":"similarity"}"#]];
+        expected.assert_eq(&mapped);
+
+        let diff = serde_json::to_string_pretty(&mapping.diff(&old, &new)).unwrap();
+        let expected = expect![[r#"
+            {
+              "added": [
+                {
+                  "part_of_speech": "noun",
+                  "offset": 40
+                }
+              ],
+              "removed": [
+                {
+                  "part_of_speech": "noun",
+                  "offset": 214
+                }
+              ],
+              "changed": [
+                {
+                  "from": {
+                    "part_of_speech": "noun",
+                    "offset": 40
+                  },
+                  "to": {
+                    "part_of_speech": "noun",
+                    "offset": 88
+                  },
+                  "added_lemmas": [
+                    "Canis_familiaris"
+                  ],
+                  "removed_lemmas": [
+                    "domestic_dog"
+                  ],
+                  "definition": "a domesticated barking animal"
+                },
+                {
+                  "from": {
+                    "part_of_speech": "noun",
+                    "offset": 151
+                  },
+                  "to": {
+                    "part_of_speech": "noun",
+                    "offset": 216
+                  },
+                  "added_lemmas": [
+                    "color"
+                  ],
+                  "removed_lemmas": [
+                    "colour"
+                  ],
+                  "definition": null
+                }
+              ]
+            }"#]];
+        expected.assert_eq(&diff);
+    }
+
+    #[test]
+    fn similar_synsets_map_one_to_one() {
+        let mut old = WordNetBuilder::new();
+        old.add_synset(PartOfSpeech::Noun, &["wireless"], "a radio receiver", &[]);
+        old.add_synset(PartOfSpeech::Noun, &["tranny"], "a radio receiver", &[]);
+
+        let mut new = WordNetBuilder::new();
+        new.add_synset(PartOfSpeech::Noun, &["radio"], "a radio receiver", &[]);
+
+        let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        old.write(dirs[0].path()).unwrap();
+        new.write(dirs[1].path()).unwrap();
+        let old = WordNet::new(dirs[0].path()).unwrap();
+        let new = WordNet::new(dirs[1].path()).unwrap();
+
+        let mapping = Mapping::align(&old, &new);
+        let mapped = mapping
+            .iter()
+            .map(|m| serde_json::to_string(m).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let expected = expect![[
+            r#"{"from":{"part_of_speech":"noun","offset":40},"to":{"part_of_speech":"noun","offset":40},"confidence":0.5,"method":"similarity"}"#
+        ]];
+        expected.assert_eq(&mapped);
+        assert_eq!(mapping.diff(&old, &new).removed.len(), 1);
+    }
+}
@@ -1,7 +1,10 @@
 use std::fmt;
 
+use serde::Serialize;
+
 /// PoS represents a part of speech
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PartOfSpeech {
     Noun,
     Verb,
@@ -34,6 +37,16 @@ impl PartOfSpeech {
         }
     }
 
+    /// The letter used for the part of speech in the database files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Noun => "n",
+            PartOfSpeech::Verb => "v",
+            PartOfSpeech::Adjective => "a",
+            PartOfSpeech::Adverb => "r",
+        }
+    }
+
     pub fn try_from_str(s: &str) -> Option<Self> {
         match s {
             "n" => Some(PartOfSpeech::Noun),
@@ -67,6 +80,7 @@ impl PartOfSpeech {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Default)]
 pub struct PartsOfSpeech<T> {
     pub noun: T,
     pub verb: T,
@@ -137,6 +151,15 @@ impl<T> PartsOfSpeech<T> {
         }
     }
 
+    pub fn get_mut(&mut self, pos: PartOfSpeech) -> &mut T {
+        match pos {
+            PartOfSpeech::Noun => &mut self.noun,
+            PartOfSpeech::Verb => &mut self.verb,
+            PartOfSpeech::Adjective => &mut self.adjective,
+            PartOfSpeech::Adverb => &mut self.adverb,
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         [&self.noun, &self.verb, &self.adjective, &self.adverb].into_iter()
     }
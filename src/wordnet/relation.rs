@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SemanticRelation {
     // @    Hypernym
     Hypernym,
@@ -79,6 +82,35 @@ impl SemanticRelation {
             _ => None,
         }
     }
+
+    /// The pointer symbol used for the relation in the database files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SemanticRelation::Hypernym => "@",
+            SemanticRelation::InstanceHypernym => "@i",
+            SemanticRelation::Hyponym => "~",
+            SemanticRelation::InstanceHyponym => "~i",
+            SemanticRelation::MemberHolonym => "#m",
+            SemanticRelation::SubstanceHolonym => "#s",
+            SemanticRelation::PartHolonym => "#p",
+            SemanticRelation::MemberMeronym => "%m",
+            SemanticRelation::SubstanceMeronym => "%s",
+            SemanticRelation::PartMeronym => "%p",
+            SemanticRelation::Attribute => "=",
+            SemanticRelation::DomainOfSynsetTopic => ";c",
+            SemanticRelation::MemberOfThisDomainTopic => "-c",
+            SemanticRelation::DomainOfSynsetRegion => ";r",
+            SemanticRelation::MemberOfThisDomainRegion => "-r",
+            SemanticRelation::DomainOfSynsetUsage => ";u",
+            SemanticRelation::MemberOfThisDomainUsage => "-u",
+            SemanticRelation::Entailment => "*",
+            SemanticRelation::Cause => ">",
+            SemanticRelation::VerbGroup => "$",
+            SemanticRelation::SimilarTo => "&",
+            SemanticRelation::DerivedFromAdjective => "\\",
+            SemanticRelation::AlsoSee => "^",
+        }
+    }
 }
 
 impl Display for SemanticRelation {
@@ -112,7 +144,8 @@ impl Display for SemanticRelation {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LexicalRelation {
     // !    Antonym
     Antonym,
@@ -151,13 +184,31 @@ impl LexicalRelation {
             ";u" => Some(LexicalRelation::DomainOfSynsetUsage),
             ";r" => Some(LexicalRelation::DomainOfSynsetRegion),
             "-r" => Some(LexicalRelation::MemberOfThisDomainRegion),
-            "-u" => Some(LexicalRelation::MemberOfThisDomainRegion),
+            "-u" => Some(LexicalRelation::MemberOfThisDomainUsage),
             "$" => Some(LexicalRelation::VerbGroup),
             "-c" => Some(LexicalRelation::MemberOfThisDomainTopic),
             ";c" => Some(LexicalRelation::DomainOfSynsetTopic),
             _ => None,
         }
     }
+
+    /// The pointer symbol used for the relation in the database files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LexicalRelation::Antonym => "!",
+            LexicalRelation::DerivationallyRelatedForm => "+",
+            LexicalRelation::AlsoSee => "^",
+            LexicalRelation::ParticipleOfVerb => "<",
+            LexicalRelation::Pertainym => "\\",
+            LexicalRelation::DomainOfSynsetUsage => ";u",
+            LexicalRelation::DomainOfSynsetRegion => ";r",
+            LexicalRelation::MemberOfThisDomainRegion => "-r",
+            LexicalRelation::MemberOfThisDomainUsage => "-u",
+            LexicalRelation::VerbGroup => "$",
+            LexicalRelation::MemberOfThisDomainTopic => "-c",
+            LexicalRelation::DomainOfSynsetTopic => ";c",
+        }
+    }
 }
 
 impl Display for LexicalRelation {
@@ -179,3 +230,32 @@ impl Display for LexicalRelation {
         f.write_str(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexical_symbols_round_trip() {
+        let relations = [
+            LexicalRelation::Antonym,
+            LexicalRelation::DerivationallyRelatedForm,
+            LexicalRelation::AlsoSee,
+            LexicalRelation::ParticipleOfVerb,
+            LexicalRelation::Pertainym,
+            LexicalRelation::DomainOfSynsetUsage,
+            LexicalRelation::DomainOfSynsetRegion,
+            LexicalRelation::MemberOfThisDomainRegion,
+            LexicalRelation::MemberOfThisDomainUsage,
+            LexicalRelation::VerbGroup,
+            LexicalRelation::MemberOfThisDomainTopic,
+            LexicalRelation::DomainOfSynsetTopic,
+        ];
+        for relation in relations {
+            assert_eq!(
+                LexicalRelation::try_from_str(relation.as_str()),
+                Some(relation)
+            );
+        }
+    }
+}
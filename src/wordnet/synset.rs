@@ -1,9 +1,19 @@
+use serde::Serialize;
+
 use super::{
     relation::{LexicalRelation, SemanticRelation},
     PartOfSpeech, WordNet,
 };
 
-#[derive(Debug)]
+/// Where a synset is in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Location {
+    pub part_of_speech: PartOfSpeech,
+    /// Offset in data file for the part of speech.
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone)]
 pub struct SynSet {
     /// Lemmas within the synset.
     pub lemmas: Vec<Lemma>,
@@ -17,7 +27,7 @@ pub struct SynSet {
     pub relationships: Vec<SemanticRelationship>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SemanticRelationship {
     /// The kind of relationship to other synsets.
     pub relation: SemanticRelation,
@@ -27,7 +37,7 @@ pub struct SemanticRelationship {
     pub part_of_speech: PartOfSpeech,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LexicalRelationship {
     /// The kind of relationship to other synsets.
     pub relation: LexicalRelation,
@@ -52,7 +62,7 @@ impl SynSet {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Lemma {
     pub word: String,
     pub part_of_speech: PartOfSpeech,
@@ -72,14 +82,10 @@ impl Lemma {
         let mut antonyms = self
             .with_relationship(LexicalRelation::Antonym)
             .iter()
-            .map(|r| {
-                (
-                    r.target,
-                    wn.resolve(r.part_of_speech, r.synset_offset)
-                        .expect("Failed to resolve word from lemma relationship"),
-                )
+            .filter_map(|r| {
+                let mut ss = wn.resolve(r.part_of_speech, r.synset_offset)?;
+                (r.target < ss.lemmas.len()).then(|| ss.lemmas.remove(r.target).word)
             })
-            .map(|(target, mut ss)| ss.lemmas.remove(target).word)
             .collect::<Vec<_>>();
         antonyms.sort_unstable();
         antonyms.dedup();
@@ -0,0 +1,298 @@
+use super::{pos::PartsOfSpeech, PartOfSpeech, Token, WordNet};
+
+const DETERMINERS: [&str; 16] = [
+    "a", "an", "the", "this", "that", "these", "those", "my", "your", "his", "her", "its", "our",
+    "their", "some", "every",
+];
+
+const PREPOSITIONS: [&str; 18] = [
+    "of", "in", "on", "at", "for", "with", "by", "from", "about", "into", "over", "under", "after",
+    "before", "through", "between", "against", "without",
+];
+
+const VERB_PRECEDERS: [&str; 20] = [
+    "to", "will", "would", "can", "could", "shall", "should", "may", "might", "must", "do", "does",
+    "did", "i", "you", "he", "she", "we", "they", "not",
+];
+
+const ADJECTIVE_PRECEDERS: [&str; 8] = [
+    "very", "too", "so", "more", "most", "quite", "rather", "less",
+];
+
+const BE_FORMS: [&str; 10] = [
+    "am", "is", "are", "was", "were", "be", "been", "being", "seem", "seems",
+];
+
+const SUFFIXES: [(&str, PartOfSpeech); 21] = [
+    ("ly", PartOfSpeech::Adverb),
+    ("ing", PartOfSpeech::Verb),
+    ("ed", PartOfSpeech::Verb),
+    ("ize", PartOfSpeech::Verb),
+    ("ise", PartOfSpeech::Verb),
+    ("tion", PartOfSpeech::Noun),
+    ("sion", PartOfSpeech::Noun),
+    ("ness", PartOfSpeech::Noun),
+    ("ment", PartOfSpeech::Noun),
+    ("ity", PartOfSpeech::Noun),
+    ("ism", PartOfSpeech::Noun),
+    ("ship", PartOfSpeech::Noun),
+    ("hood", PartOfSpeech::Noun),
+    ("ous", PartOfSpeech::Adjective),
+    ("ful", PartOfSpeech::Adjective),
+    ("able", PartOfSpeech::Adjective),
+    ("ible", PartOfSpeech::Adjective),
+    ("ive", PartOfSpeech::Adjective),
+    ("ic", PartOfSpeech::Adjective),
+    ("less", PartOfSpeech::Adjective),
+    ("al", PartOfSpeech::Adjective),
+];
+
+/// What is known about a token before tagging it.
+#[derive(Debug)]
+struct Candidate<'a> {
+    /// The token as written, lowercased.
+    text: &'a str,
+    /// The number of senses the token has for each part of speech.
+    senses: PartsOfSpeech<usize>,
+}
+
+impl WordNet {
+    /// Tag each token with its most likely part of speech, tokens that are not in WordNet (such
+    /// as function words) are not tagged.
+    pub fn tag(&self, tokens: &[Token]) -> Vec<Option<PartOfSpeech>> {
+        let texts = tokens.iter().map(|t| t.key()).collect::<Vec<_>>();
+        let candidates = tokens
+            .iter()
+            .zip(&texts)
+            .map(|(token, text)| Candidate {
+                text,
+                senses: PartsOfSpeech::with(|pos| {
+                    token
+                        .lemmas
+                        .get(pos)
+                        .iter()
+                        .map(|lemma| {
+                            let items = self.index.load(lemma);
+                            let senses = items.get(pos).as_ref().map_or(0, |i| i.syn_offsets.len());
+                            senses + self.lexicon.sense_count(lemma, pos)
+                        })
+                        .sum()
+                }),
+            })
+            .collect::<Vec<_>>();
+        tag_candidates(&candidates)
+    }
+}
+
+/// Tag the candidates left to right, scoring each part of speech a token can be by how many senses
+/// it has, its suffix and the words around it.
+fn tag_candidates(candidates: &[Candidate]) -> Vec<Option<PartOfSpeech>> {
+    let mut tags: Vec<Option<PartOfSpeech>> = Vec::with_capacity(candidates.len());
+    for (i, candidate) in candidates.iter().enumerate() {
+        let total = candidate.senses.iter().sum::<usize>();
+        if total == 0 || is_function_word(candidate.text) {
+            tags.push(None);
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|p| (candidates[p].text, tags[p]));
+        let next = candidates.get(i + 1);
+
+        let mut scores = PartsOfSpeech::with(|pos| {
+            let senses = *candidate.senses.get(pos);
+            if senses == 0 {
+                return None;
+            }
+            Some(2.0 * senses as f32 / total as f32)
+        });
+        let mut boost = |pos: PartOfSpeech, amount: f32| {
+            if let Some(score) = scores.get_mut(pos) {
+                *score += amount;
+            }
+        };
+
+        for (suffix, pos) in SUFFIXES {
+            if candidate.text.len() > suffix.len() + 2 && candidate.text.ends_with(suffix) {
+                boost(pos, 2.0);
+                break;
+            }
+        }
+
+        if let Some((text, tag)) = previous {
+            if DETERMINERS.contains(&text) {
+                boost(PartOfSpeech::Noun, 3.0);
+                boost(PartOfSpeech::Adjective, 2.0);
+            } else if PREPOSITIONS.contains(&text) {
+                boost(PartOfSpeech::Noun, 2.0);
+            } else if VERB_PRECEDERS.contains(&text) {
+                boost(PartOfSpeech::Verb, 3.0);
+            } else if ADJECTIVE_PRECEDERS.contains(&text) {
+                boost(PartOfSpeech::Adjective, 3.0);
+                boost(PartOfSpeech::Adverb, 1.0);
+            } else if BE_FORMS.contains(&text) {
+                boost(PartOfSpeech::Adjective, 2.0);
+                boost(PartOfSpeech::Verb, 1.0);
+            }
+            match tag {
+                Some(PartOfSpeech::Adjective) => boost(PartOfSpeech::Noun, 2.0),
+                Some(PartOfSpeech::Noun) => boost(PartOfSpeech::Verb, 1.0),
+                _ => {}
+            }
+        }
+
+        if next.is_some_and(|n| *n.senses.get(PartOfSpeech::Noun) > 0) {
+            boost(PartOfSpeech::Adjective, 1.0);
+        }
+
+        let best = PartOfSpeech::iter()
+            .filter_map(|pos| scores.get(pos).map(|score| (pos, score)))
+            .fold(
+                None,
+                |best: Option<(PartOfSpeech, f32)>, (pos, score)| match best {
+                    Some((_, best_score)) if best_score >= score => best,
+                    _ => Some((pos, score)),
+                },
+            )
+            .map(|(pos, _)| pos);
+        tags.push(best);
+    }
+    tags
+}
+
+/// Words that only shape the sentence, even though WordNet may have senses for some of them (e.g.
+/// `a` as the letter).
+fn is_function_word(text: &str) -> bool {
+    [
+        &DETERMINERS[..],
+        &PREPOSITIONS[..],
+        &VERB_PRECEDERS[..],
+        &ADJECTIVE_PRECEDERS[..],
+        &BE_FORMS[..],
+    ]
+    .iter()
+    .any(|words| words.contains(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use expect_test::expect;
+
+    use super::*;
+
+    fn candidate(text: &str, senses: [usize; 4]) -> Candidate<'_> {
+        Candidate {
+            text,
+            senses: PartsOfSpeech {
+                noun: senses[0],
+                verb: senses[1],
+                adjective: senses[2],
+                adverb: senses[3],
+            },
+        }
+    }
+
+    #[test]
+    fn tag_run_by_context() {
+        let sentences = [
+            vec![
+                candidate("they", [0, 0, 0, 0]),
+                candidate("run", [16, 41, 0, 0]),
+            ],
+            vec![
+                candidate("a", [3, 0, 0, 0]),
+                candidate("long", [1, 2, 12, 2]),
+                candidate("run", [16, 41, 0, 0]),
+            ],
+            vec![
+                candidate("she", [0, 0, 0, 0]),
+                candidate("was", [0, 0, 0, 0]),
+                candidate("very", [0, 0, 2, 2]),
+                candidate("quickly", [0, 0, 0, 2]),
+                candidate("tired", [0, 0, 2, 0]),
+            ],
+        ];
+        let tags = sentences
+            .iter()
+            .map(|s| tag_candidates(s))
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                [
+                    None,
+                    Some(
+                        Verb,
+                    ),
+                ],
+                [
+                    None,
+                    Some(
+                        Adjective,
+                    ),
+                    Some(
+                        Noun,
+                    ),
+                ],
+                [
+                    None,
+                    None,
+                    None,
+                    Some(
+                        Adverb,
+                    ),
+                    Some(
+                        Adjective,
+                    ),
+                ],
+            ]
+        "#]];
+        expected.assert_debug_eq(&tags);
+    }
+
+    #[test]
+    fn tag_sentence() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let tokens = wn.tokenize("the dogs run to the park");
+        let tags = tokens
+            .iter()
+            .map(|t| t.text.clone())
+            .zip(wn.tag(&tokens))
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                (
+                    "the",
+                    None,
+                ),
+                (
+                    "dogs",
+                    Some(
+                        Noun,
+                    ),
+                ),
+                (
+                    "run",
+                    Some(
+                        Verb,
+                    ),
+                ),
+                (
+                    "to",
+                    None,
+                ),
+                (
+                    "the",
+                    None,
+                ),
+                (
+                    "park",
+                    Some(
+                        Noun,
+                    ),
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&tags);
+    }
+}
@@ -0,0 +1,221 @@
+use std::ops::Range;
+
+use super::{pos::PartsOfSpeech, PartOfSpeech, WordNet};
+
+/// The most words in a single collocation in WordNet, e.g.
+/// `american_federation_of_labor_and_congress_of_industrial_organizations`.
+const MAX_COLLOCATION_WORDS: usize = 9;
+
+/// A word or collocation found in some text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// Byte range of the token in the text.
+    pub range: Range<usize>,
+    /// The token as written.
+    pub text: String,
+    /// Lemmas of the token, collocations are joined with underscores.
+    pub lemmas: PartsOfSpeech<Vec<String>>,
+}
+
+impl Token {
+    /// The first lemma for the token, if it is in WordNet.
+    pub fn lemma(&self) -> Option<&str> {
+        self.lemmas.iter().flatten().next().map(|l| l.as_str())
+    }
+
+    /// Whether the token is made up of multiple words.
+    pub fn is_collocation(&self) -> bool {
+        self.text.contains(char::is_whitespace)
+    }
+
+    /// The token in the form used for lookups, e.g. `ice_cream`.
+    pub fn key(&self) -> String {
+        self.text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("_")
+            .to_lowercase()
+    }
+}
+
+/// Byte ranges of the words in the text, without any surrounding punctuation.
+pub fn word_ranges(text: &str) -> Vec<Range<usize>> {
+    let word_char = |c: char| c.is_alphanumeric() || "_-'".contains(c);
+    let mut ranges = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if !word_char(c) {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some((i, c)) = chars.next_if(|(_, c)| word_char(*c)) {
+            end = i + c.len_utf8();
+        }
+        // trim any punctuation around the word
+        let word = text[start..end].trim_matches(|c: char| !c.is_alphanumeric());
+        if !word.is_empty() {
+            let offset = word.as_ptr() as usize - text.as_ptr() as usize;
+            ranges.push(offset..offset + word.len());
+        }
+    }
+    ranges
+}
+
+impl WordNet {
+    /// Split text into tokens, joining words into the longest collocations found in WordNet.
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let words = word_ranges(text);
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            // only words separated by whitespace can form a collocation
+            let mut end = i + 1;
+            while end < words.len()
+                && end - i < MAX_COLLOCATION_WORDS
+                && text[words[end - 1].end..words[end].start]
+                    .chars()
+                    .all(char::is_whitespace)
+            {
+                end += 1;
+            }
+
+            let first = text[words[i].clone()].to_lowercase();
+            let mut collocation = None;
+            if end > i + 1 && self.starts_collocation(&first) {
+                for n in (2..=end - i).rev() {
+                    let joined = words[i..i + n]
+                        .iter()
+                        .map(|r| text[r.clone()].to_lowercase())
+                        .collect::<Vec<_>>()
+                        .join("_");
+                    let lemmas = self.lemmatize(&joined);
+                    if lemmas.any(|l| !l.is_empty()) {
+                        collocation = Some((n, lemmas));
+                        break;
+                    }
+                }
+            }
+
+            let (n, lemmas) = collocation.unwrap_or_else(|| (1, self.lemmatize(&first)));
+            let range = words[i].start..words[i + n - 1].end;
+            tokens.push(Token {
+                text: text[range.clone()].to_owned(),
+                range,
+                lemmas,
+            });
+            i += n;
+        }
+        tokens
+    }
+
+    /// Whether any collocation starts with the word or one of its lemmas.
+    fn starts_collocation(&self, word: &str) -> bool {
+        let has_prefix = |w: &str| {
+            let prefix = format!("{w}_");
+            PartOfSpeech::iter().any(|pos| {
+                self.index.contains_prefix(&prefix, pos)
+                    || self.lexicon.contains_prefix(&prefix, pos)
+            })
+        };
+        has_prefix(word) || self.lemmatize(word).iter().flatten().any(|l| has_prefix(l))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn ranges_of_words() {
+        let text = "A 'runner' runs, well-being isn't it?";
+        let words = word_ranges(text)
+            .into_iter()
+            .map(|r| (r.clone(), &text[r]))
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                (
+                    0..1,
+                    "A",
+                ),
+                (
+                    3..9,
+                    "runner",
+                ),
+                (
+                    11..15,
+                    "runs",
+                ),
+                (
+                    17..27,
+                    "well-being",
+                ),
+                (
+                    28..33,
+                    "isn't",
+                ),
+                (
+                    34..36,
+                    "it",
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&words);
+    }
+
+    #[test]
+    fn tokenize_collocations() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let tokens = wn
+            .tokenize("We looked up ice creams and ran out of time.")
+            .into_iter()
+            .map(|t| (t.range.clone(), t.lemma().map(|l| l.to_owned())))
+            .collect::<Vec<_>>();
+        let expected = expect![[r#"
+            [
+                (
+                    0..2,
+                    None,
+                ),
+                (
+                    3..12,
+                    Some(
+                        "look_up",
+                    ),
+                ),
+                (
+                    13..23,
+                    Some(
+                        "ice_cream",
+                    ),
+                ),
+                (
+                    24..27,
+                    None,
+                ),
+                (
+                    28..35,
+                    Some(
+                        "run_out",
+                    ),
+                ),
+                (
+                    36..38,
+                    None,
+                ),
+                (
+                    39..43,
+                    Some(
+                        "time",
+                    ),
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&tokens);
+    }
+}
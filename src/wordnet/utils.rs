@@ -1,6 +1,23 @@
-use memmap::Mmap;
+pub fn binary_search_file(map: &[u8], word: &str) -> Option<String> {
+    binary_search_file_by(map, |iword| word.cmp(iword))
+}
+
+/// Whether any line in the file starts with a word with the given prefix.
+pub fn binary_search_file_prefix(map: &[u8], prefix: &str) -> bool {
+    binary_search_file_by(map, |iword| {
+        if iword.starts_with(prefix) {
+            std::cmp::Ordering::Equal
+        } else {
+            prefix.cmp(iword)
+        }
+    })
+    .is_some()
+}
 
-pub fn binary_search_file(map: &Mmap, word: &str) -> Option<String> {
+/// Search the sorted lines of the file, `cmp` compares the target against the first word of a
+/// line.
+fn binary_search_file_by(map: &[u8], cmp: impl Fn(&str) -> std::cmp::Ordering) -> Option<String> {
+    // the lines from start to end are whole, the last one may not end with a newline
     let mut start = 0_usize;
     let mut end = map.len();
 
@@ -8,55 +25,82 @@ pub fn binary_search_file(map: &Mmap, word: &str) -> Option<String> {
 
     while start < end {
         iword.clear();
-        let mut mid = (start + end) / 2;
-        // scan forwards to a newline
-        while mid < end && map[mid] != b'\n' {
-            mid += 1;
-        }
-        let line_end = mid;
-        mid -= 1;
-        while mid > start && map[mid] != b'\n' {
-            mid -= 1;
-        }
-        let line_start = mid;
-
-        // mid now points to a newline character so bump it by one to get the start of the line
-        mid += 1;
+        let mid = (start + end) / 2;
+        // scan forwards to a newline, or the end of the file
+        let line_end = map[mid..end]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(end, |i| mid + i);
+        // and backwards to the start of the line
+        let line_start = map[start..mid]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(start, |i| start + i + 1);
 
         // now we extract the word from the line
-        while mid < end && map[mid] != b' ' {
-            iword.push(map[mid] as char);
-            mid += 1;
+        let mut i = line_start;
+        while i < line_end && map[i] != b' ' {
+            iword.push(map[i] as char);
+            i += 1;
         }
-        if mid == end {
+        if i == line_end {
             // gone too far
             end = line_start;
             continue;
         }
         if iword.is_empty() {
             // may have been a license line
-            start = line_end;
+            start = line_end + 1;
             continue;
         }
 
         // and check how this word compares to the one we are searching for
-        match word.cmp(&iword) {
+        match cmp(&iword) {
             std::cmp::Ordering::Less => {
                 end = line_start;
             }
             std::cmp::Ordering::Equal => {
                 // read the rest of the line into iword
-                while map[mid] != b'\n' {
-                    iword.push(map[mid] as char);
-                    mid += 1;
-                }
+                iword.extend(map[i..line_end].iter().map(|b| *b as char));
                 // and return the parsed parts
                 return Some(iword);
             }
             std::cmp::Ordering::Greater => {
-                start = line_end;
+                start = line_end + 1;
             }
         }
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_lines() {
+        let lines = "  1 a license line\nant n 1\nbee n 2\ncat n 3\ndog n 4";
+        for (word, line) in [
+            ("ant", "ant n 1"),
+            ("bee", "bee n 2"),
+            ("cat", "cat n 3"),
+            // the final line doesn't end with a newline
+            ("dog", "dog n 4"),
+        ] {
+            assert_eq!(
+                binary_search_file(lines.as_bytes(), word).as_deref(),
+                Some(line)
+            );
+        }
+        assert_eq!(binary_search_file(lines.as_bytes(), "aardvark"), None);
+        assert_eq!(binary_search_file(lines.as_bytes(), "eel"), None);
+        assert_eq!(
+            binary_search_file(b"dog n 4", "dog").as_deref(),
+            Some("dog n 4")
+        );
+        assert_eq!(binary_search_file(b"\n", "dog"), None);
+        assert_eq!(binary_search_file(b"", "dog"), None);
+        assert!(binary_search_file_prefix(lines.as_bytes(), "do"));
+        assert!(!binary_search_file_prefix(lines.as_bytes(), "ea"));
+    }
+}
@@ -0,0 +1,264 @@
+// https://en.wikipedia.org/wiki/Lesk_algorithm
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use super::{Location, SemanticRelation, SynSet, WordNet};
+
+/// Common words that would otherwise dominate overlaps between glosses.
+const STOP_WORDS: [&str; 48] = [
+    "a", "about", "an", "and", "are", "as", "at", "be", "been", "but", "by", "can", "do", "for",
+    "from", "had", "has", "have", "he", "her", "his", "i", "if", "in", "into", "is", "it", "its",
+    "not", "of", "on", "one", "or", "she", "so", "that", "the", "their", "them", "they", "this",
+    "to", "was", "we", "were", "which", "with", "you",
+];
+
+/// Relations whose glosses are included in the signature of a synset for extended Lesk.
+const EXTENDED_RELATIONS: [SemanticRelation; 10] = [
+    SemanticRelation::Hypernym,
+    SemanticRelation::InstanceHypernym,
+    SemanticRelation::Hyponym,
+    SemanticRelation::MemberHolonym,
+    SemanticRelation::PartHolonym,
+    SemanticRelation::MemberMeronym,
+    SemanticRelation::PartMeronym,
+    SemanticRelation::SimilarTo,
+    SemanticRelation::AlsoSee,
+    SemanticRelation::Attribute,
+];
+
+/// The signatures of related synsets already worked out, kept between uses of [`Lesk`] on the
+/// same database as resolving and lemmatising the glosses of every related synset is slow.
+#[derive(Debug, Default)]
+pub struct Signatures(RwLock<HashMap<Location, Arc<BTreeSet<String>>>>);
+
+/// Word sense disambiguation by the overlap between the context a word is used in and the
+/// glosses of each of its senses.
+#[derive(Debug)]
+pub struct Lesk<'a> {
+    wordnet: &'a WordNet,
+    extended: bool,
+    signatures: Option<&'a Signatures>,
+}
+
+impl<'a> Lesk<'a> {
+    /// Compare the context against the gloss and examples of each sense.
+    pub fn simplified(wordnet: &'a WordNet) -> Self {
+        Self {
+            wordnet,
+            extended: false,
+            signatures: None,
+        }
+    }
+
+    /// Compare the context against the gloss and examples of each sense, as well as those of
+    /// closely related synsets.
+    pub fn extended(wordnet: &'a WordNet) -> Self {
+        Self {
+            wordnet,
+            extended: true,
+            signatures: None,
+        }
+    }
+
+    /// Keep the signatures of related synsets in the cache, reusing those already in it.
+    pub fn with_signatures(mut self, signatures: &'a Signatures) -> Self {
+        self.signatures = Some(signatures);
+        self
+    }
+
+    /// Normalise the words surrounding a target word into the lemmas used for overlaps.
+    pub fn context<S: AsRef<str>>(&self, words: &[S]) -> BTreeSet<String> {
+        words
+            .iter()
+            .flat_map(|w| tokens(w.as_ref()))
+            .flat_map(|t| self.normalise(&t))
+            .collect()
+    }
+
+    /// The set of lemmas describing a synset.
+    pub fn signature(&self, synset: &SynSet) -> BTreeSet<String> {
+        let mut signature = BTreeSet::new();
+        self.extend_signature(&mut signature, synset);
+        if self.extended {
+            for relation in EXTENDED_RELATIONS {
+                for r in synset.with_relationship(relation) {
+                    let location = Location {
+                        part_of_speech: r.part_of_speech,
+                        offset: r.synset_offset,
+                    };
+                    if let Some(related) = self.related_signature(location) {
+                        signature.extend(related.iter().cloned());
+                    }
+                }
+            }
+        }
+        signature
+    }
+
+    /// The number of context lemmas that overlap with the signature of the synset.
+    pub fn score(&self, synset: &SynSet, context: &BTreeSet<String>) -> usize {
+        self.signature(synset).intersection(context).count()
+    }
+
+    /// Order the synsets by their score, most likely first, keeping the original order for ties.
+    pub fn rank(&self, synsets: Vec<SynSet>, context: &BTreeSet<String>) -> Vec<(usize, SynSet)> {
+        let mut ranked = synsets
+            .into_iter()
+            .map(|ss| (self.score(&ss, context), ss))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(a, _), (b, _)| b.cmp(a));
+        ranked
+    }
+
+    /// The signature of the related synset alone, from the cache if there is one.
+    fn related_signature(&self, location: Location) -> Option<Arc<BTreeSet<String>>> {
+        let cached = self
+            .signatures
+            .and_then(|signatures| signatures.0.read().ok()?.get(&location).cloned());
+        if cached.is_some() {
+            return cached;
+        }
+        let related = self
+            .wordnet
+            .resolve(location.part_of_speech, location.offset)?;
+        let mut signature = BTreeSet::new();
+        self.extend_signature(&mut signature, &related);
+        let signature = Arc::new(signature);
+        if let Some(mut signatures) = self.signatures.and_then(|s| s.0.write().ok()) {
+            signatures.insert(location, signature.clone());
+        }
+        Some(signature)
+    }
+
+    fn extend_signature(&self, signature: &mut BTreeSet<String>, synset: &SynSet) {
+        let text = std::iter::once(&synset.definition).chain(&synset.examples);
+        for token in text.flat_map(|t| tokens(t)) {
+            signature.extend(self.normalise(&token));
+        }
+        for lemma in &synset.lemmas {
+            signature.extend(lemma.word.to_lowercase().split('_').map(|s| s.to_owned()));
+        }
+    }
+
+    /// Map a token to its lemmas, falling back to the token itself.
+    fn normalise(&self, token: &str) -> Vec<String> {
+        if STOP_WORDS.contains(&token) {
+            return Vec::new();
+        }
+        let mut lemmas = self
+            .wordnet
+            .lemmatize(token)
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+        if lemmas.is_empty() {
+            lemmas.push(token.to_owned());
+        }
+        lemmas
+    }
+}
+
+/// Split text into lowercase alphanumeric tokens.
+pub(super) fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '-')
+        .map(|t| {
+            t.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|t| t.chars().count() > 1 || t.chars().all(|c| c.is_numeric()))
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use expect_test::expect;
+
+    use super::*;
+    use crate::wordnet::PartOfSpeech;
+    use crate::wordnet::WordNetBuilder;
+
+    #[test]
+    fn tokenise_gloss() {
+        let t = tokens("sloping land (especially the slope beside a body of water); \"they pulled the canoe up on the bank\"");
+        let expected = expect![[r#"
+            [
+                "sloping",
+                "land",
+                "especially",
+                "the",
+                "slope",
+                "beside",
+                "body",
+                "of",
+                "water",
+                "they",
+                "pulled",
+                "the",
+                "canoe",
+                "up",
+                "on",
+                "the",
+                "bank",
+            ]
+        "#]];
+        expected.assert_debug_eq(&t);
+    }
+
+    #[test]
+    fn bank_by_the_river() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let lesk = Lesk::simplified(&wn);
+        let context = lesk.context(&[
+            "we", "sat", "by", "the", "river", "watching", "the", "water",
+        ]);
+        let synsets = wn.synsets_for("bank", PartOfSpeech::Noun);
+        let best = lesk
+            .rank(synsets, &context)
+            .into_iter()
+            .next()
+            .map(|(_, ss)| ss.definition);
+        let expected = expect![[r#"
+            Some(
+                "sloping land (especially the slope beside a body of water)",
+            )
+        "#]];
+        expected.assert_debug_eq(&best);
+    }
+
+    #[test]
+    fn cached_signatures() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = WordNetBuilder::new();
+        let canine = builder.add_synset(PartOfSpeech::Noun, &["canine"], "a carnivore", &[]);
+        let dog = builder.add_synset(PartOfSpeech::Noun, &["dog"], "a pet", &[]);
+        builder.add_relation(dog, SemanticRelation::Hypernym, canine);
+        builder.add_synset(PartOfSpeech::Noun, &["dog"], "a bad person", &[]);
+        builder.write(dir.path()).unwrap();
+        let wn = WordNet::new(dir.path()).unwrap();
+
+        let signatures = Signatures::default();
+        let lesk = Lesk::extended(&wn).with_signatures(&signatures);
+        let context = lesk.context(&["a", "carnivore"]);
+        let rank = || {
+            lesk.rank(wn.synsets_for("dog", PartOfSpeech::Noun), &context)
+                .into_iter()
+                .map(|(score, ss)| (score, ss.definition))
+                .collect::<Vec<_>>()
+        };
+        let uncached = rank();
+        assert_eq!(signatures.0.read().unwrap().len(), 1);
+        assert_eq!(rank(), uncached);
+        assert_eq!(
+            uncached,
+            [(1, "a pet".to_owned()), (0, "a bad person".to_owned())]
+        );
+    }
+}